@@ -0,0 +1,32 @@
+#!/bin/sh
+# Runs bench_instructions_per_second on the commit that introduced the static decode tables and
+# on its parent. The parent has no such bench, so the one from the commit is grafted into the
+# tests of its cpu.rs before running.
+set -eu
+
+commit=${1:-7eb22dd}
+root=$(git rev-parse --show-toplevel)
+work=$(mktemp -d)
+trap 'git -C "$root" worktree remove --force "$work/before" 2>/dev/null || true
+      git -C "$root" worktree remove --force "$work/after" 2>/dev/null || true
+      rm -rf "$work"' EXIT
+
+git -C "$root" worktree add --detach --quiet "$work/before" "$commit~1"
+git -C "$root" worktree add --detach --quiet "$work/after" "$commit"
+
+cpu=src/consoles/gameboy/cpu.rs
+# The bench along with the comment above it, put back before the end of the tests
+awk '/cargo test --release bench_instructions_per_second/ { keep = 1 }
+     keep { print } keep && /^    }$/ { exit }' "$work/after/$cpu" > "$work/bench.rs"
+sed -i '$d' "$work/before/$cpu"
+cat "$work/bench.rs" >> "$work/before/$cpu"
+echo "}" >> "$work/before/$cpu"
+
+# Cargo.lock is not checked in, both trees build with the dependencies resolved here
+for tree in before after; do
+    cp "$root/Cargo.lock" "$work/$tree/" 2>/dev/null || true
+    printf '%s: ' "$tree"
+    (cd "$work/$tree" && CARGO_TARGET_DIR="$work/target-$tree" cargo test --quiet --release \
+        bench_instructions_per_second -- --ignored --nocapture 2>&1 \
+        | grep 'instructions/s')
+done
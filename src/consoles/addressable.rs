@@ -3,4 +3,6 @@ use std::ops::RangeInclusive;
 pub trait Addressable<A> {
     fn assign_address_range(&mut self, range: RangeInclusive<usize>);
     fn in_range(&self, address: A) -> bool;
+    // The addresses the device answers to, used by the bus to build its page table
+    fn address_ranges(&self) -> Vec<RangeInclusive<usize>>;
 }
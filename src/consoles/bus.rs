@@ -1,9 +1,12 @@
 use num_traits::{NumCast, ToPrimitive};
 use std::error::Error;
 use std::fmt::Debug;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
 use std::{cell::RefCell, fmt::Display, rc::Rc};
 
 use super::{addressable::Addressable, readable::Readable, writeable::Writeable};
+use crate::log;
 
 pub trait ReadDevice<A, V>: Readable<A, V> + Addressable<A> {}
 pub trait WriteDevice<A, V, DV>: Writeable<A, V, DV> + Addressable<A> {}
@@ -15,6 +18,8 @@ where
 {
     BusReadError(A),
     BusWriteError(A),
+    ProhibitedRead(A),
+    ProhibitedWrite(A),
 }
 
 impl<A> Display for BusError<A>
@@ -25,6 +30,12 @@ where
         let s = match self {
             BusError::BusReadError(addr) => format!("{}{}", "Failed to read from address: ", addr),
             BusError::BusWriteError(addr) => format!("{}{}", "Failed to write to address: ", addr),
+            BusError::ProhibitedRead(addr) => {
+                format!("{}{}", "Read from prohibited address: ", addr)
+            }
+            BusError::ProhibitedWrite(addr) => {
+                format!("{}{}", "Write to prohibited address: ", addr)
+            }
         };
         write!(f, "{}", s)
     }
@@ -32,37 +43,234 @@ where
 
 impl<A> Error for BusError<A> where A: Debug + Display + NumCast + ToPrimitive {}
 
+// What the bus does on accesses to unmapped or prohibited addresses, meant for debugging
+// homebrew that relies on open bus behaviour by accident
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StrictMode {
+    #[default]
+    Off,
+    // Logs every such access as a bus warning and carries on
+    Log,
+    // Fails the access, which faults the cpu
+    Break,
+}
+
+impl FromStr for StrictMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(StrictMode::Off),
+            "log" => Ok(StrictMode::Log),
+            "break" => Ok(StrictMode::Break),
+            _ => Err(format!(
+                "Unknown strict mode {s}, expected off, log or break"
+            )),
+        }
+    }
+}
+
+// Size of the address blocks the bus decodes at once
+pub const PAGE_SIZE: usize = 0x100;
+
+// Which devices answer to the addresses of one page
+#[derive(Debug, Clone, PartialEq)]
+enum Page {
+    Unmapped,
+    // A single device covers every address of the page
+    Whole(usize),
+    // The page is shared or only partly covered, holds the device for every address in it
+    Split(Box<[Option<usize>; PAGE_SIZE]>),
+}
+
+// Devices and the pages they are mapped to. Each device is registered once from its
+// address ranges when it is connected, so an access only indexes into the page table.
+struct PageTable<D: ?Sized> {
+    devices: Vec<Rc<RefCell<D>>>,
+    pages: Vec<Page>,
+}
+
+impl<D: ?Sized> PageTable<D> {
+    fn new() -> Self {
+        PageTable {
+            devices: vec![],
+            pages: vec![],
+        }
+    }
+
+    fn connect<A>(&mut self, device: Rc<RefCell<D>>)
+    where
+        D: Addressable<A>,
+    {
+        let index = self.devices.len();
+        let ranges = device.borrow().address_ranges();
+        self.devices.push(device);
+
+        for range in ranges.iter().filter(|range| !range.is_empty()) {
+            let last_page = range.end() / PAGE_SIZE;
+            if self.pages.len() <= last_page {
+                self.pages.resize(last_page + 1, Page::Unmapped);
+            }
+
+            for page in range.start() / PAGE_SIZE..=last_page {
+                let start = page * PAGE_SIZE;
+                let end = start + PAGE_SIZE - 1;
+                let covers_page = range.contains(&start) && range.contains(&end);
+                if covers_page && self.pages[page] == Page::Unmapped {
+                    self.pages[page] = Page::Whole(index);
+                    continue;
+                }
+
+                // Devices connected earlier take precedence, a later device only gets the
+                // addresses they leave free
+                if self.pages[page] == Page::Unmapped {
+                    self.pages[page] = Page::Split(Box::new([None; PAGE_SIZE]));
+                }
+                if let Page::Split(slots) = &mut self.pages[page] {
+                    for address in start.max(*range.start())..=end.min(*range.end()) {
+                        slots[address - start].get_or_insert(index);
+                    }
+                }
+            }
+        }
+    }
+
+    fn device<A>(&self, address: A) -> Option<&Rc<RefCell<D>>>
+    where
+        A: ToPrimitive,
+    {
+        let address = address.to_usize()?;
+        let index = match self.pages.get(address / PAGE_SIZE)? {
+            Page::Unmapped => None,
+            Page::Whole(index) => Some(*index),
+            Page::Split(slots) => slots[address % PAGE_SIZE],
+        }?;
+        Some(&self.devices[index])
+    }
+}
+
 pub struct Bus<A, V, DV> {
-    readables: Vec<Rc<RefCell<dyn ReadDevice<A, V>>>>,
-    writeables: Vec<Rc<RefCell<dyn WriteDevice<A, V, DV>>>>,
+    readables: PageTable<dyn ReadDevice<A, V>>,
+    writeables: PageTable<dyn WriteDevice<A, V, DV>>,
+    // When set, unmapped reads return this value and unmapped writes are ignored
+    open_bus: Option<V>,
+    prohibited: Vec<RangeInclusive<usize>>,
+    strict_mode: StrictMode,
 }
 
 impl<A, V, DV> Bus<A, V, DV> {
     pub fn new() -> Self {
         Bus {
-            readables: vec![],
-            writeables: vec![],
+            readables: PageTable::new(),
+            writeables: PageTable::new(),
+            open_bus: None,
+            prohibited: vec![],
+            strict_mode: StrictMode::Off,
         }
     }
 
+    pub fn set_open_bus(&mut self, value: V) {
+        self.open_bus = Some(value);
+    }
+
+    // Marks a range the program should not access, only reported in strict mode
+    pub fn prohibit(&mut self, range: RangeInclusive<usize>) {
+        self.prohibited.push(range);
+    }
+
+    pub fn set_strict_mode(&mut self, mode: StrictMode) {
+        self.strict_mode = mode;
+    }
+
     pub fn connect_readable(&mut self, readable: Rc<RefCell<dyn ReadDevice<A, V>>>) {
-        self.readables.push(readable);
+        self.readables.connect(readable);
     }
 
     pub fn connect_writeable(&mut self, writeable: Rc<RefCell<dyn WriteDevice<A, V, DV>>>) {
-        self.writeables.push(writeable);
+        self.writeables.connect(writeable);
+    }
+}
+
+impl<A, V, DV> Bus<A, V, DV>
+where
+    A: NumCast + ToPrimitive + Display + Debug + 'static + Copy + Clone,
+{
+    fn is_prohibited(&self, address: A) -> bool {
+        address
+            .to_usize()
+            .is_some_and(|address| self.prohibited.iter().any(|r| r.contains(&address)))
+    }
+
+    // Checks an access against the strict mode, error is reported if the address is unmapped
+    // and prohibited is reported for any access to a prohibited range
+    fn check_strict(
+        &self,
+        address: A,
+        mapped: bool,
+        error: BusError<A>,
+        prohibited: BusError<A>,
+    ) -> Result<(), Box<dyn Error>> {
+        if self.strict_mode == StrictMode::Off {
+            return Ok(());
+        }
+
+        let error = if self.is_prohibited(address) {
+            prohibited
+        } else if !mapped {
+            error
+        } else {
+            return Ok(());
+        };
+
+        match self.strict_mode {
+            StrictMode::Log => {
+                log!(Bus, Warn, "Strict bus: {error}");
+                Ok(())
+            }
+            _ => Err(Box::new(error)),
+        }
+    }
+
+    // Hands the device at the address to write, does nothing if the write goes to the open bus
+    fn write_with(
+        &self,
+        address: A,
+        write: impl FnOnce(&mut dyn WriteDevice<A, V, DV>) -> Result<(), Box<dyn Error>>,
+    ) -> Result<(), Box<dyn Error>> {
+        let writeable = self.writeables.device(address);
+        self.check_strict(
+            address,
+            writeable.is_some(),
+            BusError::BusWriteError(address),
+            BusError::ProhibitedWrite(address),
+        )?;
+
+        match writeable {
+            Some(writeable) => write(&mut *writeable.borrow_mut()),
+            None if self.open_bus.is_some() => Ok(()),
+            None => Err(Box::new(BusError::BusWriteError::<A>(address))),
+        }
     }
 }
 
 impl<A, V, DV> Readable<A, V> for Bus<A, V, DV>
 where
     A: NumCast + ToPrimitive + Display + Debug + 'static + Copy + Clone,
+    V: Copy,
 {
     fn read(&self, address: A) -> Result<V, Box<dyn Error>> {
-        if let Some(readable) = self.readables.iter().find(|r| r.borrow().in_range(address)) {
-            readable.borrow().read(address)
-        } else {
-            Err(Box::new(BusError::BusReadError::<A>(address)))
+        let readable = self.readables.device(address);
+        self.check_strict(
+            address,
+            readable.is_some(),
+            BusError::BusReadError(address),
+            BusError::ProhibitedRead(address),
+        )?;
+
+        match (readable, self.open_bus) {
+            (Some(readable), _) => readable.borrow().read(address),
+            (None, Some(value)) => Ok(value),
+            (None, None) => Err(Box::new(BusError::BusReadError::<A>(address))),
         }
     }
 }
@@ -72,28 +280,249 @@ where
     A: NumCast + ToPrimitive + Display + Debug + 'static + Copy + Clone,
 {
     fn write(&mut self, address: A, data: V) -> Result<(), Box<dyn Error>> {
-        if let Some(writeable) = self
-            .writeables
-            .iter()
-            .find(|w| w.borrow().in_range(address))
-        {
-            let _ = writeable.as_ref().borrow_mut().write(address, data);
-            Ok(())
-        } else {
-            Err(Box::new(BusError::BusWriteError::<A>(address)))
-        }
+        self.write_with(address, |writeable| writeable.write(address, data))
     }
 
     fn write_16(&mut self, address: A, data: DV) -> Result<(), Box<dyn Error>> {
-        if let Some(writeable) = self
-            .writeables
-            .iter()
-            .find(|w| w.borrow().in_range(address))
-        {
-            let _ = writeable.as_ref().borrow_mut().write_16(address, data);
+        self.write_with(address, |writeable| writeable.write_16(address, data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::error::Error;
+    use std::hint::black_box;
+    use std::ops::RangeInclusive;
+    use std::rc::Rc;
+    use std::time::Instant;
+
+    use rstest::rstest;
+
+    use super::{Bus, ReadDevice, StrictMode, WriteDevice};
+    use crate::consoles::addressable::Addressable;
+    use crate::consoles::readable::Readable;
+    use crate::consoles::writeable::Writeable;
+
+    // Answers every read in its range with its id and remembers the last write
+    struct Device {
+        id: u8,
+        range: RangeInclusive<usize>,
+        written: Option<(u16, u8)>,
+        read_only: bool,
+    }
+
+    impl Device {
+        fn new(id: u8, range: RangeInclusive<usize>) -> Rc<RefCell<Device>> {
+            Rc::new(RefCell::new(Device {
+                id,
+                range,
+                written: None,
+                read_only: false,
+            }))
+        }
+    }
+
+    impl Readable<u16, u8> for Device {
+        fn read(&self, _address: u16) -> Result<u8, Box<dyn Error>> {
+            Ok(self.id)
+        }
+    }
+
+    impl Writeable<u16, u8, u16> for Device {
+        fn write(&mut self, address: u16, data: u8) -> Result<(), Box<dyn Error>> {
+            if self.read_only {
+                return Err("read only".into());
+            }
+            self.written = Some((address, data));
             Ok(())
-        } else {
-            Err(Box::new(BusError::BusWriteError::<A>(address)))
         }
+
+        fn write_16(&mut self, address: u16, data: u16) -> Result<(), Box<dyn Error>> {
+            self.written = Some((address, data as u8));
+            Ok(())
+        }
+    }
+
+    impl Addressable<u16> for Device {
+        fn assign_address_range(&mut self, range: RangeInclusive<usize>) {
+            self.range = range;
+        }
+
+        fn in_range(&self, address: u16) -> bool {
+            self.range.contains(&(address as usize))
+        }
+
+        fn address_ranges(&self) -> Vec<RangeInclusive<usize>> {
+            vec![self.range.clone()]
+        }
+    }
+
+    impl ReadDevice<u16, u8> for Device {}
+
+    impl WriteDevice<u16, u8, u16> for Device {}
+
+    // Laid out like the game boy, io registers and hram share the last page
+    const LAYOUT: [(u8, RangeInclusive<usize>); 10] = [
+        (1, 0xC000..=0xCFFF),
+        (2, 0xD000..=0xDFFF),
+        (3, 0x0000..=0x7FFF),
+        (4, 0x8000..=0x9FFF),
+        (5, 0xA000..=0xBFFF),
+        (6, 0xFE00..=0xFE9F),
+        (7, 0xFF01..=0xFF02),
+        (8, 0xFF0F..=0xFF0F),
+        (9, 0xFF80..=0xFFFE),
+        (10, 0xFFFF..=0xFFFF),
+    ];
+
+    fn setup() -> Bus<u16, u8, u16> {
+        let mut bus = Bus::new();
+        for (id, range) in LAYOUT {
+            let device = Device::new(id, range);
+            bus.connect_readable(device.clone());
+            bus.connect_writeable(device);
+        }
+        bus
+    }
+
+    #[rstest]
+    #[case(0x0000, Some(3))]
+    #[case(0x7FFF, Some(3))]
+    #[case(0xC123, Some(1))]
+    #[case(0xDFFF, Some(2))]
+    #[case(0xFE9F, Some(6))]
+    #[case(0xFF02, Some(7))]
+    #[case(0xFF0F, Some(8))]
+    #[case(0xFF80, Some(9))]
+    #[case(0xFFFF, Some(10))]
+    #[case(0xE000, None)]
+    #[case(0xFEA0, None)]
+    #[case(0xFF00, None)]
+    #[case(0xFF03, None)]
+    fn test_read(#[case] address: u16, #[case] expected: Option<u8>) {
+        let bus = setup();
+        assert_eq!(bus.read(address).ok(), expected);
+    }
+
+    #[test]
+    fn test_write() {
+        let mut bus = Bus::new();
+        let io = Device::new(1, 0xFF01..=0xFF02);
+        let hram = Device::new(2, 0xFF80..=0xFFFE);
+        bus.connect_writeable(io.clone());
+        bus.connect_writeable(hram.clone());
+
+        bus.write(0xFF90, 0x42).unwrap();
+        assert_eq!(hram.borrow().written, Some((0xFF90, 0x42)));
+        assert_eq!(io.borrow().written, None);
+        assert!(bus.write(0xFF0F, 0x42).is_err());
+        assert!(bus.write_16(0x1234, 0x42).is_err());
+    }
+
+    #[test]
+    fn test_first_connected_device_wins() {
+        let mut bus = Bus::<u16, u8, u16>::new();
+        bus.connect_readable(Device::new(1, 0xC080..=0xC0FF));
+        bus.connect_readable(Device::new(2, 0xC000..=0xCFFF));
+        bus.connect_readable(Device::new(3, 0xC000..=0xCFFF));
+        bus.connect_readable(Device::new(4, 0xC200..=0xC2FF));
+
+        assert_eq!(bus.read(0xC000).unwrap(), 2);
+        assert_eq!(bus.read(0xC080).unwrap(), 1);
+        assert_eq!(bus.read(0xC100).unwrap(), 2);
+        assert_eq!(bus.read(0xC200).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_open_bus() {
+        let mut bus = setup();
+        bus.set_open_bus(0xFF);
+
+        assert_eq!(bus.read(0xFEA0).unwrap(), 0xFF);
+        assert_eq!(bus.read(0xFF03).unwrap(), 0xFF);
+        assert!(bus.write(0xFEA0, 0x42).is_ok());
+        assert!(bus.write_16(0xE000, 0x42).is_ok());
+    }
+
+    #[test]
+    fn test_device_errors_are_propagated() {
+        let mut bus = Bus::new();
+        let rom = Device::new(1, 0x0000..=0x7FFF);
+        rom.borrow_mut().read_only = true;
+        bus.connect_writeable(rom);
+        bus.set_open_bus(0xFF);
+
+        assert!(bus.write(0x2000, 0x42).is_err());
+    }
+
+    #[rstest]
+    #[case(StrictMode::Off, 0xFF03, true)]
+    #[case(StrictMode::Log, 0xFF03, true)]
+    #[case(StrictMode::Break, 0xFF03, false)]
+    #[case(StrictMode::Break, 0xFEA0, false)]
+    #[case(StrictMode::Break, 0xC000, true)]
+    #[case(StrictMode::Off, 0xC100, true)]
+    #[case(StrictMode::Break, 0xC100, false)]
+    fn test_strict_mode(#[case] mode: StrictMode, #[case] address: u16, #[case] allowed: bool) {
+        let mut bus = setup();
+        bus.set_open_bus(0xFF);
+        bus.prohibit(0xC100..=0xC1FF);
+        bus.prohibit(0xFEA0..=0xFEFF);
+        bus.set_strict_mode(mode);
+
+        assert_eq!(bus.read(address).is_ok(), allowed);
+        assert_eq!(bus.write(address, 0x42).is_ok(), allowed);
+    }
+
+    #[test]
+    fn test_parse_strict_mode() {
+        assert_eq!("log".parse(), Ok(StrictMode::Log));
+        assert_eq!("break".parse(), Ok(StrictMode::Break));
+        assert!("loud".parse::<StrictMode>().is_err());
+    }
+
+    // The bus before the page table, every access searches the devices in connection order
+    struct LinearBus {
+        readables: Vec<Rc<RefCell<dyn ReadDevice<u16, u8>>>>,
+    }
+
+    impl LinearBus {
+        fn read(&self, address: u16) -> Result<u8, Box<dyn Error>> {
+            match self.readables.iter().find(|r| r.borrow().in_range(address)) {
+                Some(readable) => readable.borrow().read(address),
+                None => Err("unmapped".into()),
+            }
+        }
+    }
+
+    fn time_reads(read: impl Fn(u16) -> Result<u8, Box<dyn Error>>) -> f64 {
+        const ACCESSES: u32 = 1 << 22;
+        // Mostly rom and wram like a running game, with the occasional io register
+        let addresses = [
+            0x0150, 0x4321, 0xC010, 0xCFF0, 0xFF0F, 0xFF85, 0xFFFF, 0x0151,
+        ];
+        let start = Instant::now();
+        for i in 0..ACCESSES {
+            let _ = black_box(read(black_box(addresses[i as usize % addresses.len()])));
+        }
+        start.elapsed().as_nanos() as f64 / ACCESSES as f64
+    }
+
+    // cargo test --release bench_bus_read -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_bus_read() {
+        let bus = setup();
+        let mut linear = LinearBus { readables: vec![] };
+        for (id, range) in LAYOUT {
+            linear.readables.push(Device::new(id, range));
+        }
+
+        let linear_ns = time_reads(|address| linear.read(address));
+        let paged_ns = time_reads(|address| bus.read(address));
+        println!("linear search: {linear_ns:.2} ns/read");
+        println!("page table:    {paged_ns:.2} ns/read");
+        println!("speedup:       {:.2}x", linear_ns / paged_ns);
     }
 }
@@ -1,4 +1,4 @@
-use super::gameboy::gbcartridge::GbCartridge;
+use super::registry::ConsoleRegistry;
 use std::error::Error;
 use std::{any::Any, fmt::Display};
 
@@ -6,6 +6,7 @@ pub trait Cartridge {
     fn dump(&self) -> String;
     fn dump_raw(&self) -> String;
     fn as_any(&self) -> &dyn Any;
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
 }
 
 #[derive(Debug)]
@@ -21,19 +22,15 @@ impl Display for CartridgeNotFoundError {
 
 impl Error for CartridgeNotFoundError {}
 
-pub fn create_catridge(path: &str) -> Result<impl Cartridge, Box<dyn Error>> {
-    if let Some(i) = path.rfind(".") {
-        let suffix = &path[i + 1..];
+// Loads the rom for whichever compiled in console takes it, see ConsoleRegistry
+pub fn create_catridge(path: &str) -> Result<Box<dyn Cartridge>, Box<dyn Error>> {
+    ConsoleRegistry::default().load_cartridge(path)
+}
 
-        match suffix {
-            "gbc" => GbCartridge::new(path),
-            _ => Err(Box::new(CartridgeNotFoundError {
-                what: "Suffix unknown: ".to_string() + suffix,
-            })),
-        }
-    } else {
-        Err(Box::new(CartridgeNotFoundError {
-            what: "Invalid path: ".to_string() + path,
-        }))
-    }
+// The name only picks the console, the rom does not have to exist as a file
+pub fn create_cartridge_from_bytes(
+    name: &str,
+    data: Vec<u8>,
+) -> Result<Box<dyn Cartridge>, Box<dyn Error>> {
+    ConsoleRegistry::default().cartridge_from_bytes(name, data)
 }
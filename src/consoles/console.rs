@@ -1,17 +1,95 @@
 use std::fmt::Display;
+use std::io::Write;
 
-use super::{
-    cartridge::Cartridge,
-    gameboy::{game_boy::GameBoy, gbcartridge::GbCartridge},
-};
+use super::bus::StrictMode;
+use super::cartridge::Cartridge;
+use super::pacing::{self, FramePacer};
+use super::registry::ConsoleRegistry;
 use std::error::Error;
 
+// Rate of the samples handed out by Console::audio_samples
+pub const SAMPLE_RATE: u32 = 48_000;
+
+// The buttons held down on the controller, a console ignores those its controller lacks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Buttons {
+    pub up: bool,
+    pub down: bool,
+    pub left: bool,
+    pub right: bool,
+    pub a: bool,
+    pub b: bool,
+    pub start: bool,
+    pub select: bool,
+}
+
+// The picture on the screen, row by row from the top left as 0x00RRGGBB
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Frame<'a> {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: &'a [u32],
+}
+
 pub trait Console {
-    fn save_game(&self, path: String);
-    fn load_save(&self, path: String);
-    fn run(&mut self);
+    // Battery backed cartridge ram, fails on consoles and cartridges that cannot keep any
+    fn save_game(&self, path: String) -> Result<(), Box<dyn Error>>;
+    fn load_save(&mut self, path: String) -> Result<(), Box<dyn Error>>;
+    // Frames per second of the console at full speed
+    fn frame_rate(&self) -> f64;
+    // Runs frame after frame at the pace the pacer sets, until its handle stops it or the
+    // console fails
+    fn run(&mut self, pacer: &mut FramePacer) -> Result<(), Box<dyn Error>> {
+        pacing::run(self, pacer)
+    }
+    // Writes one line per executed instruction to out, in the console's reference log format
+    fn trace_to(&mut self, out: Box<dyn Write>);
+    // Reports accesses to unmapped or prohibited addresses, see StrictMode
+    fn set_strict_mode(&mut self, mode: StrictMode);
+    // Turns the console off and on again with the same cartridge in, settings such as the
+    // strict mode or a tracer stay
+    fn reset(&mut self);
+    // Runs until the current frame is complete, fails if the cpu froze
+    fn step_frame(&mut self) -> Result<(), Box<dyn Error>>;
+    fn framebuffer(&self) -> Frame<'_>;
+    // Interleaved stereo samples at SAMPLE_RATE, everything produced since the last call
+    fn audio_samples(&mut self) -> Vec<i16>;
+    fn set_buttons(&mut self, buttons: Buttons);
+    // Reads and writes go through the bus the way the cpu would see them, but take no time
+    fn read_memory(&self, address: usize) -> Result<u8, Box<dyn Error>>;
+    fn write_memory(&mut self, address: usize, value: u8) -> Result<(), Box<dyn Error>>;
+    // Everything needed to continue from the same point later, in the same version of the
+    // emulator. The cartridge is not part of it.
+    fn save_state(&self) -> Vec<u8>;
+    fn load_state(&mut self, state: &[u8]) -> Result<(), Box<dyn Error>>;
+}
+
+#[derive(Debug)]
+pub struct AddressOutOfRangeError {
+    pub address: usize,
 }
 
+impl Display for AddressOutOfRangeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:#X} is outside the address space", self.address)
+    }
+}
+
+impl Error for AddressOutOfRangeError {}
+
+#[derive(Debug)]
+pub struct UnsupportedError {
+    pub what: String,
+}
+
+impl Display for UnsupportedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.what)
+    }
+}
+
+impl Error for UnsupportedError {}
+
 #[derive(Debug)]
 pub struct NoConsolePresentError {
     pub what: String,
@@ -25,12 +103,17 @@ impl Display for NoConsolePresentError {
 
 impl Error for NoConsolePresentError {}
 
-pub fn create_console_for(cart: impl Cartridge) -> Result<impl Console, Box<dyn Error>> {
-    if let Some(cartridge) = cart.as_any().downcast_ref::<GbCartridge>() {
-        Ok(GameBoy::new(cartridge.clone()))
-    } else {
-        Err(Box::new(NoConsolePresentError {
-            what: String::from("No Console present for the rom provided"),
-        }))
-    }
+// Hands the cartridge to whichever compiled in console takes it, see ConsoleRegistry
+pub fn create_console_for(
+    cartridge: Box<dyn Cartridge>,
+) -> Result<Box<dyn Console>, Box<dyn Error>> {
+    ConsoleRegistry::default().create_console_for(cartridge)
+}
+
+// A console for a rom that is already in memory, the name picks the console like a path would
+pub fn create_console_from_bytes(
+    name: &str,
+    data: Vec<u8>,
+) -> Result<Box<dyn Console>, Box<dyn Error>> {
+    ConsoleRegistry::default().create_console_from_bytes(name, data)
 }
@@ -5,6 +5,7 @@ use crate::shift_right;
 use super::{
     addressable::Addressable,
     bus::{ReadDevice, WriteDevice},
+    cartridge::Cartridge,
     memory_map::gameboy::ROM_BANK_00,
     readable::Readable,
     writeable::Writeable,
@@ -12,7 +13,6 @@ use super::{
 
 #[derive(Debug, Clone)]
 pub struct FakeCartridge {
-    path: String,
     data: Vec<u8>,
     address_range: RangeInclusive<usize>,
 }
@@ -21,12 +21,19 @@ impl FakeCartridge {
     pub fn new() -> FakeCartridge {
         let data = ROM_BANK_00.map(|_| 0).collect();
         FakeCartridge {
-            path: "fake:cartridge".to_string(),
             data,
             address_range: (0..=0),
         }
     }
+}
+
+impl Default for FakeCartridge {
+    fn default() -> Self {
+        FakeCartridge::new()
+    }
+}
 
+impl Cartridge for FakeCartridge {
     fn dump(&self) -> String {
         self.dump_raw()
     }
@@ -41,13 +48,17 @@ impl FakeCartridge {
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
+
+    fn into_any(self: Box<Self>) -> Box<dyn std::any::Any> {
+        self
+    }
 }
 
 impl ReadDevice<u16, u8> for FakeCartridge {}
 
 impl Readable<u16, u8> for FakeCartridge {
     fn read(&self, address: u16) -> Result<u8, Box<dyn std::error::Error>> {
-        Ok(self.data[address as usize])
+        Ok(self.data.get(address as usize).copied().unwrap_or(0xFF))
     }
 }
 
@@ -55,16 +66,17 @@ impl WriteDevice<u16, u8, u16> for FakeCartridge {}
 
 impl Writeable<u16, u8, u16> for FakeCartridge {
     fn write(&mut self, address: u16, data: u8) -> Result<(), Box<dyn std::error::Error>> {
-        self.data[address as usize] = data;
+        if let Some(byte) = self.data.get_mut(address as usize) {
+            *byte = data;
+        }
         Ok(())
     }
 
     fn write_16(&mut self, address: u16, data: u16) -> Result<(), Box<dyn std::error::Error>> {
         let upper = shift_right!(data, 8, u8);
         let lower = (data & 0xFF) as u8;
-        self.data[address as usize] = lower;
-        self.data[(address + 1) as usize] = upper;
-        Ok(())
+        self.write(address, lower)?;
+        self.write(address.wrapping_add(1), upper)
     }
 }
 
@@ -76,4 +88,8 @@ impl Addressable<u16> for FakeCartridge {
     fn in_range(&self, address: u16) -> bool {
         self.address_range.contains(&(address as usize))
     }
+
+    fn address_ranges(&self) -> Vec<RangeInclusive<usize>> {
+        vec![self.address_range.clone()]
+    }
 }
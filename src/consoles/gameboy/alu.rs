@@ -0,0 +1,179 @@
+// The arithmetic of the sm83 together with the flags it produces. Which of the flags end up in F
+// is up to the FlagAffection of the instruction in the INSTRUCTIONS table.
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AluFlags {
+    pub zero: bool,
+    pub half_carry: bool,
+    pub carry: bool,
+}
+
+impl AluFlags {
+    // Operations that can neither carry nor borrow, e.g. AND, OR and XOR
+    pub fn from_result(result: u8) -> AluFlags {
+        AluFlags {
+            zero: result == 0,
+            ..AluFlags::default()
+        }
+    }
+}
+
+// a + b + carry, used by ADD, ADC and INC
+pub fn add_8(a: u8, b: u8, carry: bool) -> (u8, AluFlags) {
+    let carry = carry as u8;
+    let result = a.wrapping_add(b).wrapping_add(carry);
+    let flags = AluFlags {
+        zero: result == 0,
+        half_carry: (a & 0x0F) + (b & 0x0F) + carry > 0x0F,
+        carry: a as u16 + b as u16 + carry as u16 > 0xFF,
+    };
+    (result, flags)
+}
+
+// a - b - carry, used by SUB, SBC, CP and DEC. The carries are borrows.
+pub fn sub_8(a: u8, b: u8, carry: bool) -> (u8, AluFlags) {
+    let carry = carry as u8;
+    let result = a.wrapping_sub(b).wrapping_sub(carry);
+    let flags = AluFlags {
+        zero: result == 0,
+        half_carry: (a & 0x0F) < (b & 0x0F) + carry,
+        carry: (a as u16) < b as u16 + carry as u16,
+    };
+    (result, flags)
+}
+
+// ADD HL,rr carries out of bit 11 and bit 15
+pub fn add_16(a: u16, b: u16) -> (u16, AluFlags) {
+    let result = a.wrapping_add(b);
+    let flags = AluFlags {
+        zero: result == 0,
+        half_carry: (a & 0x0FFF) + (b & 0x0FFF) > 0x0FFF,
+        carry: a as u32 + b as u32 > 0xFFFF,
+    };
+    (result, flags)
+}
+
+// ADD SP,r8 and LD HL,SP+r8 add a signed offset, but carry as if the offset was added unsigned
+// to the lower byte of sp
+pub fn add_sp(sp: u16, offset: u8) -> (u16, AluFlags) {
+    let result = sp.wrapping_add_signed(offset as i8 as i16);
+    let flags = AluFlags {
+        zero: result == 0,
+        half_carry: (sp & 0x0F) + (offset as u16 & 0x0F) > 0x0F,
+        carry: (sp & 0xFF) + offset as u16 > 0xFF,
+    };
+    (result, flags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AluFlags, add_8, add_16, add_sp, sub_8};
+
+    // The carry into a bit of a sum or the borrow into a bit of a difference, read off the
+    // operands and the full width result
+    fn carry_into(bit: u32, a: u32, b: u32, result: u32) -> bool {
+        (a ^ b ^ result) & (1 << bit) != 0
+    }
+
+    // xorshift32, fixed seed so that a failure can be reproduced
+    fn samples(count: usize) -> impl Iterator<Item = u32> {
+        let mut state = 0x2545_F491u32;
+        (0..count).map(move |_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state
+        })
+    }
+
+    #[test]
+    fn test_add_8() {
+        for a in 0..=255u32 {
+            for b in 0..=255u32 {
+                for carry in [false, true] {
+                    let sum = a + b + carry as u32;
+                    let expected = AluFlags {
+                        zero: sum & 0xFF == 0,
+                        half_carry: carry_into(4, a, b, sum),
+                        carry: carry_into(8, a, b, sum),
+                    };
+                    assert_eq!(
+                        add_8(a as u8, b as u8, carry),
+                        (sum as u8, expected),
+                        "{a:#04X} + {b:#04X} + {carry}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_sub_8() {
+        for a in 0..=255u32 {
+            for b in 0..=255u32 {
+                for carry in [false, true] {
+                    let difference = a.wrapping_sub(b).wrapping_sub(carry as u32);
+                    let expected = AluFlags {
+                        zero: difference & 0xFF == 0,
+                        half_carry: carry_into(4, a, b, difference),
+                        carry: carry_into(8, a, b, difference),
+                    };
+                    assert_eq!(
+                        sub_8(a as u8, b as u8, carry),
+                        (difference as u8, expected),
+                        "{a:#04X} - {b:#04X} - {carry}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_add_16() {
+        let edges = [0x0000, 0x0001, 0x0FFF, 0x1000, 0x7FFF, 0x8000, 0xFFFF];
+        let pairs = edges
+            .iter()
+            .flat_map(|a| edges.iter().map(move |b| (*a, *b)))
+            .chain(samples(0x10000).map(|sample| (sample >> 16, sample & 0xFFFF)));
+
+        for (a, b) in pairs {
+            let sum = a + b;
+            let expected = AluFlags {
+                zero: sum & 0xFFFF == 0,
+                half_carry: carry_into(12, a, b, sum),
+                carry: carry_into(16, a, b, sum),
+            };
+            assert_eq!(
+                add_16(a as u16, b as u16),
+                (sum as u16, expected),
+                "{a:#06X} + {b:#06X}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_add_sp() {
+        let edges = [0x0000, 0x000F, 0x00FF, 0x0100, 0xFF00, 0xFFFF];
+        let stack_pointers = edges
+            .into_iter()
+            .chain(samples(256).map(|sample| sample & 0xFFFF));
+
+        for sp in stack_pointers {
+            for offset in 0..=255u32 {
+                // Sign extended, the flags still only depend on the lower byte
+                let extended = offset as u8 as i8 as i16 as u16 as u32;
+                let sum = (sp + extended) & 0xFFFF;
+                let expected = AluFlags {
+                    zero: sum == 0,
+                    half_carry: carry_into(4, sp, extended, sum),
+                    carry: carry_into(8, sp, extended, sum),
+                };
+                assert_eq!(
+                    add_sp(sp as u16, offset as u8),
+                    (sum as u16, expected),
+                    "{sp:#06X} + {offset:#04X}"
+                );
+            }
+        }
+    }
+}
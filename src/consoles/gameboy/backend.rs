@@ -0,0 +1,76 @@
+use std::error::Error;
+
+use super::game_boy::GameBoy;
+use super::gbcartridge::GbCartridge;
+use crate::consoles::cartridge::Cartridge;
+use crate::consoles::console::Console;
+use crate::consoles::registry::ConsoleBackend;
+
+const SUFFIXES: &[&str] = &[
+    "gb",
+    #[cfg(feature = "gbc")]
+    "gbc",
+];
+
+pub struct GameBoyBackend;
+
+impl ConsoleBackend for GameBoyBackend {
+    fn name(&self) -> &'static str {
+        "gameboy"
+    }
+
+    fn suffixes(&self) -> &[&'static str] {
+        SUFFIXES
+    }
+
+    fn load_cartridge(
+        &self,
+        name: &str,
+        data: Vec<u8>,
+    ) -> Result<Box<dyn Cartridge>, Box<dyn Error>> {
+        Ok(Box::new(GbCartridge::from_bytes(name, data)))
+    }
+
+    fn accepts(&self, cartridge: &dyn Cartridge) -> bool {
+        cartridge.as_any().is::<GbCartridge>()
+    }
+
+    fn create_console(
+        &self,
+        cartridge: Box<dyn Cartridge>,
+    ) -> Result<Box<dyn Console>, Box<dyn Error>> {
+        match cartridge.into_any().downcast::<GbCartridge>() {
+            Ok(cartridge) => Ok(Box::new(GameBoy::new(*cartridge))),
+            Err(_) => Err("Not a game boy cartridge".into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::consoles::console::Console;
+    use crate::consoles::registry::ConsoleRegistry;
+
+    fn create(name: &str) -> Result<Box<dyn Console>, Box<dyn std::error::Error>> {
+        ConsoleRegistry::default().create_console_from_bytes(name, vec![0; 0x8000])
+    }
+
+    #[test]
+    fn test_create_from_bytes() {
+        let mut console = create("test.gb").unwrap();
+        console.write_memory(0xC000, 0x42).unwrap();
+        assert_eq!(console.read_memory(0xC000).unwrap(), 0x42);
+    }
+
+    #[test]
+    #[cfg(feature = "gbc")]
+    fn test_create_gbc() {
+        assert!(create("test.gbc").is_ok());
+    }
+
+    #[test]
+    #[cfg(not(feature = "gbc"))]
+    fn test_gbc_left_out() {
+        assert!(create("test.gbc").is_err());
+    }
+}
@@ -0,0 +1,287 @@
+use std::rc::Rc;
+
+use super::instruction::Instruction;
+use super::opcode::OpCode;
+use crate::consoles::memory_map::gameboy::{ECHO_RAM, H_RAM, OBJECT_ATTRIBUTE_MEMORY, WRAM};
+
+// Straight-line runs of decoded instructions, so that code the cpu keeps coming back to is only
+// decoded once. The cpu still executes them one at a time, a block only saves the decoding.
+
+// A write only has to look this many bytes back for the blocks it hits
+const MAX_INSTRUCTIONS: usize = 32;
+const MAX_BLOCK_BYTES: usize = MAX_INSTRUCTIONS * 3;
+
+// Rom and the rams below OAM, along with high ram. IO registers are never cached.
+fn is_cacheable(address: u16) -> bool {
+    let address = address as usize;
+    address < *OBJECT_ATTRIBUTE_MEMORY.start() || H_RAM.contains(&address)
+}
+
+// Instructions after which execution does not necessarily go on with the next one
+fn ends_block(opcode: OpCode) -> bool {
+    matches!(
+        opcode,
+        OpCode::JUMP(_)
+            | OpCode::JP
+            | OpCode::JP_HL
+            | OpCode::JR(_)
+            | OpCode::JRUC
+            | OpCode::CALL(_)
+            | OpCode::CALL_UC
+            | OpCode::RET(_)
+            | OpCode::RET_UC
+            | OpCode::RETI
+            | OpCode::RST(_)
+            | OpCode::HALT
+            | OpCode::STOP
+    )
+}
+
+// The instruction at pc, a prefixed one is decoded along with its prefix
+fn decode_at(pc: u16, read: &impl Fn(u16) -> Option<u8>) -> Option<&'static Instruction> {
+    let instruction = match read(pc)? {
+        0xCB => Instruction::fetch(read(pc.wrapping_add(1))?, true)?,
+        byte => Instruction::fetch(byte, false)?,
+    };
+    let cacheable =
+        (0..instruction.length as u16).all(|offset| is_cacheable(pc.wrapping_add(offset)));
+    cacheable.then_some(instruction)
+}
+
+#[derive(Debug)]
+pub struct BasicBlock {
+    // Every instruction along with its address
+    instructions: Vec<(u16, &'static Instruction)>,
+    // One past the last byte of the block
+    end: u16,
+}
+
+impl BasicBlock {
+    // Decodes from start up to and including the first instruction that changes the flow of
+    // control. An opcode that does not decode or leaves the cacheable regions ends it early.
+    pub fn decode(start: u16, read: impl Fn(u16) -> Option<u8>) -> Option<BasicBlock> {
+        let mut instructions = vec![];
+        let mut pc = start;
+        while instructions.len() < MAX_INSTRUCTIONS && is_cacheable(pc) {
+            let Some(instruction) = decode_at(pc, &read) else {
+                break;
+            };
+            instructions.push((pc, instruction));
+            pc += instruction.length as u16;
+            if ends_block(instruction.opcode) {
+                break;
+            }
+        }
+
+        if instructions.is_empty() {
+            return None;
+        }
+        Some(BasicBlock {
+            instructions,
+            end: pc,
+        })
+    }
+
+    pub fn start(&self) -> u16 {
+        self.instructions[0].0
+    }
+
+    pub fn get(&self, index: usize) -> Option<(u16, &'static Instruction)> {
+        self.instructions.get(index).copied()
+    }
+
+    fn contains(&self, address: u16) -> bool {
+        (self.start()..self.end).contains(&address)
+    }
+}
+
+pub struct BlockCache {
+    // Jump table from the address of the first instruction of a block to the block
+    blocks: Vec<Option<Rc<BasicBlock>>>,
+    // How many cached blocks every address is part of
+    coverage: Vec<u8>,
+}
+
+impl BlockCache {
+    pub fn new() -> BlockCache {
+        BlockCache {
+            blocks: vec![None; 0x10000],
+            coverage: vec![0; 0x10000],
+        }
+    }
+
+    // The block starting at pc, decoded on the first visit
+    pub fn block(&mut self, pc: u16, read: impl Fn(u16) -> Option<u8>) -> Option<Rc<BasicBlock>> {
+        if let Some(block) = &self.blocks[pc as usize] {
+            return Some(block.clone());
+        }
+
+        let block = Rc::new(BasicBlock::decode(pc, read)?);
+        for address in block.start()..block.end {
+            self.coverage[address as usize] += 1;
+        }
+        self.blocks[pc as usize] = Some(block.clone());
+        Some(block)
+    }
+
+    // Drops the blocks a write to the address changes, returns whether there were any.
+    // Echo ram shows 0xC000-0xDDFF again, so a write to either side hits both.
+    pub fn invalidate(&mut self, address: u16) -> bool {
+        let offset = ECHO_RAM.start() - WRAM.start();
+        let mirror = match address as usize {
+            address if ECHO_RAM.contains(&address) => Some(address - offset),
+            address if ECHO_RAM.contains(&(address + offset)) => Some(address + offset),
+            _ => None,
+        };
+
+        let hit = self.invalidate_address(address);
+        match mirror {
+            Some(mirror) => self.invalidate_address(mirror as u16) || hit,
+            None => hit,
+        }
+    }
+
+    fn invalidate_address(&mut self, address: u16) -> bool {
+        if self.coverage[address as usize] == 0 {
+            return false;
+        }
+
+        let first = (address as usize).saturating_sub(MAX_BLOCK_BYTES);
+        for start in first..=address as usize {
+            if !matches!(&self.blocks[start], Some(block) if block.contains(address)) {
+                continue;
+            }
+            if let Some(block) = self.blocks[start].take() {
+                for covered in block.start()..block.end {
+                    self.coverage[covered as usize] -= 1;
+                }
+            }
+        }
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.blocks.fill(None);
+        self.coverage.fill(0);
+    }
+}
+
+impl Default for BlockCache {
+    fn default() -> Self {
+        BlockCache::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::{BasicBlock, BlockCache, MAX_INSTRUCTIONS};
+    use crate::consoles::gameboy::opcode::OpCode;
+
+    fn memory(code: &[(u16, &[u8])]) -> Vec<u8> {
+        let mut memory = vec![0; 0x10000];
+        for (address, bytes) in code {
+            let start = *address as usize;
+            memory[start..start + bytes.len()].copy_from_slice(bytes);
+        }
+        memory
+    }
+
+    fn block_count(cache: &BlockCache) -> usize {
+        cache.blocks.iter().flatten().count()
+    }
+
+    fn decode(memory: &[u8], start: u16) -> Option<BasicBlock> {
+        BasicBlock::decode(start, |address| Some(memory[address as usize]))
+    }
+
+    #[rstest]
+    // ld b,d8, inc b, jr -4 and a nop that is not part of the block
+    #[case(0x0150, &[0x06, 0x05, 0x04, 0x18, 0xFC, 0x00], 3, 0x0155)]
+    // rlc b, bit 0,(hl), ret
+    #[case(0xC000, &[0xCB, 0x00, 0xCB, 0x46, 0xC9], 3, 0xC005)]
+    // nop, halt
+    #[case(0xFF80, &[0x00, 0x76, 0x00], 2, 0xFF82)]
+    // nop, then the end of high ram
+    #[case(0xFFFD, &[0x00, 0x00], 2, 0xFFFF)]
+    // nop, then ld a,d8 running into OAM
+    #[case(0xFDFE, &[0x00, 0x3E], 1, 0xFDFF)]
+    // nop, then an illegal opcode
+    #[case(0x0200, &[0x00, 0xD3], 1, 0x0201)]
+    fn test_decode(
+        #[case] start: u16,
+        #[case] code: &[u8],
+        #[case] expected_len: usize,
+        #[case] expected_end: u16,
+    ) {
+        let memory = memory(&[(start, code)]);
+        let block = decode(&memory, start).unwrap();
+        assert_eq!(block.instructions.len(), expected_len);
+        assert_eq!(block.end, expected_end);
+        assert_eq!(block.start(), start);
+    }
+
+    #[rstest]
+    #[case(0xFF00)]
+    #[case(0xFE00)]
+    #[case(0xFFFF)]
+    fn test_decode_outside_cacheable_regions(#[case] start: u16) {
+        let memory = memory(&[]);
+        assert!(decode(&memory, start).is_none());
+    }
+
+    #[test]
+    fn test_decode_stops_at_max_instructions() {
+        let memory = memory(&[]);
+        let block = decode(&memory, 0x0000).unwrap();
+        assert_eq!(block.instructions.len(), MAX_INSTRUCTIONS);
+        assert!(
+            (0..block.instructions.len())
+                .all(|index| block.get(index).unwrap().1.opcode == OpCode::NOP)
+        );
+    }
+
+    #[test]
+    fn test_block_is_decoded_once() {
+        let memory = memory(&[(0xC000, &[0x04, 0xC9])]);
+        let mut cache = BlockCache::new();
+        let first = cache.block(0xC000, |address| Some(memory[address as usize]));
+        let second = cache.block(0xC000, |_| panic!("decoded again"));
+        assert!(std::rc::Rc::ptr_eq(&first.unwrap(), &second.unwrap()));
+        assert_eq!(block_count(&cache), 1);
+    }
+
+    #[rstest]
+    // The last byte of both blocks around 0xC000
+    #[case(0xC002, true, 1)]
+    // The byte after them
+    #[case(0xC003, false, 3)]
+    // The second byte through echo ram
+    #[case(0xE001, true, 1)]
+    // Inside the block at 0xC010 only
+    #[case(0xC011, true, 2)]
+    fn test_invalidate(#[case] address: u16, #[case] expected_hit: bool, #[case] remaining: usize) {
+        // inc b, inc b, ret at 0xC000 and inc c, ret at 0xC010
+        let memory = memory(&[(0xC000, &[0x04, 0x04, 0xC9]), (0xC010, &[0x0C, 0xC9])]);
+        let read = |address: u16| Some(memory[address as usize]);
+        let mut cache = BlockCache::new();
+        cache.block(0xC000, read);
+        cache.block(0xC010, read);
+        // Starting halfway through the first block overlaps it
+        cache.block(0xC001, read);
+
+        assert_eq!(cache.invalidate(address), expected_hit);
+        assert_eq!(block_count(&cache), remaining);
+    }
+
+    #[test]
+    fn test_clear() {
+        let memory = memory(&[(0x0100, &[0x00, 0xC3, 0x50, 0x01])]);
+        let mut cache = BlockCache::new();
+        cache.block(0x0100, |address| Some(memory[address as usize]));
+        cache.clear();
+        assert_eq!(block_count(&cache), 0);
+        assert!(!cache.invalidate(0x0101));
+    }
+}
@@ -0,0 +1,7 @@
+// Hardware that runs alongside the cpu. The cpu advances it by one M-cycle, 4 T-cycles, for
+// every bus access and every internal cycle of an instruction, so that a device sees the reads
+// and writes of an instruction in the M-cycle they happen in rather than all at once.
+pub trait Clocked {
+    // Advances the device by the given number of T-cycles
+    fn tick(&mut self, cycles: u32);
+}
@@ -0,0 +1,300 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::execution::Steppable;
+use super::game_boy::GameBoy;
+use super::gbcartridge::GbCartridge;
+use super::serial::SerialCapture;
+use super::trace::TraceLine;
+
+// LD B,B, which mooneye-gb test roms execute once they are done
+const DEBUG_BREAKPOINT: u8 = 0x40;
+
+// How a test rom reports its result
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Check {
+    // blargg: prints "Passed" or "Failed" over the serial port
+    Serial,
+    // mooneye-gb: B, C, D, E, H, L hold 3, 5, 8, 13, 21, 34 at LD B,B, or 0x42 on failure
+    Fibonacci,
+    // dmg-acid2 and other visual tests: the final frame has to hash to the given value
+    Framebuffer(u64),
+}
+
+impl Display for Check {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Check::Serial => write!(f, "serial"),
+            Check::Fibonacci => write!(f, "fibonacci"),
+            Check::Framebuffer(_) => write!(f, "framebuffer"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Passed,
+    Failed(String),
+    TimedOut,
+    Unsupported(String),
+    // The cpu faulted while running the rom
+    Crashed(String),
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Outcome::Passed => write!(f, "pass"),
+            Outcome::Failed(reason) => write!(f, "FAIL ({reason})"),
+            Outcome::TimedOut => write!(f, "TIMEOUT"),
+            Outcome::Unsupported(reason) => write!(f, "skip ({reason})"),
+            Outcome::Crashed(reason) => write!(f, "CRASH ({reason})"),
+        }
+    }
+}
+
+pub struct TestRom {
+    pub path: PathBuf,
+    pub check: Check,
+}
+
+impl TestRom {
+    // mooneye-gb roms are recognised by their path, a rom with a <name>.hash file next to it
+    // holding a hex framebuffer hash is checked visually, everything else is assumed to be blargg
+    pub fn new(path: PathBuf) -> Result<TestRom, Box<dyn Error>> {
+        let hash_path = path.with_extension("hash");
+        let check = if hash_path.exists() {
+            let hash = fs::read_to_string(&hash_path)?;
+            Check::Framebuffer(u64::from_str_radix(hash.trim(), 16)?)
+        } else if path.to_string_lossy().to_lowercase().contains("mooneye") {
+            Check::Fibonacci
+        } else {
+            Check::Serial
+        };
+        Ok(TestRom { path, check })
+    }
+
+    pub fn name(&self, root: &Path) -> String {
+        self.path
+            .strip_prefix(root)
+            .unwrap_or(&self.path)
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+// All .gb and .gbc files below dir, sorted by path
+pub fn find_test_roms(dir: &Path) -> Result<Vec<TestRom>, Box<dyn Error>> {
+    let mut paths = vec![];
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path
+                .extension()
+                .is_some_and(|suffix| suffix == "gb" || suffix == "gbc")
+            {
+                paths.push(path);
+            }
+        }
+    }
+    paths.sort();
+    paths.into_iter().map(TestRom::new).collect()
+}
+
+fn serial_outcome(output: &str) -> Option<Outcome> {
+    if output.contains("Passed") {
+        Some(Outcome::Passed)
+    } else if output.contains("Failed") {
+        let reason = output.lines().rev().find(|line| !line.trim().is_empty());
+        Some(Outcome::Failed(
+            reason.unwrap_or("Failed").trim().to_string(),
+        ))
+    } else {
+        None
+    }
+}
+
+fn fibonacci_outcome(state: &TraceLine) -> Option<Outcome> {
+    if state.pc_mem[0] != DEBUG_BREAKPOINT {
+        return None;
+    }
+    let registers = [state.b, state.c, state.d, state.e, state.h, state.l];
+    if registers == [3, 5, 8, 13, 21, 34] {
+        Some(Outcome::Passed)
+    } else if registers == [0x42; 6] {
+        Some(Outcome::Failed("failure signature".to_string()))
+    } else {
+        None
+    }
+}
+
+// Steps the game boy until the check decides the outcome or max_cycles have passed
+pub fn run_check(gameboy: &mut GameBoy, check: Check, max_cycles: u64) -> Outcome {
+    // The screen stays blank until there is a ppu, so no hash can tell a pass from a failure
+    if let Check::Framebuffer(_) = check {
+        return Outcome::Unsupported("no ppu to render a frame".to_string());
+    }
+
+    let capture = SerialCapture::new();
+    gameboy.connect_serial(Box::new(capture.clone()));
+    let mut serial_length = 0;
+
+    while gameboy.cycles() < max_cycles {
+        if check == Check::Fibonacci
+            && let Some(outcome) = fibonacci_outcome(&gameboy.cpu_mut().trace_line())
+        {
+            return outcome;
+        }
+
+        match gameboy.step() {
+            Ok(true) => {}
+            Ok(false) => {
+                return Outcome::Failed(format!("cpu stopped at {:#06X}", gameboy.pc()));
+            }
+            Err(fault) => return Outcome::Crashed(fault.to_string()),
+        }
+        if let Some(lock_up) = gameboy.lock_up() {
+            return Outcome::Failed(lock_up.to_string());
+        }
+
+        if check == Check::Serial && capture.bytes().len() != serial_length {
+            serial_length = capture.bytes().len();
+            if let Some(outcome) = serial_outcome(&capture.text()) {
+                return outcome;
+            }
+        }
+    }
+
+    Outcome::TimedOut
+}
+
+pub fn run_test_rom(rom: &TestRom, max_cycles: u64) -> Outcome {
+    let cartridge = match GbCartridge::new(&rom.path.to_string_lossy()) {
+        Ok(cartridge) => cartridge,
+        Err(e) => return Outcome::Failed(e.to_string()),
+    };
+
+    let mut gameboy = GameBoy::new(cartridge);
+    run_check(&mut gameboy, rom.check, max_cycles)
+}
+
+pub struct ConformanceReport {
+    pub results: Vec<(String, Check, Outcome)>,
+}
+
+impl ConformanceReport {
+    pub fn run(dir: &Path, max_cycles: u64) -> Result<ConformanceReport, Box<dyn Error>> {
+        let results = find_test_roms(dir)?
+            .iter()
+            .map(|rom| (rom.name(dir), rom.check, run_test_rom(rom, max_cycles)))
+            .collect();
+        Ok(ConformanceReport { results })
+    }
+
+    pub fn passed(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|(_, _, outcome)| *outcome == Outcome::Passed)
+            .count()
+    }
+}
+
+impl Display for ConformanceReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let width = self
+            .results
+            .iter()
+            .map(|(name, _, _)| name.len())
+            .max()
+            .unwrap_or(0)
+            .max("Rom".len());
+
+        writeln!(f, "{:width$}  {:11}  Result", "Rom", "Check")?;
+        for (name, check, outcome) in self.results.iter() {
+            writeln!(f, "{name:width$}  {:11}  {outcome}", check.to_string())?;
+        }
+        write!(f, "{}/{} passed", self.passed(), self.results.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::consoles::gameboy::{game_boy::GameBoy, gbcartridge::GbCartridge};
+
+    use super::{Check, Outcome, run_check, serial_outcome};
+    use crate::consoles::bus::StrictMode;
+
+    fn setup(program: &[u8]) -> GameBoy {
+        let mut data = vec![0; 0x8000];
+        data[0x100..0x100 + program.len()].copy_from_slice(program);
+        GameBoy::new(GbCartridge::from_bytes("test", data))
+    }
+
+    #[test]
+    fn test_serial_outcome() {
+        assert_eq!(serial_outcome("cpu_instrs\n\n"), None);
+        assert_eq!(
+            serial_outcome("01-special\n\nPassed\n"),
+            Some(Outcome::Passed)
+        );
+        assert_eq!(
+            serial_outcome("01-special\n\nFailed #6\n"),
+            Some(Outcome::Failed("Failed #6".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_fibonacci_pass() {
+        // LD B,3 / LD C,5 / LD D,8 / LD E,13 / LD H,21 / LD L,34 / LD B,B
+        let mut gameboy = setup(&[
+            0x06, 3, 0x0E, 5, 0x16, 8, 0x1E, 13, 0x26, 21, 0x2E, 34, 0x40,
+        ]);
+        assert_eq!(
+            run_check(&mut gameboy, Check::Fibonacci, 1000),
+            Outcome::Passed
+        );
+    }
+
+    #[test]
+    fn test_lock_up() {
+        let mut gameboy = setup(&[0xED]);
+        assert_eq!(
+            run_check(&mut gameboy, Check::Serial, 1000),
+            Outcome::Failed("Locked up on illegal opcode 0xED at 0x0100".to_string())
+        );
+    }
+
+    #[test]
+    fn test_framebuffer_unsupported() {
+        let mut gameboy = setup(&[0x18, 0xFE]);
+        assert_eq!(
+            run_check(&mut gameboy, Check::Framebuffer(0x1234), 1000),
+            Outcome::Unsupported("no ppu to render a frame".to_string())
+        );
+    }
+
+    #[test]
+    fn test_fault_crashes() {
+        // ld a,(0xFEA0), which strict mode refuses
+        let mut gameboy = setup(&[0xFA, 0xA0, 0xFE]);
+        gameboy.set_strict_mode(StrictMode::Break);
+        assert!(matches!(
+            run_check(&mut gameboy, Check::Serial, 1000),
+            Outcome::Crashed(_)
+        ));
+    }
+
+    #[test]
+    fn test_timeout() {
+        let mut gameboy = setup(&[0x18, 0xFE]);
+        assert_eq!(
+            run_check(&mut gameboy, Check::Serial, 1000),
+            Outcome::TimedOut
+        );
+    }
+}
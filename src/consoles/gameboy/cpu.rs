@@ -1,9 +1,11 @@
 use crate::consoles::bus::Bus;
-use crate::consoles::memory_map::gameboy::WRAM;
+use crate::consoles::memory_map::gameboy::{
+    INTERRUPT_ENABLE_REGISTER, INTERRUPT_FLAG_REGISTER, JOYPAD, WRAM,
+};
 use crate::consoles::readable::Readable;
+use crate::consoles::state::{SaveState, StateError, StateReader, StateWriter};
 use crate::consoles::writeable::Writeable;
-use crate::{and, log, or, shift_left, shift_right, trace, xor};
-use ::function_name::named;
+use crate::{and, log, or, shift_left, shift_right, xor};
 
 #[allow(unused_imports)]
 use crate::utils::conversion::u16_to_u8;
@@ -15,13 +17,84 @@ extern crate libc;
 #[allow(unused_imports)]
 use super::registers::{CARRY_BIT_POS, HALF_CARRY_BIT_POS, SUB_BIT_POS, ZERO_BIT_POS};
 use std::cell::RefCell;
+use std::error::Error;
 use std::fmt::Display;
 use std::rc::Rc;
 
+use super::alu::{self, AluFlags};
+use super::block_cache::{BasicBlock, BlockCache};
+use super::clock::Clocked;
+use super::execution::{RunUntil, Steppable, StopHandle, StopReason};
 use super::instruction::{FlagAction, Instruction};
+use super::interrupts::Interrupt;
 use super::opcode::OpCode;
 use super::registers::Flag;
 use super::registers::Registers;
+use super::trace::{TraceLine, TraceWriter};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CpuError {
+    UnknownOpcode { opcode: u8, prefixed: bool },
+    // Nothing on the bus answered a read of the address
+    BusFault(u16),
+    // The write was refused by the bus or the device at the address
+    BusWriteFault(u16),
+    // The instruction exists, but not for these operands
+    UnsupportedOperand(String),
+}
+
+impl Display for CpuError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CpuError::UnknownOpcode {
+                opcode,
+                prefixed: false,
+            } => write!(f, "Unknown opcode {opcode:#04X}"),
+            CpuError::UnknownOpcode {
+                opcode,
+                prefixed: true,
+            } => write!(f, "Unknown opcode 0xCB {opcode:#04X}"),
+            CpuError::BusFault(address) => write!(f, "Bus fault reading {address:#06X}"),
+            CpuError::BusWriteFault(address) => write!(f, "Bus fault writing {address:#06X}"),
+            CpuError::UnsupportedOperand(what) => write!(f, "Unsupported operand: {what}"),
+        }
+    }
+}
+
+impl Error for CpuError {}
+
+// A cpu error together with the address of the instruction that caused it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CpuFault {
+    pub pc: u16,
+    pub error: CpuError,
+}
+
+impl Display for CpuFault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at {:#06X}", self.error, self.pc)
+    }
+}
+
+impl Error for CpuFault {}
+
+// Executing one of the opcodes the sm83 does not define hangs the cpu until it is powered off.
+// The rest of the hardware keeps running, but the cpu ignores interrupts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LockUp {
+    pub pc: u16,
+    pub opcode: u8,
+}
+
+impl Display for LockUp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Locked up on illegal opcode {:#04X} at {:#06X}",
+            self.opcode, self.pc
+        )
+    }
+}
 
 #[allow(dead_code)]
 pub struct Cpu {
@@ -32,7 +105,19 @@ pub struct Cpu {
     addr: u16,
     is_prefixed: bool,
     interrupts_enabled: bool,
+    // EI only enables interrupts once the instruction after it is done
+    enable_interrupts_next: bool,
     is_stopped: bool,
+    is_halted: bool,
+    cycles: u64,
+    clocked: Vec<Rc<RefCell<dyn Clocked>>>,
+    tracer: Option<TraceWriter>,
+    stop: StopHandle,
+    fault: Option<CpuFault>,
+    lock_up: Option<LockUp>,
+    block_cache: Option<BlockCache>,
+    // The block being executed and the index of its next instruction
+    block: Option<(Rc<BasicBlock>, usize)>,
 }
 
 #[allow(dead_code, unused_assignments)]
@@ -46,16 +131,48 @@ impl Cpu {
             addr: 0,
             is_prefixed: false,
             interrupts_enabled: true,
+            enable_interrupts_next: false,
             is_stopped: false,
+            is_halted: false,
+            cycles: 0,
+            clocked: vec![],
+            tracer: None,
+            stop: StopHandle::new(),
+            fault: None,
+            lock_up: None,
+            block_cache: None,
+            block: None,
         }
     }
 
+    // Hardware the cpu advances through every M-cycle it spends
+    pub fn connect_clocked(&mut self, device: Rc<RefCell<dyn Clocked>>) {
+        self.clocked.push(device);
+    }
+
+    // Decodes basic blocks once and keeps them instead of decoding every instruction, without
+    // logging every instruction. Meant for fast forwarding and bulk testing.
+    pub fn set_block_cache(&mut self, enabled: bool) {
+        self.block_cache = enabled.then(BlockCache::new);
+        self.block = None;
+    }
+
+    pub fn set_tracer(&mut self, tracer: Option<TraceWriter>) {
+        self.tracer = tracer;
+    }
+
     pub fn power_up(&mut self) {
-        // println!("cpu.power_up()");
-        // self.registers.set_combined_register(Target::BC, 0x0013);
-        // self.registers.set_combined_register(Target::DE, 0x00D8);
-        // self.registers.set_combined_register(Target::HL, 0x014D);
-        // self.sp = 0xFFFE;
+        // There is no boot rom, so start from the state the dmg boot rom leaves behind
+        self.registers.a = 0x01;
+        self.registers.f = 0xB0;
+        self.registers.b = 0x00;
+        self.registers.c = 0x13;
+        self.registers.d = 0x00;
+        self.registers.e = 0xD8;
+        self.registers.h = 0x01;
+        self.registers.l = 0x4D;
+        self.sp = 0xFFFE;
+        self.pc = 0x0100;
         // log!(self.memory.write_byte(0xFF05, 0x00));
         // log!(self.memory.write_byte(0xFF06, 0x00));
         // log!(self.memory.write_byte(0xFF07, 0x00));
@@ -90,162 +207,179 @@ impl Cpu {
     }
 
     pub fn load_program(&mut self, program: Vec<u8>) {
-        for i in 0..program.len() {
-            let _ = self.bus.borrow_mut().write(i as u16, program[i]);
-        }
-    }
-
-    fn set_flags(&mut self, instruction: &Instruction, old_value: u8, new_value: u8) {
-        match instruction.flag_affection.zero_flag {
-            FlagAction::Reset => self.registers.set_flag(Flag::Zero, false),
-            FlagAction::Set => self.registers.set_flag(Flag::Zero, true),
-            FlagAction::Affected => self.registers.set_flag(Flag::Zero, new_value == 0),
-            _ => {}
-        }
-
-        match instruction.flag_affection.sub_flag {
-            FlagAction::Reset => self.registers.set_flag(Flag::Sub, false),
-            FlagAction::Set => self.registers.set_flag(Flag::Sub, true),
-            FlagAction::Affected => {
-                // Check if this arm ever gets executed
-                unimplemented!()
-            }
-            _ => {}
-        }
-
-        match instruction.flag_affection.half_carry_flag {
-            FlagAction::Reset => self.registers.set_flag(Flag::HalfCarry, false),
-            FlagAction::Set => self.registers.set_flag(Flag::HalfCarry, true),
-            FlagAction::Affected => {
-                match instruction.flag_affection.sub_flag {
-                    FlagAction::Affected => self
-                        .registers
-                        .set_flag(Flag::HalfCarry, old_value > 0b1111 && new_value < 0b10000),
-                    FlagAction::Set => self
-                        .registers
-                        .set_flag(Flag::HalfCarry, old_value > 0b1111 && new_value < 0b10000),
-                    FlagAction::Reset => self
-                        .registers
-                        .set_flag(Flag::HalfCarry, old_value <= 0b1111 && new_value > 0b1111),
-                    _ => {}
-                }
-                // if instruction.[SUB_FLAG] == AFFECTED || instruction.flags[SUB_FLAG] == SET {
-                //     self.registers
-                //         .set_flag(Flag::HalfCarry, old_value > 0b1111 && new_value < 0b10000);
-                // } else if instruction.flags[SUB_FLAG] == RESET {
-                //     self.registers
-                //         .set_flag(Flag::HalfCarry, old_value < 0b10000 && new_value > 0b1111);
-                // }
-            }
-            _ => {}
-        }
-
-        match instruction.flag_affection.carry_flag {
-            FlagAction::Reset => self.registers.set_flag(Flag::Carry, false),
-            FlagAction::Set => self.registers.set_flag(Flag::Carry, true),
-            FlagAction::Affected => {
-                match instruction.flag_affection.sub_flag {
-                    FlagAction::Affected => {
-                        self.registers.set_flag(Flag::Carry, old_value < new_value)
-                    }
-                    FlagAction::Set => self.registers.set_flag(Flag::Carry, old_value < new_value),
-                    FlagAction::Reset => {
-                        self.registers.set_flag(Flag::Carry, old_value > new_value);
-                        println!("old = {old_value} new = {new_value}");
-                    }
-                    _ => {}
-                }
-                // if let FlagAction::Affected = instruction.flag_affection.sub_flag {
-                //     self.registers .set_flag(Flag::Carry, old_value > 0b0 && old_value < new_value);
-
-                // } else if let FlagAction::Set = instruction.flag_affection.sub_flag {
-                //     self.registers
-                //         .set_flag(Flag::Carry, old_value > 0b0 && old_value < new_value);
-                // } else if let FlagAction::Reset =  instruction.flag_affection.sub_flag {
-                //     self.registers
-                //         .set_flag(Flag::Carry, old_value < 0b11111111 && old_value > new_value);
-                // }
-            }
-            _ => {}
-        }
-    }
-
-    fn set_flags_16(&mut self, instruction: &Instruction, old_value: u16, new_value: u16) {
-        match instruction.flag_affection.zero_flag {
-            FlagAction::Reset => self.registers.set_flag(Flag::Zero, false),
-            FlagAction::Set => self.registers.set_flag(Flag::Zero, true),
-            FlagAction::Affected => self.registers.set_flag(Flag::Zero, new_value == 0),
-            _ => {}
-        }
-
-        match instruction.flag_affection.sub_flag {
-            FlagAction::Reset => self.registers.set_flag(Flag::Sub, false),
-            FlagAction::Set => self.registers.set_flag(Flag::Sub, true),
-            FlagAction::Affected => {
-                // Check if this arm ever gets executed
-            }
-            _ => {}
-        }
-
-        match instruction.flag_affection.half_carry_flag {
-            FlagAction::Reset => self.registers.set_flag(Flag::HalfCarry, false),
-            FlagAction::Set => self.registers.set_flag(Flag::HalfCarry, true),
-            FlagAction::Affected => {
-                match instruction.flag_affection.sub_flag {
-                    FlagAction::Affected => self
-                        .registers
-                        .set_flag(Flag::HalfCarry, old_value > 0b1111 && new_value <= 0b1111),
-                    FlagAction::Set => self
-                        .registers
-                        .set_flag(Flag::HalfCarry, old_value > 0b1111 && new_value <= 0b1111),
-                    FlagAction::Reset => self.registers.set_flag(Flag::HalfCarry, false),
-                    FlagAction::NotAffected => self
-                        .registers
-                        .set_flag(Flag::HalfCarry, old_value > 0b1111 && new_value <= 0b1111),
-                }
-                // if instruction.flags[SUB_FLAG] == AFFECTED || instruction.flags[SUB_FLAG] == SET {
-                //     self.registers.set_flag(
-                //         Flag::HalfCarry,
-                //         old_value > 0b11111111 && new_value < 0b100000000,
-                //     );
-                // } else if instruction.flags[SUB_FLAG] == RESET {
-                //     self.registers.set_flag(
-                //         Flag::HalfCarry,
-                //         old_value < 0b100000000 && new_value > 0b11111111,
-                //     );
-                // }
-            }
-            _ => {}
-        }
-
-        match instruction.flag_affection.carry_flag {
-            FlagAction::Reset => self.registers.set_flag(Flag::Carry, false),
-            FlagAction::Set => self.registers.set_flag(Flag::Carry, true),
-            FlagAction::Affected => {
-                match instruction.flag_affection.sub_flag {
-                    FlagAction::Affected => self
-                        .registers
-                        .set_flag(Flag::Carry, old_value > 0b0 && old_value < new_value),
-                    FlagAction::Set => self.registers.set_flag(Flag::Carry, true),
-                    FlagAction::Reset => self.registers.set_flag(Flag::Carry, false),
-                    _ => {}
-                }
-                // if instruction.flags[SUB_FLAG] == AFFECTED || instruction.flags[SUB_FLAG] == SET {
-                //     self.registers
-                //         .set_flag(Flag::Carry, old_value > 0b0 && old_value < new_value);
-                // } else if instruction.flags[SUB_FLAG] == RESET {
-                //     self.registers.set_flag(
-                //         Flag::Carry,
-                //         old_value < 0b1111111111111111 && old_value > new_value,
-                //     );
-                // }
+        for (i, byte) in program.into_iter().enumerate() {
+            let _ = self.bus.borrow_mut().write(i as u16, byte);
+        }
+        self.clear_block_cache();
+    }
+
+    fn clear_block_cache(&mut self) {
+        if let Some(cache) = &mut self.block_cache {
+            cache.clear();
+        }
+        self.block = None;
+    }
+
+    // One M-cycle passes for the rest of the hardware as well
+    fn cycle(&mut self) {
+        self.cycles += 4;
+        for device in self.clocked.iter() {
+            device.borrow_mut().tick(4);
+        }
+    }
+
+    // Every bus access takes an M-cycle, the access happens at the end of it
+    fn read(&mut self, address: u16) -> Result<u8, CpuError> {
+        self.cycle();
+        self.peek(address)
+    }
+
+    fn write(&mut self, address: u16, value: u8) -> Result<(), CpuError> {
+        self.cycle();
+        self.poke(address, value)
+    }
+
+    // Bus accesses that take no time, for the debugger and the cpu's own registers
+    fn peek(&self, address: u16) -> Result<u8, CpuError> {
+        self.bus
+            .borrow()
+            .read(address)
+            .map_err(|_| CpuError::BusFault(address))
+    }
+
+    fn poke(&mut self, address: u16, value: u8) -> Result<(), CpuError> {
+        self.invalidate_code(address);
+        self.bus
+            .borrow_mut()
+            .write(address, value)
+            .map_err(|_| CpuError::BusWriteFault(address))
+    }
+
+    // Code that is written to has to be decoded again
+    fn invalidate_code(&mut self, address: u16) {
+        if let Some(cache) = &mut self.block_cache
+            && cache.invalidate(address)
+        {
+            self.block = None;
+        }
+    }
+
+    // The instruction at pc out of the block cache, if it is enabled and pc is in cacheable
+    // code. Running on through a block takes no lookup at all.
+    fn cached_instruction(&mut self) -> Option<&'static Instruction> {
+        if let Some((block, index)) = &mut self.block
+            && let Some((pc, instruction)) = block.get(*index)
+            && pc == self.pc
+        {
+            *index += 1;
+            return Some(instruction);
+        }
+
+        let bus = &self.bus;
+        let cache = self.block_cache.as_mut()?;
+        let block = cache.block(self.pc, |address| bus.borrow().read(address).ok())?;
+        let (_, instruction) = block.get(0)?;
+        self.block = Some((block, 1));
+        Some(instruction)
+    }
+
+    // Two byte writes, the bytes may belong to different devices
+    fn write_16(&mut self, address: u16, value: u16) -> Result<(), CpuError> {
+        let [lower, upper] = value.to_le_bytes();
+        self.write(address, lower)?;
+        self.write(address.wrapping_add(1), upper)
+    }
+
+    // The byte following the opcode
+    fn immediate_8(&mut self) -> Result<u8, CpuError> {
+        self.read(self.pc.wrapping_add(1))
+    }
+
+    // The two bytes following the opcode, stored little endian
+    fn immediate_16(&mut self) -> Result<u16, CpuError> {
+        let lower = self.read(self.pc.wrapping_add(1))?;
+        let upper = self.read(self.pc.wrapping_add(2))?;
+        Ok(u16::from_le_bytes([lower, upper]))
+    }
+
+    // Value of an 8 bit operand, HL stands for the byte at (HL)
+    fn operand(&mut self, target: Target) -> Result<u8, CpuError> {
+        match target {
+            Target::HL => self.read(self.registers.combined_register(Target::HL)?),
+            Target::D8 => self.immediate_8(),
+            _ => self.registers.get_register(target),
+        }
+    }
+
+    fn set_operand(&mut self, target: Target, value: u8) -> Result<(), CpuError> {
+        match target {
+            Target::HL => self.write(self.registers.combined_register(Target::HL)?, value),
+            _ => self.registers.set_register(target, value),
+        }
+    }
+
+    fn register_16(&self, target: Target) -> Result<u16, CpuError> {
+        match target {
+            Target::SP => Ok(self.sp),
+            _ => self.registers.combined_register(target),
+        }
+    }
+
+    fn set_register_16(&mut self, target: Target, value: u16) -> Result<(), CpuError> {
+        match target {
+            Target::SP => {
+                self.sp = value;
+                Ok(())
             }
-            _ => {}
+            _ => self.registers.set_combined_register(target, value),
         }
     }
 
-    pub fn run(&mut self) {
-        while self.tick() {}
+    // The address held by HL, which (HL+) and (HL-) increment or decrement after the access
+    fn indirect_hl(&mut self, target: Target) -> Result<u16, CpuError> {
+        let address = self.registers.combined_register(Target::HL)?;
+        let hl = match target {
+            Target::HLP => address.wrapping_add(1),
+            Target::HLM => address.wrapping_sub(1),
+            _ => address,
+        };
+        self.registers.set_combined_register(Target::HL, hl)?;
+        Ok(address)
+    }
+
+    // Applies the flag affection of the instruction: set and reset flags are forced, affected
+    // flags are taken from the operation and the rest is left alone
+    fn set_flags(&mut self, instruction: &Instruction, flags: AluFlags) -> Result<(), CpuError> {
+        let affection = &instruction.flag_affection;
+        if let FlagAction::Affected = affection.sub_flag {
+            return Err(CpuError::UnsupportedOperand(format!(
+                "{:?} affects the sub flag",
+                instruction.opcode
+            )));
+        }
+
+        for (flag, action, value) in [
+            (Flag::Zero, &affection.zero_flag, flags.zero),
+            (Flag::Sub, &affection.sub_flag, false),
+            (
+                Flag::HalfCarry,
+                &affection.half_carry_flag,
+                flags.half_carry,
+            ),
+            (Flag::Carry, &affection.carry_flag, flags.carry),
+        ] {
+            match action {
+                FlagAction::Reset => self.registers.set_flag(flag, false),
+                FlagAction::Set => self.registers.set_flag(flag, true),
+                FlagAction::Affected => self.registers.set_flag(flag, value),
+                FlagAction::NotAffected => {}
+            }
+        }
+        Ok(())
+    }
+
+    pub fn run(&mut self) -> StopReason {
+        self.run_until(RunUntil::Stopped)
     }
 
     pub fn get_reg_a(&self) -> u8 {
@@ -253,1261 +387,1038 @@ impl Cpu {
     }
 
     pub fn write_to_memory(&mut self, address: u16, byte: u8) {
+        self.invalidate_code(address);
         let _ = self.bus.borrow_mut().write(address, byte);
     }
 
-    pub fn read_memory(&self, address: u16) -> u8 {
-        self.bus.borrow().read(address).unwrap()
+    pub fn read_memory(&self, address: u16) -> Result<u8, CpuError> {
+        self.peek(address)
+    }
+
+    // Like write_to_memory, but reports a write the bus refuses
+    pub fn write_memory(&mut self, address: u16, byte: u8) -> Result<(), CpuError> {
+        self.poke(address, byte)
     }
 
     pub fn zero_memory(&mut self) {
         for i in 0..0xFFFF {
             let _ = self.bus.borrow_mut().write(i, 0);
         }
+        self.clear_block_cache();
     }
 
-    pub fn set_memory_to_end_of_program(&mut self) {
-        for i in 0..0xFFFF {
-            let _ = self.bus.borrow_mut().write(
-                i,
-                Instruction::byte_from_opcode(OpCode::EndOfProgram).unwrap(),
-            );
+    pub fn trace_line(&self) -> TraceLine {
+        let bus = self.bus.borrow();
+        let mut pc_mem = [0xFF; 4];
+        for (i, byte) in pc_mem.iter_mut().enumerate() {
+            *byte = bus.read(self.pc.wrapping_add(i as u16)).unwrap_or(0xFF);
         }
+        TraceLine::new(&self.registers, self.sp, self.pc, pc_mem)
     }
 
-    pub fn tick(&mut self) -> bool {
-        let instruction_byte = self.bus.borrow().read(self.pc).unwrap();
+    pub fn set_pc(&mut self, pc: u16) {
+        self.pc = pc;
+    }
+
+    pub fn interrupts_enabled(&self) -> bool {
+        self.interrupts_enabled
+    }
 
-        if instruction_byte == Instruction::byte_from_opcode(OpCode::EndOfProgram).unwrap() {
-            return false;
+    pub fn set_interrupts_enabled(&mut self, enabled: bool) {
+        self.interrupts_enabled = enabled;
+        self.enable_interrupts_next = false;
+    }
+
+    // Puts the registers into the state recorded by a trace line, e.g. the first line of a
+    // reference log which starts after the boot rom has run
+    pub fn load_trace_line(&mut self, line: &TraceLine) {
+        self.registers.a = line.a;
+        self.registers.f = line.f;
+        self.registers.b = line.b;
+        self.registers.c = line.c;
+        self.registers.d = line.d;
+        self.registers.e = line.e;
+        self.registers.h = line.h;
+        self.registers.l = line.l;
+        self.sp = line.sp;
+        self.pc = line.pc;
+    }
+
+    fn trace_state(&mut self) {
+        if self.tracer.is_none() {
+            return;
+        }
+        let line = self.trace_line();
+        if let Some(tracer) = &mut self.tracer
+            && let Err(e) = tracer.write_line(&line)
+        {
+            log!(Cpu, Error, "Failed to write trace: {e}");
+            self.tracer = None;
         }
+    }
 
-        let pc_increment: u16 =
-            if let Some(instruction) = Instruction::fetch(instruction_byte, self.is_prefixed) {
-                println!(
-                    "Pc: {}, Byte: {instruction_byte}, char: {}",
-                    self.pc, instruction_byte as char
-                );
-                println!("Instruction: {instruction:?}");
-                self.is_prefixed = false;
-                self.execute(instruction)
-            } else {
-                panic!(
-                    "Uknown instruction: 0x{:x} @ address {}",
-                    instruction_byte, self.pc
-                );
-            };
+    // The upper byte is pushed first, after an internal cycle
+    fn push_16(&mut self, value: u16) -> Result<(), CpuError> {
+        let [lower, upper] = value.to_le_bytes();
+        self.cycle();
+        self.sp = self.sp.wrapping_sub(1);
+        self.write(self.sp, upper)?;
+        self.sp = self.sp.wrapping_sub(1);
+        self.write(self.sp, lower)
+    }
+
+    fn pop_16(&mut self) -> Result<u16, CpuError> {
+        let lower = self.read(self.sp)?;
+        let upper = self.read(self.sp.wrapping_add(1))?;
+        self.sp = self.sp.wrapping_add(2);
+        Ok(u16::from_le_bytes([lower, upper]))
+    }
+
+    // The highest priority interrupt that is both requested and enabled, along with IF
+    fn pending_interrupt(&self) -> Option<(Interrupt, u8)> {
+        let bus = self.bus.borrow();
+        let flags = bus.read(INTERRUPT_FLAG_REGISTER as u16).unwrap_or(0);
+        let enable = bus.read(INTERRUPT_ENABLE_REGISTER as u16).unwrap_or(0);
+        Interrupt::pending(flags, enable).map(|interrupt| (interrupt, flags))
+    }
+
+    // Jumps to the vector of the highest priority pending interrupt, if interrupts are enabled
+    fn service_interrupt(&mut self) -> Result<bool, CpuError> {
+        if !self.interrupts_enabled {
+            return Ok(false);
+        }
+
+        match self.pending_interrupt() {
+            Some((interrupt, flags)) => {
+                // Two internal cycles, pushing pc and one more to jump to the vector
+                self.interrupts_enabled = false;
+                self.poke(INTERRUPT_FLAG_REGISTER as u16, flags & !interrupt.bit())?;
+                self.cycle();
+                self.push_16(self.pc)?;
+                self.pc = interrupt.vector();
+                self.cycle();
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    // STOP ends once one of the P1 input lines is low, i.e. a button on a selected row is held
+    fn joypad_pressed(&self) -> bool {
+        let bus = self.bus.borrow();
+        bus.read(JOYPAD as u16).is_ok_and(|p1| p1 & 0x0F != 0x0F)
+    }
+
+    // Executes one byte of an instruction, returns false while the cpu is stopped
+    pub fn tick(&mut self) -> Result<bool, CpuError> {
+        // A stopped cpu lets time pass until a button wakes it up
+        if self.is_stopped {
+            if !self.joypad_pressed() {
+                self.cycle();
+                return Ok(false);
+            }
+            self.is_stopped = false;
+        }
+
+        // A locked up cpu only lets time pass
+        if self.lock_up.is_some() {
+            self.cycle();
+            return Ok(true);
+        }
+
+        // A halted cpu wakes up once an interrupt is pending, whether or not it gets serviced
+        if self.is_halted {
+            if self.pending_interrupt().is_none() {
+                self.cycle();
+                return Ok(true);
+            }
+            self.is_halted = false;
+        }
+
+        if !self.is_prefixed && self.service_interrupt()? {
+            return Ok(true);
+        }
+
+        // Whether an EI before this instruction is waiting for it to finish
+        let enable_interrupts = self.enable_interrupts_next;
+
+        // A prefixed instruction spans two ticks, only trace its first byte
+        if !self.is_prefixed {
+            self.trace_state();
+        }
+
+        // Cached prefixed instructions are executed in one tick
+        if !self.is_prefixed
+            && let Some(instruction) = self.cached_instruction()
+        {
+            log!(
+                Cpu,
+                Debug,
+                "pc: {:04X} {} (cached)",
+                self.pc,
+                instruction.opcode
+            );
+            self.pc = self.execute(instruction)?;
+            self.finish_instruction(enable_interrupts);
+            return Ok(true);
+        }
+
+        // pc stays on the prefix until the prefixed instruction has executed
+        let address = if self.is_prefixed {
+            self.pc.wrapping_add(1)
+        } else {
+            self.pc
+        };
+        // execute fetches the opcode again in the M-cycle it belongs to
+        let instruction_byte = self.peek(address)?;
+
+        let instruction = match Instruction::fetch(instruction_byte, self.is_prefixed) {
+            Some(instruction) => instruction,
+            None if !self.is_prefixed && Instruction::is_illegal(instruction_byte) => {
+                self.lock_up = Some(LockUp {
+                    pc: self.pc,
+                    opcode: instruction_byte,
+                });
+                self.cycle();
+                return Ok(true);
+            }
+            None => {
+                return Err(CpuError::UnknownOpcode {
+                    opcode: instruction_byte,
+                    prefixed: self.is_prefixed,
+                });
+            }
+        };
+
+        log!(Cpu, Debug, "pc: {:04X} {}", self.pc, instruction.opcode);
+        self.is_prefixed = false;
+        self.pc = self.execute(instruction)?;
+        self.finish_instruction(enable_interrupts);
+        Ok(true)
+    }
+
+    // Applies an EI that was pending when the instruction started, unless it was DI. The CB
+    // prefix on its own is not an instruction yet.
+    fn finish_instruction(&mut self, enable_interrupts: bool) {
+        if enable_interrupts && self.enable_interrupts_next && !self.is_prefixed {
+            self.interrupts_enabled = true;
+            self.enable_interrupts_next = false;
+        }
+    }
 
-        self.pc = pc_increment;
-        true
+    // The error that froze the cpu, if any. A faulted cpu does not execute anything anymore.
+    pub fn fault(&self) -> Option<&CpuFault> {
+        self.fault.as_ref()
     }
 
-    #[allow(unused_variables)]
-    pub fn execute(&mut self, instruction: &Instruction) -> u16 {
+    // Runs an instruction M-cycle by M-cycle, starting with the fetch of its opcode. The
+    // internal cycles left after the last bus access are spent at the end.
+    pub fn execute(&mut self, instruction: &Instruction) -> Result<u16, CpuError> {
+        // The length and cycles of the prefixed instruction include the prefix
+        if let OpCode::CB = instruction.opcode {
+            self.is_prefixed = true;
+            return Ok(self.pc);
+        }
+
+        let start = self.cycles;
+        self.read(self.pc)?;
+        if instruction.is_prefixed() {
+            self.read(self.pc.wrapping_add(1))?;
+        }
+
         let mut pc_increment = instruction.length as u16;
         let mut cycles = instruction.cycles;
 
-        let optional_cycles = instruction.optional_cycles;
+        // Taken jumps, calls and returns set pc themselves, the others take the shorter time
+        let mut branch = |taken: bool| {
+            if taken {
+                pc_increment = 0;
+            } else {
+                cycles = instruction.optional_cycles;
+            }
+        };
 
         match instruction.opcode {
             OpCode::ADC(target) => {
-                self.adc(target);
+                self.adc(instruction, target)?;
             }
             OpCode::ADD(target) => {
-                self.add(target);
+                self.add(instruction, target)?;
             }
             OpCode::ADD16(dst, src) => {
-                self.add_16(dst, src);
+                self.add_16(instruction, dst, src)?;
             }
             OpCode::AND(target) => {
-                self.and(target);
+                self.and(instruction, target)?;
             }
             OpCode::BIT(bit, target) => {
-                self.bit(bit, target);
+                self.bit(instruction, bit, target)?;
             }
             OpCode::CALL(flag) => {
-                if self.call(flag) {
-                    pc_increment = 0;
-                }
+                branch(self.call(flag)?);
             }
             OpCode::CALL_UC => {
-                if self.registers.get_flag(Flag::Zero) {
-                    self.call(Flag::Zero);
-                } else {
-                    self.call(Flag::NotZero);
-                }
+                self.call_uc()?;
                 pc_increment = 0;
             }
             OpCode::CCF => {
-                self.registers
-                    .set_flag(Flag::Carry, !self.registers.get_flag(Flag::Carry));
+                self.ccf();
             }
             OpCode::CP(target) => {
-                self.cp(target);
+                self.cp(instruction, target)?;
             }
             OpCode::CPL => {
-                self.cpl();
+                self.cpl(instruction)?;
             }
-            OpCode::DDA => {
-                self.dda();
+            OpCode::DAA => {
+                self.daa();
             }
             OpCode::DEC(target) => {
-                self.dec(target);
+                self.dec(instruction, target)?;
             }
             OpCode::DEC16(target) => {
-                self.dec_16(target);
+                self.dec_16(target)?;
             }
             OpCode::DisableInterrupt => {
                 self.interrupts_enabled = false;
+                self.enable_interrupts_next = false;
             }
             OpCode::EnableInterrupt => {
-                self.interrupts_enabled = true;
+                self.enable_interrupts_next = !self.interrupts_enabled;
+            }
+            OpCode::HALT => {
+                self.is_halted = true;
             }
-            OpCode::HALT => {}
             OpCode::INC(target) => {
-                self.inc(target);
+                self.inc(instruction, target)?;
             }
             OpCode::INC16(target) => {
-                self.inc_16(target);
+                self.inc_16(target)?;
             }
             OpCode::JUMP(flag) => {
-                if self.jump_by_flag(flag) {
-                    pc_increment = 0;
-                } else {
-                    cycles = optional_cycles;
-                }
+                branch(self.jump_by_flag(flag)?);
             }
             OpCode::JP => {
-                self.jp();
+                self.jp()?;
+                pc_increment = 0;
             }
             OpCode::JR(flag) => {
-                self.jr(flag);
+                branch(self.jr(flag)?);
             }
             OpCode::JP_HL => {
-                self.jump_hl();
+                self.jump_hl()?;
+                pc_increment = 0;
             }
             OpCode::JRUC => {
-                self.jruc();
+                self.jruc()?;
+                pc_increment = 0;
             }
             OpCode::LD(dst, src) => {
                 if self.registers.is_16bit_target(dst) || self.registers.is_16bit_target(src) {
-                    self.load_16(dst, src);
+                    self.load_16(instruction, dst, src)?;
                 } else {
-                    self.load(dst, src);
+                    self.load(dst, src)?;
                 }
             }
             OpCode::LDH(dst, src) => {
-                self.ldh(dst, src);
+                self.ldh(dst, src)?;
             }
             OpCode::NOP => {}
             OpCode::OR(target) => {
-                self.or(target);
+                self.or(instruction, target)?;
             }
             OpCode::POP(target) => {
-                self.pop(target);
+                self.pop(target)?;
             }
             OpCode::PUSH(target) => {
-                self.push(target);
-            }
-            OpCode::CB => {
-                self.is_prefixed = true;
+                self.push(target)?;
             }
             OpCode::RES(bit, target) => {
-                self.res(bit, target);
+                self.res(bit, target)?;
             }
             OpCode::RET(flag) => {
-                if self.ret(flag) {
-                    pc_increment = 0;
-                }
+                branch(self.ret(flag)?);
             }
             OpCode::RET_UC => {
-                if self.registers.get_flag(Flag::Zero) {
-                    self.ret(Flag::Zero);
-                } else {
-                    self.ret(Flag::NotZero);
-                }
+                self.pc = self.pop_16()?;
                 pc_increment = 0;
             }
             OpCode::RETI => {
-                if self.registers.get_flag(Flag::Zero) {
-                    self.ret(Flag::Zero);
-                } else {
-                    self.ret(Flag::NotZero);
-                }
+                self.pc = self.pop_16()?;
                 pc_increment = 0;
                 self.interrupts_enabled = true;
             }
             OpCode::RL(target) => {
-                self.rl(target);
+                self.rl(target)?;
             }
             OpCode::RLA => {
-                self.rla();
+                self.rla()?;
             }
             OpCode::RLC(target) => {
-                self.rlc(target);
+                self.rlc(target)?;
             }
             OpCode::RLCA => {
-                self.rlca();
+                self.rlca()?;
             }
             OpCode::RR(target) => {
-                self.rr(target);
+                self.rr(target)?;
             }
             OpCode::RRA => {
-                self.rra();
+                self.rra()?;
             }
             OpCode::RRC(target) => {
-                self.rrc(target);
+                self.rrc(target)?;
             }
             OpCode::RRCA => {
-                self.rrca();
+                self.rrca()?;
             }
             OpCode::RST(address) => {
-                self.rst(address);
+                self.rst(address)?;
                 pc_increment = 0;
             }
             OpCode::SBC(target) => {
-                self.sbc(target);
+                self.sbc(instruction, target)?;
             }
             OpCode::SCF => {
-                self.registers.set_flag(Flag::Carry, true);
+                self.scf();
             }
             OpCode::SET(bit, target) => {
-                self.set(bit, target);
+                self.set(bit, target)?;
             }
             OpCode::SUB(target) => {
-                self.sub(target);
+                self.sub(instruction, target)?;
             }
             OpCode::STOP => {
                 self.is_stopped = true;
             }
             OpCode::SLA(target) => {
-                self.sla(target);
+                self.sla(target)?;
             }
             OpCode::SRL(target) => {
-                self.srl(target);
+                self.srl(target)?;
             }
             OpCode::SRA(target) => {
-                self.sra(target);
+                self.sra(target)?;
             }
             OpCode::SWAP(target) => {
-                self.swap(target);
-            }
-            OpCode::XOR(target) => self.xor(target),
-            OpCode::STORE(dst, src) => self.store(dst, src),
-            _ => {
-                panic!("Unimplemented");
+                self.swap(target)?;
             }
+            OpCode::XOR(target) => self.xor(instruction, target)?,
+            _ => return Err(unsupported(instruction.opcode)),
         }
 
-        self.pc.wrapping_add(pc_increment)
+        debug_assert!(
+            self.cycles - start <= cycles as u64,
+            "{:?} took {} cycles instead of {cycles}",
+            instruction.opcode,
+            self.cycles - start
+        );
+        while self.cycles - start < cycles as u64 {
+            self.cycle();
+        }
+        Ok(self.pc.wrapping_add(pc_increment))
     }
 
-    #[named]
-    fn adc(&mut self, reg: Target) {
-        let v = match reg {
-            Target::HL => self
-                .bus
-                .borrow()
-                .read(self.registers.combined_register(reg))
-                .unwrap(),
-            Target::D8 => self.bus.borrow().read(self.pc + 1).unwrap(),
-            _ => self.registers.get_register(reg),
-        };
-
-        let carry = self.registers.filter_flag(Flag::Carry);
-        self.registers.a = self.registers.a.wrapping_add(v + carry);
+    fn adc(&mut self, instruction: &Instruction, reg: Target) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "adc reg: {reg}");
+        let v = self.operand(reg)?;
+        let carry = self.registers.get_flag(Flag::Carry);
+        let (result, flags) = alu::add_8(self.registers.a, v, carry);
+        self.registers.a = result;
 
-        self.set_flags(
-            Instruction::from_opcode(OpCode::ADC(reg)).unwrap(),
-            v,
-            self.registers.a,
-        );
+        self.set_flags(instruction, flags)
     }
 
-    #[named]
-    fn add(&mut self, src: Target) {
-        log!("src: {src}");
-        let v = match src {
-            Target::HL => self
-                .bus
-                .borrow()
-                .read(self.registers.combined_register(Target::HL))
-                .unwrap(),
-            Target::D8 => self.bus.borrow().read(self.pc + 1).unwrap(),
-            _ => self.registers.get_register(src),
-        };
-
-        self.registers.a = self.registers.a.wrapping_add(v);
+    fn add(&mut self, instruction: &Instruction, src: Target) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "add src: {src}");
+        let v = self.operand(src)?;
+        let (result, flags) = alu::add_8(self.registers.a, v, false);
+        self.registers.a = result;
 
-        self.set_flags(
-            Instruction::from_opcode(OpCode::ADD(src)).unwrap(),
-            v,
-            self.registers.a,
-        );
+        self.set_flags(instruction, flags)
     }
 
-    #[named]
-    fn add_16(&mut self, dst: Target, src: Target) {
-        log!("src: {src} dst: {dst}");
-        let v: u16 = match src {
-            Target::HL => self.registers.combined_register(src),
-            Target::BC => self.registers.combined_register(src),
-            Target::DE => self.registers.combined_register(src),
-            Target::SP => self.sp,
-            Target::R8 => self.bus.borrow().read(self.pc + 1).unwrap() as u16,
-            _ => {
-                unimplemented!(
-                    "Unimplemented {}",
-                    format!("{:#?}", OpCode::ADD16(dst, src))
-                );
-            }
-        };
-
-        let (old, new) = match dst {
-            Target::HL => {
-                let old = self.registers.combined_register(dst);
-                let new = old + v;
-                self.registers.l = (new & 0xFF) as u8;
-                self.registers.h = (new >> 8) as u8;
-                (old, new)
-            }
-            Target::SP => {
-                let old = self.sp;
-                self.sp += v;
-                let new = self.sp;
-                (old, new)
+    fn add_16(
+        &mut self,
+        instruction: &Instruction,
+        dst: Target,
+        src: Target,
+    ) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "add_16 src: {src} dst: {dst}");
+        let flags = match (dst, src) {
+            (Target::HL, Target::BC | Target::DE | Target::HL | Target::SP) => {
+                let hl = self.registers.combined_register(Target::HL)?;
+                let (result, flags) = alu::add_16(hl, self.register_16(src)?);
+                self.registers.set_combined_register(Target::HL, result)?;
+                flags
             }
-            _ => {
-                unimplemented!(
-                    "Unimplemented {}",
-                    format!("{:#?}", OpCode::ADD16(dst, src))
-                );
+            (Target::SP, Target::R8) => {
+                let (result, flags) = alu::add_sp(self.sp, self.immediate_8()?);
+                self.sp = result;
+                flags
             }
+            _ => return Err(unsupported(OpCode::ADD16(dst, src))),
         };
 
-        self.set_flags_16(
-            Instruction::from_opcode(OpCode::ADD16(dst, src)).unwrap(),
-            old,
-            new,
-        );
+        self.set_flags(instruction, flags)
     }
 
-    #[named]
-    fn and(&mut self, src: Target) {
-        log!("src: {src}");
+    fn and(&mut self, instruction: &Instruction, src: Target) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "and src: {src}");
         let old = self.registers.a;
-        match src {
-            Target::HL => {
-                self.registers.a &= self
-                    .bus
-                    .borrow()
-                    .read(self.registers.combined_register(src))
-                    .unwrap()
-            }
-            Target::D8 => self.registers.a &= self.bus.borrow().read(self.pc + 1).unwrap(),
-            _ => {
-                let new = and!(
-                    self.registers.get_register(Target::A),
-                    self.registers.get_register(src)
-                );
-                self.registers.set_register(Target::A, new);
-            }
-        }
-        self.set_flags(
-            Instruction::from_opcode(OpCode::AND(src)).unwrap(),
-            old,
-            self.registers.a,
-        );
+        self.registers.a = and!(old, self.operand(src)?);
+        self.set_flags(instruction, AluFlags::from_result(self.registers.a))
     }
 
-    fn bit(&mut self, bit_pos: u8, reg: Target) {
-        let v = match reg {
-            Target::HL => self
-                .bus
-                .borrow()
-                .read(self.registers.combined_register(Target::HL))
-                .unwrap(),
-            _ => self.registers.get_register(reg),
-        };
+    fn bit(&mut self, instruction: &Instruction, bit_pos: u8, reg: Target) -> Result<(), CpuError> {
+        let v = self.operand(reg)?;
         let bit = v & (1 << bit_pos);
-
-        // Carry not affected
-        // self.registers.set_flag(Flag::Zero, bit == 0);
-        // self.registers.set_flag(Flag::Sub, false);
-        // self.registers.set_flag(Flag::HalfCarry, true);
-        self.set_flags(
-            Instruction::from_opcode(OpCode::BIT(bit_pos, reg)).unwrap(),
-            v,
-            bit,
-        );
+        self.set_flags(instruction, AluFlags::from_result(bit))
     }
 
-    #[named]
-    fn call(&mut self, flag: Flag) -> bool {
-        log!("flag: {flag}");
+    fn call(&mut self, flag: Flag) -> Result<bool, CpuError> {
+        log!(Cpu, Trace, "call flag: {flag}");
+        // The address is read whether or not the call is taken
+        let address = self.immediate_16()?;
         if self.registers.get_flag(flag) {
-            let mut bus = self.bus.borrow_mut();
-            let address = match bus.read(self.pc + 3) {
-                Ok(lower) => match bus.read(self.pc + 4) {
-                    Ok(higher) => shift_left!(higher, 8, u16) | lower as u16,
-                    Err(e) => {
-                        println!("{e}");
-                        panic!()
-                    }
-                },
-                Err(e) => {
-                    println!("{e}");
-                    panic!()
-                }
-            };
-
-            log!("address: {address}");
-            let _ = bus.write_16(self.sp, self.pc + 3);
-
-            self.pc = match bus.read(self.pc + 1) {
-                Ok(lower) => match bus.read(self.pc + 2) {
-                    Ok(upper) => shift_left!(upper, 8, u16) | lower as u16,
-                    Err(e) => {
-                        println!("{e}");
-                        panic!()
-                    }
-                },
-                Err(e) => {
-                    println!("{e}");
-                    panic!()
-                }
-            };
-
-            true
+            self.push_16(self.pc.wrapping_add(3))?;
+            self.pc = address;
+            Ok(true)
         } else {
             // No flags affected
-            false
+            Ok(false)
         }
     }
 
-    fn cp(&mut self, reg: Target) {
-        let v = match reg {
-            Target::HL => shift_left!(self.registers.h, 8, u16) | self.registers.l as u16,
-            Target::D8 => self.bus.borrow().read(self.pc + 1).unwrap() as u16,
-            _ => self.registers.get_register(reg) as u16,
-        };
+    fn call_uc(&mut self) -> Result<(), CpuError> {
+        let address = self.immediate_16()?;
+        log!(Cpu, Trace, "call_uc address: {address}");
+        self.push_16(self.pc.wrapping_add(3))?;
+        self.pc = address;
+        Ok(())
+    }
 
-        let result = self.registers.a as u16 - v;
-        self.set_flags_16(
-            Instruction::from_opcode(OpCode::CP(reg)).unwrap(),
-            v,
-            result,
-        );
+    fn ccf(&mut self) {
+        self.registers.set_flag(Flag::Sub, false);
+        self.registers.set_flag(Flag::HalfCarry, false);
+        self.registers
+            .set_flag(Flag::Carry, !self.registers.get_flag(Flag::Carry));
     }
 
-    #[named]
-    fn cpl(&mut self) {
-        log!("");
+    fn cp(&mut self, instruction: &Instruction, reg: Target) -> Result<(), CpuError> {
+        let v = self.operand(reg)?;
+        let (_, flags) = alu::sub_8(self.registers.a, v, false);
+        self.set_flags(instruction, flags)
+    }
+
+    fn cpl(&mut self, instruction: &Instruction) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "cpl");
         self.registers.a = !self.registers.a;
 
-        self.set_flags(Instruction::from_opcode(OpCode::CPL).unwrap(), 0, 0);
-        // self.registers.set_flag(Flag::Sub, true);
-        // self.registers.set_flag(Flag::HalfCarry, true);
+        self.set_flags(instruction, AluFlags::default())
     }
 
-    fn dda(&mut self) {
-        let mut correction: u8 = 0;
-        let carry_flag = self.registers.get_flag(Flag::HalfCarry);
+    // Adjusts A back to binary coded decimal after an addition or a subtraction of two BCD numbers
+    fn daa(&mut self) {
+        let a = self.registers.a;
+        let subtract = self.registers.get_flag(Flag::Sub);
+        let half_carry = self.registers.get_flag(Flag::HalfCarry);
+        let mut carry = self.registers.get_flag(Flag::Carry);
 
-        if carry_flag || and!(self.registers.a, 0x0F) > 9 {
+        // A subtraction can only have borrowed, so only the flags decide the correction
+        let mut correction: u8 = 0;
+        if half_carry || (!subtract && and!(a, 0x0F) > 0x09) {
             correction |= 0x06;
         }
-
-        if carry_flag || self.registers.a > 0x9F {
+        if carry || (!subtract && a > 0x99) {
             correction |= 0x60;
-            self.registers.set_flag(Flag::Carry, true);
+            carry = true;
         }
 
-        self.registers.a = (self.registers.a).wrapping_add(correction);
+        self.registers.a = if subtract {
+            a.wrapping_sub(correction)
+        } else {
+            a.wrapping_add(correction)
+        };
         self.registers.set_flag(Flag::Zero, self.registers.a == 0);
         self.registers.set_flag(Flag::HalfCarry, false);
-        // Do not call self.set_flags() for this instruction, since it is an edge case
-    }
-
-    #[named]
-    fn dec(&mut self, target: Target) {
-        log!("target: {target}");
-        let f = |reg: Target, cpu: &mut Cpu| {
-            let old = cpu.registers.get_register(reg);
-            let new = (old).wrapping_sub(1);
-            cpu.set_flags(
-                Instruction::from_opcode(OpCode::DEC(target)).unwrap(),
-                old,
-                new,
-            );
-            cpu.registers.set_register(reg, new);
-        };
-        match target {
-            Target::HL => {
-                let mut v = self.registers.combined_register(target);
-                // self.registers.set_flag(Flag::HalfCarry, v == 0b10000);
-                let old = v;
-                v = v.wrapping_sub(1);
-                self.registers.set_combined_register(Target::HL, v);
-                // self.registers.set_flag(Flag::Zero, v == 0);
-                // self.registers.set_flag(Flag::Sub, true);
-                self.set_flags_16(
-                    Instruction::from_opcode(OpCode::DEC(target)).unwrap(),
-                    old,
-                    v,
-                );
-            }
-            _ => f(target, self),
-        };
+        self.registers.set_flag(Flag::Carry, carry);
+    }
 
-        // self.registers.set_flag(Flag::HalfCarry, false);
-        // self.registers.set_flag(Flag::Carry, false);
-        // self.registers.set_flag(Flag::Sub, true);
-    }
-
-    #[named]
-    fn dec_16(&mut self, target: Target) {
-        log!("target: {target}");
-        if target == Target::SP {
-            let old = self.sp;
-            self.sp = self.sp.wrapping_sub(1);
-            let new = self.sp;
-            self.set_flags_16(
-                Instruction::from_opcode(OpCode::DEC16(target)).unwrap(),
-                old,
-                new,
-            );
-        } else {
-            let old = self.registers.combined_register(target);
-            let new = old.wrapping_sub(1);
-            self.registers.set_combined_register(target, new);
-            self.set_flags_16(
-                Instruction::from_opcode(OpCode::DEC16(target)).unwrap(),
-                old,
-                new,
-            );
-        }
+    fn dec(&mut self, instruction: &Instruction, target: Target) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "dec target: {target}");
+        let (result, flags) = alu::sub_8(self.operand(target)?, 1, false);
+        self.set_operand(target, result)?;
+        self.set_flags(instruction, flags)
     }
 
-    fn inc(&mut self, target: Target) {
-        let f = |reg: Target, cpu: &mut Cpu| {
-            let old = cpu.registers.get_register(reg);
-            let new = old.wrapping_add(1);
-            cpu.set_flags(
-                Instruction::from_opcode(OpCode::INC(target)).unwrap(),
-                old,
-                new,
-            );
-            cpu.registers.set_register(reg, new);
-        };
-        match target {
-            Target::HL => {
-                let mut v = self.registers.combined_register(target);
-                let old = v;
-                // self.registers.set_flag(Flag::HalfCarry, v == 0b1111);
-                v = v.wrapping_add(1);
-                self.registers.set_combined_register(Target::HL, v);
-                // self.registers.set_flag(Flag::Zero, v == 0);
-                // self.registers.set_flag(Flag::Sub, false);
-                self.set_flags_16(
-                    Instruction::from_opcode(OpCode::INC(target)).unwrap(),
-                    old,
-                    v,
-                );
-            }
-            _ => f(target, self),
-        }
+    fn dec_16(&mut self, target: Target) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "dec_16 target: {target}");
+        let v = self.register_16(target)?;
+        self.set_register_16(target, v.wrapping_sub(1))
     }
 
-    #[named]
-    fn inc_16(&mut self, target: Target) {
-        log!("target: {target}");
-        if target == Target::SP {
-            let old = self.sp;
-            self.sp = self.sp.wrapping_add(1);
-            self.set_flags_16(
-                Instruction::from_opcode(OpCode::INC16(target)).unwrap(),
-                old,
-                self.sp,
-            );
-        } else {
-            let old = self.registers.combined_register(target);
-            let new = old.wrapping_add(1);
-            self.registers.set_combined_register(target, new);
-            self.set_flags_16(
-                Instruction::from_opcode(OpCode::INC16(target)).unwrap(),
-                old,
-                new,
-            );
-        }
+    fn inc(&mut self, instruction: &Instruction, target: Target) -> Result<(), CpuError> {
+        let (result, flags) = alu::add_8(self.operand(target)?, 1, false);
+        self.set_operand(target, result)?;
+        self.set_flags(instruction, flags)
+    }
+
+    fn inc_16(&mut self, target: Target) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "inc_16 target: {target}");
+        let v = self.register_16(target)?;
+        self.set_register_16(target, v.wrapping_add(1))
     }
 
-    #[named]
-    fn jp(&mut self) {
-        log!();
-        let lower_byte = self.bus.borrow().read(self.pc + 1).unwrap() as u16;
-        let upper_byte = self.bus.borrow().read(self.pc + 2).unwrap() as u16;
-        self.pc = (upper_byte << 8) + lower_byte;
-        println!("pc: {}", self.pc);
+    fn jp(&mut self) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "jp");
+        self.pc = self.immediate_16()?;
+        Ok(())
     }
 
-    #[named]
-    fn jump_by_flag(&mut self, flag: Flag) -> bool {
-        log!("flag: {flag}");
+    fn jump_by_flag(&mut self, flag: Flag) -> Result<bool, CpuError> {
+        log!(Cpu, Trace, "jump_by_flag flag: {flag}");
+        let address = self.immediate_16()?;
         if self.registers.get_flag(flag) {
-            self.pc = shift_left!(self.bus.borrow().read(self.pc + 2).unwrap(), 8, u16)
-                | self.bus.borrow().read(self.pc + 1).unwrap() as u16;
-            true
+            self.pc = address;
+            Ok(true)
         } else {
-            false
+            Ok(false)
         }
     }
 
-    #[named]
-    fn jruc(&mut self) {
-        log!("");
-        self.pc = self.pc + self.bus.borrow().read(self.pc + 1).unwrap() as u16;
+    // The offset is signed and relative to the instruction following the jump
+    fn jruc(&mut self) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "jruc");
+        let offset = self.immediate_8()?;
+        self.jump_relative(offset);
+        Ok(())
     }
 
-    #[named]
-    fn jr(&mut self, flag: Flag) -> bool {
-        log!("flag: {flag}");
+    fn jump_relative(&mut self, offset: u8) {
+        self.pc = self
+            .pc
+            .wrapping_add(2)
+            .wrapping_add_signed(offset as i8 as i16);
+    }
+
+    fn jr(&mut self, flag: Flag) -> Result<bool, CpuError> {
+        log!(Cpu, Trace, "jr flag: {flag}");
+        let offset = self.immediate_8()?;
         if self.registers.get_flag(flag) {
-            self.pc = self.pc + self.bus.borrow().read(self.pc + 1).unwrap() as u16;
-            true
+            self.jump_relative(offset);
+            Ok(true)
         } else {
-            false
+            Ok(false)
         }
     }
 
-    #[named]
-    fn jump_hl(&mut self) {
-        log!("");
-        self.pc = self.registers.combined_register(Target::HL);
+    fn jump_hl(&mut self) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "jump_hl");
+        self.pc = self.registers.combined_register(Target::HL)?;
+        Ok(())
     }
 
-    #[allow(unused_assignments)]
-    #[named]
-    pub fn load(&mut self, dst: Target, src: Target) {
-        log!("src: {src} dst: {dst}");
+    // 8 bit loads. BC, DE, HLP, HLM and A16 stand for the byte at the address they hold,
+    // HL follows the operand convention.
+    pub fn load(&mut self, dst: Target, src: Target) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "load src: {src} dst: {dst}");
         let v = match src {
-            Target::HL | Target::BC | Target::DE => self
-                .bus
-                .borrow()
-                .read(self.registers.combined_register(src))
-                .unwrap(),
-            Target::A8 => self
-                .bus
-                .borrow()
-                .read(0xFF00 + self.bus.borrow().read(self.pc + 1).unwrap() as u16)
-                .unwrap(),
-            Target::A16 => self
-                .bus
-                .borrow()
-                .read(
-                    shift_left!(self.bus.borrow().read(self.pc + 1).unwrap(), 8, u16)
-                        | self.bus.borrow().read(self.pc + 2).unwrap() as u16,
-                )
-                .unwrap(),
-            Target::R8 => self
-                .bus
-                .borrow()
-                .read(self.pc + self.bus.borrow().read(self.pc + 1).unwrap() as u16)
-                .unwrap(),
-            Target::D8 => self.bus.borrow().read(self.pc + 1).unwrap(),
-            _ => self.registers.get_register(src),
+            Target::BC | Target::DE => self.read(self.registers.combined_register(src)?)?,
+            Target::HLP | Target::HLM => {
+                let address = self.indirect_hl(src)?;
+                self.read(address)?
+            }
+            Target::A16 => {
+                let address = self.immediate_16()?;
+                self.read(address)?
+            }
+            _ => self.operand(src)?,
         };
 
         match dst {
-            Target::HL => self.registers.set_combined_register(Target::HL, v as u16),
-            Target::BC => self.registers.set_combined_register(Target::BC, v as u16),
-            Target::DE => self.registers.set_combined_register(Target::DE, v as u16),
-            Target::A8 => {
-                let mut bus = self.bus.borrow_mut();
-                let addr = 0xFF00 + bus.read(self.pc + 1).unwrap() as u16;
-                let _ = bus.write(addr, v);
+            Target::BC | Target::DE => self.write(self.registers.combined_register(dst)?, v)?,
+            Target::HLP | Target::HLM => {
+                let address = self.indirect_hl(dst)?;
+                self.write(address, v)?;
             }
             Target::A16 => {
-                let mut bus = self.bus.borrow_mut();
-                let addr = shift_left!(bus.read(self.pc + 1).unwrap(), 8, u16)
-                    | bus.read(self.pc + 2).unwrap() as u16;
-                let _ = bus.write(addr, v);
+                let address = self.immediate_16()?;
+                self.write(address, v)?;
             }
-            _ => self.registers.set_register(dst, v),
+            _ => self.set_operand(dst, v)?,
         };
+        Ok(())
     }
-    #[named]
-    pub fn ldh(&mut self, dst: Target, src: Target) {
-        log!("src: {src} dst: {dst}");
-        let v = match src {
-            Target::A => self.registers.a as u16,
-            Target::A8 => {
-                0xFF00
-                    + self
-                        .bus
-                        .borrow()
-                        .read(self.bus.borrow().read(self.pc + 1).unwrap() as u16)
-                        .unwrap() as u16
-            }
-            _ => panic!(),
-        };
 
-        match dst {
-            Target::A => self.registers.a = v as u8,
-            Target::A8 => {
-                let mut bus = self.bus.borrow_mut();
-                let addr = 0xFF00 + bus.read(bus.read(self.pc + 1).unwrap() as u16).unwrap() as u16;
-                let _ = bus.write(addr, v as u8);
+    // Accesses to 0xFF00 plus the immediate byte or plus C
+    pub fn ldh(&mut self, dst: Target, src: Target) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "ldh src: {src} dst: {dst}");
+        match (dst, src) {
+            (Target::A, Target::A8) => {
+                let address = 0xFF00 | self.immediate_8()? as u16;
+                self.registers.a = self.read(address)?;
+            }
+            (Target::A, Target::C) => {
+                self.registers.a = self.read(0xFF00 | self.registers.c as u16)?;
+            }
+            (Target::A8, Target::A) => {
+                let address = 0xFF00 | self.immediate_8()? as u16;
+                self.write(address, self.registers.a)?;
             }
-            _ => panic!(),
+            (Target::C, Target::A) => {
+                self.write(0xFF00 | self.registers.c as u16, self.registers.a)?;
+            }
+            _ => return Err(unsupported(OpCode::LDH(dst, src))),
         }
+        Ok(())
     }
 
-    #[named]
-    pub fn load_16(&mut self, dst: Target, src: Target) {
-        log!("src: {src} dst: {dst}");
-        let v = match src {
-            Target::HL | Target::BC | Target::DE => self
-                .bus
-                .borrow()
-                .read(self.registers.combined_register(src))
-                .unwrap() as u16,
-            Target::A8 => self
-                .bus
-                .borrow()
-                .read(0xFF00 + self.bus.borrow().read(self.pc + 1).unwrap() as u16)
-                .unwrap() as u16,
-            Target::A16 => self
-                .bus
-                .borrow()
-                .read(
-                    shift_left!(self.bus.borrow().read(self.pc + 1).unwrap(), 8, u16)
-                        + self.bus.borrow().read(self.pc + 2).unwrap() as u16,
-                )
-                .unwrap() as u16,
-            Target::D8 => self.bus.borrow().read(self.pc + 1).unwrap() as u16,
-            Target::D16 => {
-                shift_left!(self.bus.borrow().read(self.pc + 1).unwrap(), 8, u16)
-                    + (self.bus.borrow().read(self.pc + 2).unwrap() as u16)
-            }
-            Target::SP => self.sp,
-            Target::SP_R8 => self
-                .bus
-                .borrow()
-                .read(self.sp + self.bus.borrow().read(self.pc + 1).unwrap() as u16)
-                .unwrap() as u16,
-            _ => self.registers.get_register(src) as u16,
-        };
-
-        match dst {
-            Target::HL | Target::BC | Target::DE => {
-                let _ = self.bus.borrow_mut().write_16(
-                    self.bus
-                        .borrow()
-                        .read(self.registers.combined_register(dst))
-                        .unwrap() as u16,
-                    v,
-                );
-            }
-            Target::A8 => {
-                let mut bus = self.bus.borrow_mut();
-                let addr = 0xFF00 + bus.read(self.pc + 1).unwrap() as u16;
-                let _ = bus.write_16(addr, v);
+    pub fn load_16(
+        &mut self,
+        instruction: &Instruction,
+        dst: Target,
+        src: Target,
+    ) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "load_16 src: {src} dst: {dst}");
+        match (dst, src) {
+            (Target::BC | Target::DE | Target::HL | Target::SP, Target::D16) => {
+                let value = self.immediate_16()?;
+                self.set_register_16(dst, value)?;
             }
-            Target::A16 => {
-                let mut bus = self.bus.borrow_mut();
-                let addr = shift_left!(bus.read(self.pc + 1).unwrap(), 8, u16)
-                    + bus.read(self.pc + 2).unwrap() as u16;
-                let _ = bus.write_16(addr, v);
+            (Target::SP, Target::HL) => {
+                self.sp = self.registers.combined_register(Target::HL)?;
             }
-            Target::SP => self.sp = v,
-            Target::SP_R8 => {
-                let mut bus = self.bus.borrow_mut();
-                let addr = self.sp + bus.read(self.pc + 1).unwrap() as u16;
-                let _ = bus.write_16(addr, v);
+            (Target::HL, Target::SP_R8) => {
+                let (result, flags) = alu::add_sp(self.sp, self.immediate_8()?);
+                self.registers.set_combined_register(Target::HL, result)?;
+                self.set_flags(instruction, flags)?;
             }
-            _ => {
-                unimplemented!("Unimplemented {}", format!("{:#?}", OpCode::LD(dst, src)));
+            (Target::A16, Target::SP) => {
+                let address = self.immediate_16()?;
+                self.write_16(address, self.sp)?;
             }
+            _ => return Err(unsupported(OpCode::LD(dst, src))),
         }
+        Ok(())
     }
 
-    #[named]
-    fn or(&mut self, src: Target) {
-        log!("src: {src}");
+    fn or(&mut self, instruction: &Instruction, src: Target) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "or src: {src}");
         let old = self.registers.a;
-        match src {
-            Target::HL => {
-                self.registers.a =
-                    (self.registers.a as u16 | self.registers.combined_register(Target::HL)) as u8
-            }
-            Target::D8 => self.registers.a |= self.bus.borrow().read(self.pc + 1).unwrap(),
-            _ => {
-                let v = self.registers.get_register(Target::A);
-                let bits = self.registers.get_register(src);
-                self.registers.set_register(Target::A, or!(v, bits));
-            }
-        }
-
-        self.set_flags(
-            Instruction::from_opcode(OpCode::OR(src)).unwrap(),
-            old,
-            self.registers.a,
-        );
+        self.registers.a = or!(old, self.operand(src)?);
+        self.set_flags(instruction, AluFlags::from_result(self.registers.a))
     }
 
-    #[named]
-    fn pop(&mut self, target: Target) {
-        log!("target: {target}");
-        let v = shift_left!(self.bus.borrow().read(self.sp + 1).unwrap(), 8, u16)
-            | self.bus.borrow().read(self.sp + 2).unwrap() as u16;
-
-        self.sp = self.sp.wrapping_add(2);
-
+    fn pop(&mut self, target: Target) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "pop target: {target}");
+        let v = self.pop_16()?;
         match target {
-            Target::AF | Target::BC | Target::DE | Target::HL => {
-                self.registers.set_combined_register(target, v)
-            }
-            _ => {
-                unimplemented!("Unimplemented {}", format!("{:#?}", OpCode::POP(target)));
-            }
+            // The lower nibble of F does not exist
+            Target::AF => self.registers.set_combined_register(target, v & 0xFFF0),
+            Target::BC | Target::DE | Target::HL => self.registers.set_combined_register(target, v),
+            _ => Err(unsupported(OpCode::POP(target))),
         }
     }
 
-    #[named]
-    fn push(&mut self, target: Target) {
-        log!("target: {target}");
+    fn push(&mut self, target: Target) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "push target: {target}");
         let v = match target {
             Target::AF | Target::BC | Target::DE | Target::HL => {
-                self.registers.combined_register(target)
-            }
-            _ => {
-                unimplemented!("Unimplemented {}", format!("{:#?}", OpCode::PUSH(target)));
+                self.registers.combined_register(target)?
             }
+            _ => return Err(unsupported(OpCode::PUSH(target))),
         };
-
-        let mut bus = self.bus.borrow_mut();
-        let _ = bus.write(self.sp, (v & 0b11111111) as u8);
-        let _ = bus.write(self.sp - 1, shift_right!(v & 0b1111111100000000, 8, u8));
-
-        self.sp -= 2;
+        self.push_16(v)
     }
 
-    #[named]
-    fn res(&mut self, bit: u8, reg: Target) {
-        log!("bit: {bit} reg: {reg}");
-        if reg == Target::HL {
-            let mask = !shift_left!(1, bit, u16);
-            let v = self.registers.combined_register(Target::HL);
-            self.registers
-                .set_combined_register(Target::HL, and!(v, mask));
-        } else {
-            let mask = !shift_left!(1, bit);
-            match reg {
-                _ => {
-                    let v = self.registers.get_register(reg);
-                    self.registers.set_register(reg, and!(v, mask));
-                }
-            }
-        }
+    fn res(&mut self, bit: u8, reg: Target) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "res bit: {bit} reg: {reg}");
+        let v = self.operand(reg)?;
+        self.set_operand(reg, and!(v, !shift_left!(1, bit)))
     }
 
-    #[named]
-    fn ret(&mut self, flag: Flag) -> bool {
-        log!("flag: {flag}");
+    fn ret(&mut self, flag: Flag) -> Result<bool, CpuError> {
+        log!(Cpu, Trace, "ret flag: {flag}");
+        // Checking the condition takes an internal cycle
+        self.cycle();
         if self.registers.get_flag(flag) {
-            self.pc = shift_left!(self.bus.borrow().read(self.sp - 1).unwrap(), 8, u16)
-                | (self.bus.borrow().read(self.sp - 2).unwrap() as u16);
-
-            true
+            self.pc = self.pop_16()?;
+            Ok(true)
         } else {
-            false
+            Ok(false)
         }
     }
 
-    #[named]
-    fn rla(&mut self) {
-        log!("");
-        let msb = (self.registers.a & (1 << ZERO_BIT_POS)) >> ZERO_BIT_POS;
-        let carry = self.registers.filter_flag(Flag::Carry);
-        self.registers
-            .set_register(Target::A, shift_left!(self.registers.a, 1) | carry);
-        self.registers.set_flag(Flag::Carry, msb != 0);
-        self.registers.set_flag(Flag::Zero, self.registers.a == 0);
-        // Do not call self.set_flags()
+    // Rotates and shifts set zero from the result and carry from the bit shifted out
+    fn set_shift_flags(&mut self, result: u8, carry: bool) {
+        self.registers.set_flag(Flag::Zero, result == 0);
+        self.registers.set_flag(Flag::Sub, false);
+        self.registers.set_flag(Flag::HalfCarry, false);
+        self.registers.set_flag(Flag::Carry, carry);
     }
 
-    #[named]
-    fn rlca(&mut self) {
-        log!("");
-        self.registers.set_flag(
-            Flag::Carry,
-            and!(self.registers.a, shift_left!(1, ZERO_BIT_POS)) != 0,
-        );
-        self.registers
-            .set_register(Target::A, self.registers.a.rotate_left(1));
-
-        self.registers.set_flag(Flag::Zero, self.registers.a == 0);
-        // Do not call self.set_flags()
+    // The a register rotations behave like their prefixed versions, except that they clear zero
+    fn rla(&mut self) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "rla");
+        self.rl(Target::A)?;
+        self.registers.set_flag(Flag::Zero, false);
+        Ok(())
     }
 
-    fn rra(&mut self) {
-        let lsb = and!(self.registers.a, 1);
-        let carry = self.registers.filter_flag(Flag::Carry) << ZERO_BIT_POS;
-        self.registers
-            .set_register(Target::A, shift_right!(self.registers.a, 1) | carry);
-        self.registers.set_flag(Flag::Carry, lsb != 0);
-        self.registers.set_flag(Flag::Zero, self.registers.a == 0);
-        // Do not call self.set_flags()
+    fn rlca(&mut self) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "rlca");
+        self.rlc(Target::A)?;
+        self.registers.set_flag(Flag::Zero, false);
+        Ok(())
     }
 
-    #[named]
-    fn rrca(&mut self) {
-        log!("");
-        self.registers
-            .set_flag(Flag::Carry, (self.registers.a & 1) != 0);
-        self.registers.a = self.registers.a.rotate_right(1);
+    fn rra(&mut self) -> Result<(), CpuError> {
+        self.rr(Target::A)?;
+        self.registers.set_flag(Flag::Zero, false);
+        Ok(())
+    }
 
-        self.registers.set_flag(Flag::Zero, self.registers.a == 0);
-        // Do not call self.set_flags()
+    fn rrca(&mut self) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "rrca");
+        self.rrc(Target::A)?;
+        self.registers.set_flag(Flag::Zero, false);
+        Ok(())
     }
 
-    #[named]
-    fn rl(&mut self, reg: Target) {
-        log!("reg: {reg}");
-        let f = |reg: Target, cpu: &mut Cpu| {
-            let old = cpu.registers.get_register(reg);
-            let new_carry = shift_right!(old, 7);
-            let old_carry = if cpu.registers.get_flag(Flag::Carry) {
-                1
-            } else {
-                0
-            };
-            cpu.registers.set_flag_from_u8(Flag::Carry, new_carry);
-            let new = (old << 1) | old_carry;
-            cpu.registers.set_register(reg, new);
-            cpu.set_flags(Instruction::from_opcode(OpCode::RL(reg)).unwrap(), old, new);
-        };
-        match reg {
-            Target::HL => {
-                let old = self.registers.combined_register(Target::HL);
-                let lower = old & 0b11111111;
-                let upper = (old & 0b1111111100000000) >> 7;
-                let old_carry = if self.registers.get_flag(Flag::Carry) {
-                    1
-                } else {
-                    0
-                };
-                let msb_upper = shift_right!(upper, 7, u8);
-                let msb_lower = shift_right!(lower, 7, u8);
-                let new_upper = shift_left!(upper, 1, u8) | old_carry;
-                let new_carry = msb_lower as u8;
-                let new_lower = shift_left!(lower, 1, u8) | msb_upper;
-                let new_value = shift_left!(new_upper, 7, u16) + new_lower as u16;
-                self.registers.set_combined_register(Target::HL, new_value);
-                self.registers.set_flag(Flag::Zero, new_value == 0);
-                self.registers.set_flag(Flag::Sub, false);
-                self.registers.set_flag(Flag::HalfCarry, false);
-                self.registers.set_flag(Flag::Carry, new_carry > 0);
-                return;
-            }
-            _ => f(reg, self),
-        };
+    fn rl(&mut self, reg: Target) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "rl reg: {reg}");
+        let old = self.operand(reg)?;
+        let new = shift_left!(old, 1) | self.registers.filter_flag(Flag::Carry);
+        self.set_operand(reg, new)?;
+        self.set_shift_flags(new, shift_right!(old, 7) != 0);
+        Ok(())
     }
 
-    #[named]
-    fn rlc(&mut self, reg: Target) {
-        log!("reg: {reg}");
-        let f = |reg: Target, cpu: &mut Cpu| {
-            let old = cpu.registers.get_register(reg);
-            cpu.registers
-                .set_flag(Flag::Carry, (old & shift_left!(1, ZERO_BIT_POS)) != 0);
-            let new = old.rotate_left(1);
-            cpu.registers.set_flag(Flag::Zero, new == 0);
-            cpu.registers.set_register(reg, new);
-        };
-        match reg {
-            Target::HL => {
-                let v = self.registers.combined_register(reg);
-                self.registers
-                    .set_flag(Flag::Carry, (v & shift_left!(1, 15)) != 0);
-                self.registers.set_combined_register(reg, v.rotate_left(1));
-            }
-            _ => f(reg, self),
-        };
+    fn rlc(&mut self, reg: Target) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "rlc reg: {reg}");
+        let old = self.operand(reg)?;
+        let new = old.rotate_left(1);
+        self.set_operand(reg, new)?;
+        self.set_shift_flags(new, shift_right!(old, 7) != 0);
+        Ok(())
     }
 
-    #[named]
-    fn rr(&mut self, reg: Target) {
-        log!("reg: {reg}");
-        let f = |reg: Target, cpu: &mut Cpu| {
-            let old = cpu.registers.get_register(reg);
-            let new_carry = old & 0b1;
-            let old_carry = if cpu.registers.get_flag(Flag::Carry) {
-                println!("carry 1");
-                1
-            } else {
-                println!("carry 0");
-                0
-            };
-            let new = shift_right!(old, 1) | shift_left!(old_carry, 7);
-            cpu.registers.set_flag_from_u8(Flag::Carry, new_carry);
-            cpu.registers.set_register(reg, new);
-            cpu.set_flags(Instruction::from_opcode(OpCode::RR(reg)).unwrap(), old, new);
-        };
-        match reg {
-            _ => f(reg, self),
-        };
+    fn rr(&mut self, reg: Target) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "rr reg: {reg}");
+        let old = self.operand(reg)?;
+        let new = shift_right!(old, 1) | shift_left!(self.registers.filter_flag(Flag::Carry), 7);
+        self.set_operand(reg, new)?;
+        self.set_shift_flags(new, and!(old, 1) != 0);
+        Ok(())
     }
 
-    #[allow(unused_assignments)]
-    #[named]
-    fn rrc(&mut self, reg: Target) {
-        log!("reg: {reg}");
-        let f = |reg: Target, cpu: &mut Cpu| {
-            let old = cpu.registers.get_register(reg);
-            let old_carry = if cpu.registers.get_flag(Flag::Carry) {
-                1
-            } else {
-                0
-            };
-            let new_carry = old & 1;
-            cpu.registers.set_flag(Flag::Carry, new_carry == 1);
-            let new = shift_right!(old, 1) | shift_left!(old_carry, 7);
-            cpu.registers.set_flag(Flag::Zero, new == 0);
-            cpu.registers.set_register(reg, new);
-        };
-        match reg {
-            _ => f(reg, self),
-        };
+    fn rrc(&mut self, reg: Target) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "rrc reg: {reg}");
+        let old = self.operand(reg)?;
+        let new = old.rotate_right(1);
+        self.set_operand(reg, new)?;
+        self.set_shift_flags(new, and!(old, 1) != 0);
+        Ok(())
     }
 
-    #[named]
-    fn rst(&mut self, address: u16) {
-        log!("address: {address}");
-        let _ = self.bus.borrow_mut().write(
-            self.sp - 1,
-            shift_right!(and!(self.pc, 0b1111111100000000), 8, u8),
-        );
-        let _ = self
-            .bus
-            .borrow_mut()
-            .write(self.sp, and!(self.pc, 0b11111111, u8));
+    fn rst(&mut self, address: u16) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "rst address: {address}");
+        self.push_16(self.pc.wrapping_add(1))?;
+        self.pc = address;
+        Ok(())
+    }
 
-        self.sp = self.sp.wrapping_sub(2);
+    fn sbc(&mut self, instruction: &Instruction, reg: Target) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "sbc reg: {reg}");
+        let v = self.operand(reg)?;
+        let carry = self.registers.get_flag(Flag::Carry);
+        let (result, flags) = alu::sub_8(self.registers.a, v, carry);
+        self.registers.a = result;
 
-        self.pc = address;
+        self.set_flags(instruction, flags)
     }
 
-    #[named]
-    fn sbc(&mut self, reg: Target) {
-        log!("reg: {reg}");
-        let v = match reg {
-            Target::HL => self
-                .bus
-                .borrow()
-                .read(self.registers.combined_register(reg))
-                .unwrap(),
-            Target::D8 => self.bus.borrow().read(self.pc + 1).unwrap(),
-            _ => self.registers.get_register(reg),
-        };
+    fn scf(&mut self) {
+        self.registers.set_flag(Flag::Sub, false);
+        self.registers.set_flag(Flag::HalfCarry, false);
+        self.registers.set_flag(Flag::Carry, true);
+    }
 
-        let carry = self.registers.filter_flag(Flag::Carry);
-        let old = self.registers.a;
+    fn set(&mut self, bit: u8, reg: Target) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "set bit: {bit} reg: {reg}");
+        let v = self.operand(reg)?;
+        self.set_operand(reg, or!(v, shift_left!(1, bit)))
+    }
 
-        self.registers.set_register(
-            Target::A,
-            self.registers
-                .get_register(Target::A)
-                .wrapping_sub(v)
-                .wrapping_sub(carry),
-        );
+    fn sla(&mut self, reg: Target) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "sla reg: {reg}");
+        let old = self.operand(reg)?;
+        let new = shift_left!(old, 1);
+        self.set_operand(reg, new)?;
+        self.set_shift_flags(new, shift_right!(old, 7) != 0);
+        Ok(())
+    }
 
-        self.set_flags(
-            Instruction::from_opcode(OpCode::SBC(reg)).unwrap(),
-            old,
-            self.registers.a,
-        );
+    fn srl(&mut self, reg: Target) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "srl reg: {reg}");
+        let old = self.operand(reg)?;
+        let new = shift_right!(old, 1);
+        self.set_operand(reg, new)?;
+        self.set_shift_flags(new, and!(old, 1) != 0);
+        Ok(())
     }
 
-    #[named]
-    fn set(&mut self, bit: u8, reg: Target) {
-        log!("bit: {bit} reg: {reg}");
-        let mask = 1 << bit;
-        match reg {
-            _ => {
-                let v = self.registers.get_register(reg);
-                self.registers.set_register(reg, or!(v, mask));
-            }
-        }
+    // Shifts right, keeping the sign bit
+    fn sra(&mut self, reg: Target) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "sra reg: {reg}");
+        let old = self.operand(reg)?;
+        let new = shift_right!(old, 1) | and!(old, 0x80);
+        self.set_operand(reg, new)?;
+        self.set_shift_flags(new, and!(old, 1) != 0);
+        Ok(())
     }
 
-    #[named]
-    fn sl(&mut self, reg: Target) {
-        log!("reg: {reg}");
-        match reg {
-            _ => self
-                .registers
-                .set_register(reg, shift_left!(self.registers.get_register(reg), 1)),
-        }
-    }
-
-    #[allow(unused_assignments)]
-    #[named]
-    fn sla(&mut self, reg: Target) {
-        log!("reg: {reg}");
-        let f = |reg: Target, cpu: &mut Cpu| {
-            let old = cpu.registers.get_register(reg);
-            let bit = old & shift_left!(1, ZERO_BIT_POS);
-            let new = shift_left!(old, 1);
-            cpu.registers.set_flag(Flag::Carry, bit == 0);
-            cpu.registers.set_flag(Flag::Zero, new == 0);
-            cpu.registers.set_flag(Flag::HalfCarry, false);
-            cpu.registers.set_flag(Flag::Sub, false);
-            cpu.registers.set_register(reg, new);
-        };
+    fn sub(&mut self, instruction: &Instruction, src: Target) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "sub src: {src}");
+        let v = self.operand(src)?;
+        let (result, flags) = alu::sub_8(self.registers.a, v, false);
+        self.registers.a = result;
 
-        match reg {
-            _ => f(reg, self),
-        };
+        self.set_flags(instruction, flags)
     }
 
-    #[named]
-    fn srl(&mut self, reg: Target) {
-        log!("reg: {reg}");
-        let f = |reg: Target, cpu: &mut Cpu| {
-            let old = cpu.registers.get_register(reg);
-            let lsb = and!(old, 1);
-            let new = shift_right!(old, 1);
-            cpu.registers.set_register(reg, new);
-            cpu.registers.set_flag(Flag::Zero, new == 0);
-            cpu.registers.set_flag(Flag::Sub, false);
-            cpu.registers.set_flag(Flag::Carry, lsb != 0);
-            cpu.registers.set_flag(Flag::HalfCarry, false);
-        };
+    fn swap(&mut self, reg: Target) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "swap reg: {reg}");
+        let new = self.operand(reg)?.rotate_left(4);
+        self.set_operand(reg, new)?;
+        self.set_shift_flags(new, false);
+        Ok(())
+    }
 
-        match reg {
-            Target::HL => {
-                let mut v = self.registers.combined_register(reg);
-                let lsb = and!(v, 1);
-                v = shift_right!(v, 1);
-                self.registers.set_flag(Flag::Zero, v == 0);
-                self.registers.set_flag(Flag::Sub, false);
-                self.registers.set_flag(Flag::Carry, lsb != 0);
-                self.registers.set_flag(Flag::HalfCarry, false);
-                self.registers.set_combined_register(reg, v);
-                // return;
-            }
-            _ => f(reg, self),
-        };
+    fn xor(&mut self, instruction: &Instruction, src: Target) -> Result<(), CpuError> {
+        log!(Cpu, Trace, "xor src: {src}");
+        let old = self.registers.a;
+        self.registers.a = xor!(old, self.operand(src)?);
+        self.set_flags(instruction, AluFlags::from_result(self.registers.a))
     }
 
-    #[allow(unused_assignments)]
-    #[named]
-    fn sra(&mut self, reg: Target) {
-        log!("reg: {reg}");
-        let msb = |v: u8| v & shift_left!(1, ZERO_BIT_POS);
-        let f = |reg: Target, cpu: &mut Cpu| {
-            let old = cpu.registers.get_register(reg);
-            let lsb = and!(old, 1);
-            let new = shift_right!(old, 1) | (msb(old));
-            cpu.registers.set_flag(Flag::Zero, new == 0);
-            cpu.registers.set_flag(Flag::Sub, false);
-            cpu.registers.set_flag(Flag::Carry, lsb != 0);
-            cpu.registers.set_flag(Flag::HalfCarry, false);
-            cpu.registers.set_register(Target::A, new);
-        };
-        match reg {
-            _ => f(reg, self),
-        }
+    pub fn reset_registers(&mut self) {
+        self.pc = 0x100;
+        self.sp = 0xFFFE;
+        self.registers.reset();
     }
+}
 
-    #[named]
-    fn sub(&mut self, src: Target) {
-        log!("src: {src}");
-        let v = match src {
-            Target::HL => self.registers.combined_register(Target::HL) as u8,
-            Target::D8 => self.bus.borrow().read(self.pc + 1).unwrap(),
-            _ => self.registers.get_register(src),
-        };
+fn unsupported(opcode: OpCode) -> CpuError {
+    CpuError::UnsupportedOperand(format!("{opcode:?}"))
+}
+
+impl Steppable for Cpu {
+    // Executes one whole instruction, including both bytes of a prefixed one.
+    // An error freezes the cpu at the instruction that caused it.
+    fn step(&mut self) -> Result<bool, CpuFault> {
+        if let Some(fault) = &self.fault {
+            return Err(fault.clone());
+        }
 
-        let old = self.registers.a;
-        self.registers.set_register(
-            Target::A,
-            self.registers.get_register(Target::A).wrapping_sub(v),
-        );
+        let pc = self.pc;
+        let mut result = self.tick();
+        if let Ok(true) = result
+            && self.is_prefixed
+        {
+            result = self.tick();
+        }
 
-        self.set_flags(
-            Instruction::from_opcode(OpCode::SUB(src)).unwrap(),
-            old,
-            self.registers.a,
-        );
+        result.map_err(|error| {
+            let fault = CpuFault { pc, error };
+            self.fault = Some(fault.clone());
+            fault
+        })
     }
 
-    #[named]
-    fn swap(&mut self, reg: Target) {
-        log!("reg: {reg}");
-        let f = |reg: Target, cpu: &mut Cpu| {
-            let old = cpu.registers.get_register(reg);
-            let upper = and!(old, shift_left!(0b1111, 4));
-            let lower = and!(old, 0b1111);
-            // let old = v;
-            let v = shift_right!(upper, 4) | shift_left!(lower, 4);
-            cpu.set_flags(Instruction::from_opcode(OpCode::SWAP(reg)).unwrap(), old, v);
-            cpu.registers.set_register(reg, v);
-        };
-        match reg {
-            _ => f(reg, self),
-        }
+    // T-cycles executed since power on
+    fn cycles(&self) -> u64 {
+        self.cycles
     }
 
-    #[named]
-    fn xor(&mut self, src: Target) {
-        log!("src: {src}");
-        let old = self.registers.a;
-        match src {
-            Target::HL => {
-                self.registers.a ^= self
-                    .bus
-                    .borrow()
-                    .read(self.registers.combined_register(src))
-                    .unwrap()
-            }
-            Target::D8 => self.registers.a ^= self.bus.borrow().read(self.pc + 1).unwrap(),
-            _ => {
-                let bits = self.registers.get_register(src);
-                let v = self.registers.get_register(Target::A);
-                self.registers.set_register(Target::A, xor!(v, bits));
-            }
-        }
+    fn pc(&self) -> u16 {
+        self.pc
+    }
 
-        self.set_flags(
-            Instruction::from_opcode(OpCode::XOR(src)).unwrap(),
-            old,
-            self.registers.a,
-        );
+    fn stop_handle(&self) -> &StopHandle {
+        &self.stop
     }
 
-    #[allow(unused_variables)]
-    #[named]
-    fn store(&mut self, dst: Target, src: Target) {
-        log!("src: {src} dst: {dst}");
-        if dst.is_16bit() {
-            self.store_16(dst, src);
-            return;
-        }
+    fn lock_up(&self) -> Option<LockUp> {
+        self.lock_up
     }
+}
 
-    #[allow(unused_variables)]
-    #[named]
-    fn store_16(&mut self, dst: Target, src: Target) {
-        log!("src: {src} dst: {dst}");
+// A fault is not saved, loading the state runs into it again. The block cache is rebuilt as the
+// loaded code runs.
+impl SaveState for Cpu {
+    fn save_state(&self, out: &mut StateWriter) {
+        let registers = &self.registers;
+        for register in [
+            registers.a,
+            registers.f,
+            registers.b,
+            registers.c,
+            registers.d,
+            registers.e,
+            registers.h,
+            registers.l,
+        ] {
+            out.write_u8(register);
+        }
+        out.write_u16(self.pc);
+        out.write_u16(self.sp);
+        out.write_bool(self.is_prefixed);
+        out.write_bool(self.interrupts_enabled);
+        out.write_bool(self.enable_interrupts_next);
+        out.write_bool(self.is_stopped);
+        out.write_bool(self.is_halted);
+        out.write_u64(self.cycles);
+        out.write_bool(self.lock_up.is_some());
+        if let Some(lock_up) = self.lock_up {
+            out.write_u16(lock_up.pc);
+            out.write_u8(lock_up.opcode);
+        }
     }
 
-    pub fn reset_registers(&mut self) {
-        self.pc = 0x100;
-        self.sp = 0xE000;
-        self.registers.reset();
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), StateError> {
+        let registers = &mut self.registers;
+        for register in [
+            &mut registers.a,
+            &mut registers.f,
+            &mut registers.b,
+            &mut registers.c,
+            &mut registers.d,
+            &mut registers.e,
+            &mut registers.h,
+            &mut registers.l,
+        ] {
+            *register = state.read_u8()?;
+        }
+        self.pc = state.read_u16()?;
+        self.sp = state.read_u16()?;
+        self.is_prefixed = state.read_bool()?;
+        self.interrupts_enabled = state.read_bool()?;
+        self.enable_interrupts_next = state.read_bool()?;
+        self.is_stopped = state.read_bool()?;
+        self.is_halted = state.read_bool()?;
+        self.cycles = state.read_u64()?;
+        self.lock_up = match state.read_bool()? {
+            true => Some(LockUp {
+                pc: state.read_u16()?,
+                opcode: state.read_u8()?,
+            }),
+            false => None,
+        };
+        self.fault = None;
+        self.clear_block_cache();
+        Ok(())
     }
 }
 
@@ -1524,7 +1435,7 @@ impl Display for Cpu {
 #[cfg(test)]
 mod tests {
     use core::panic;
-    use std::{cell::RefCell, rc::Rc};
+    use std::{cell::RefCell, io::Write, ops::RangeInclusive, rc::Rc};
 
     use rstest::rstest;
 
@@ -1532,16 +1443,21 @@ mod tests {
         and,
         consoles::{
             addressable::Addressable,
-            bus::Bus,
+            bus::{Bus, ReadDevice, WriteDevice},
             fake_cartridge::FakeCartridge,
             gameboy::{
-                cpu::Cpu,
+                clock::Clocked,
+                cpu::{Cpu, CpuError, CpuFault, LockUp},
+                execution::{RunUntil, Steppable, StopReason, emulated_mhz},
                 instruction::Instruction,
-                opcode::OpCode::EndOfProgram,
+                interrupts::{Interrupt, InterruptController},
+                io::IoRegisters,
+                opcode::OpCode::{self, NOP},
                 registers::{Flag, ZERO_BIT_POS},
                 target::Target,
+                trace::TraceWriter,
             },
-            memory::Memory,
+            memory::{Memory, PowerOnPattern},
             memory_map::gameboy::{H_RAM, ROM_BANK_00, WRAM},
             readable::Readable,
             writeable::Writeable,
@@ -1553,18 +1469,16 @@ mod tests {
     fn setup() -> Cpu {
         let mut bus = Bus::<u16, u8, u16>::new();
 
-        let get_default_value = || Instruction::byte_from_opcode(EndOfProgram).unwrap();
-        let memory = Rc::new(RefCell::new(Memory::<u16, u8, u16, 0x10000>::new(
-            u16_to_u8,
-            Some(Box::new(get_default_value)),
+        let nop = PowerOnPattern::Fill(Instruction::byte_from_opcode(NOP).unwrap());
+        let memory = Rc::new(RefCell::new(Memory::<u16, u8, u16, 0x1000>::new(
+            u16_to_u8, nop,
         )));
         memory.borrow_mut().assign_address_range(WRAM);
         bus.connect_readable(memory.clone());
         bus.connect_writeable(memory);
 
-        let h_ram = Rc::new(RefCell::new(Memory::<u16, u8, u16, 126>::new(
-            u16_to_u8,
-            Some(Box::new(get_default_value)),
+        let h_ram = Rc::new(RefCell::new(Memory::<u16, u8, u16, 127>::new(
+            u16_to_u8, nop,
         )));
         h_ram.borrow_mut().assign_address_range(H_RAM);
         bus.connect_readable(h_ram.clone());
@@ -1577,10 +1491,24 @@ mod tests {
         bus.connect_readable(cartridge.clone());
         bus.connect_writeable(cartridge);
 
+        let interrupts = Rc::new(RefCell::new(InterruptController::new()));
+        let mut io = IoRegisters::new();
+        io.connect(interrupts.clone());
+        let io = Rc::new(RefCell::new(io));
+        bus.connect_readable(io.clone());
+        bus.connect_writeable(io);
+        bus.connect_readable(interrupts.clone());
+        bus.connect_writeable(interrupts);
+
         let bus = Rc::new(RefCell::new(bus));
         Cpu::new(bus)
     }
 
+    // The table entry the cpu would have decoded for the opcode
+    fn decoded(opcode: OpCode) -> &'static Instruction {
+        Instruction::from_opcode(opcode).unwrap()
+    }
+
     #[rstest]
     #[case(Target::A, true, 3)]
     #[case(Target::A, false, 2)]
@@ -1612,12 +1540,14 @@ mod tests {
             Target::L => cpu.registers.l = 1,
             Target::HL => {
                 let _ = cpu.bus.borrow_mut().write(cpu.sp, 1);
-                cpu.registers.set_combined_register(Target::HL, cpu.sp);
+                cpu.registers
+                    .set_combined_register(Target::HL, cpu.sp)
+                    .unwrap();
             }
             _ => panic!("Unsupported register"),
         }
         println!("pre cpu: {cpu}");
-        cpu.adc(src);
+        cpu.adc(decoded(OpCode::ADC(src)), src).unwrap();
         println!("post cpu: {cpu}");
 
         assert_eq!(
@@ -1635,13 +1565,13 @@ mod tests {
     #[case(0, Target::H, 5, 5, false, false)]
     #[case(0, Target::L, 5, 5, false, false)]
     #[case(0, Target::HL, 5, 5, false, false)]
-    #[case(255, Target::B, 1, 0, true, false)]
-    #[case(255, Target::C, 1, 0, true, false)]
-    #[case(255, Target::D, 1, 0, true, false)]
-    #[case(255, Target::E, 1, 0, true, false)]
-    #[case(255, Target::H, 1, 0, true, false)]
-    #[case(255, Target::L, 1, 0, true, false)]
-    #[case(255, Target::HL, 1, 0, true, false)]
+    #[case(255, Target::B, 1, 0, true, true)]
+    #[case(255, Target::C, 1, 0, true, true)]
+    #[case(255, Target::D, 1, 0, true, true)]
+    #[case(255, Target::E, 1, 0, true, true)]
+    #[case(255, Target::H, 1, 0, true, true)]
+    #[case(255, Target::L, 1, 0, true, true)]
+    #[case(255, Target::HL, 1, 0, true, true)]
     #[case(0b1111, Target::B, 1, 0b10000, false, true)]
     #[case(0b1111, Target::C, 1, 0b10000, false, true)]
     #[case(0b1111, Target::D, 1, 0b10000, false, true)]
@@ -1669,11 +1599,13 @@ mod tests {
             Target::L => cpu.registers.l = val_b,
             Target::HL => {
                 let _ = cpu.bus.borrow_mut().write(cpu.sp, val_b);
-                cpu.registers.set_combined_register(Target::HL, cpu.sp);
+                cpu.registers
+                    .set_combined_register(Target::HL, cpu.sp)
+                    .unwrap();
             }
             _ => panic!("Unsupported register"),
         }
-        cpu.add(src);
+        cpu.add(decoded(OpCode::ADD(src)), src).unwrap();
         assert_eq!(cpu.registers.a, expected, "False expected value");
         assert_eq!(
             cpu.registers.get_flag(Flag::Zero),
@@ -1725,7 +1657,8 @@ mod tests {
             _ => panic!("Unsupported register"),
         }
 
-        cpu.add_16(dst, src);
+        cpu.add_16(decoded(OpCode::ADD16(dst, src)), dst, src)
+            .unwrap();
 
         match dst {
             Target::HL => {
@@ -1781,11 +1714,13 @@ mod tests {
             Target::L => cpu.registers.l = src_value,
             Target::HL => {
                 let _ = cpu.bus.borrow_mut().write(cpu.sp, src_value);
-                cpu.registers.set_combined_register(Target::HL, cpu.sp);
+                cpu.registers
+                    .set_combined_register(Target::HL, cpu.sp)
+                    .unwrap();
             }
             _ => panic!("Unsupported register"),
         }
-        cpu.and(src);
+        cpu.and(decoded(OpCode::AND(src)), src).unwrap();
 
         assert_eq!(cpu.registers.a, expected);
         assert_eq!(cpu.registers.get_flag(Flag::Zero), expected_zero_flag);
@@ -1832,11 +1767,14 @@ mod tests {
             Target::L => cpu.registers.l = value,
             Target::HL => {
                 let _ = cpu.bus.borrow_mut().write(cpu.sp, value);
-                cpu.registers.set_combined_register(Target::HL, cpu.sp);
+                cpu.registers
+                    .set_combined_register(Target::HL, cpu.sp)
+                    .unwrap();
             }
             _ => panic!("Unsupported register"),
         }
-        cpu.bit(bit_pos, src);
+        cpu.bit(decoded(OpCode::BIT(bit_pos, src)), bit_pos, src)
+            .unwrap();
 
         assert_eq!(cpu.registers.get_flag(Flag::Zero), expected_zero_flag);
         assert_eq!(cpu.registers.get_flag(Flag::Sub), expected_sub_flag);
@@ -1850,13 +1788,11 @@ mod tests {
     #[test]
     fn test_call_and_ret() {
         let mut cpu = setup();
+        cpu.sp = 0xCFF0;
 
-        eprintln!("pc = {} sp = {}", cpu.pc, cpu.sp);
         let address1 = cpu.pc + 10;
         let address2 = cpu.pc + 3;
 
-        let _ = cpu.bus.borrow_mut().write_16(1, address1);
-
         let _ = cpu
             .bus
             .borrow_mut()
@@ -1865,15 +1801,300 @@ mod tests {
             cpu.pc + 2,
             shift_right!(and!(address1, 0b1111111100000000), 8, u8),
         );
-        assert!(cpu.call(Flag::NotZero));
+        assert!(cpu.call(Flag::NotZero).unwrap());
         assert_eq!(cpu.pc, address1);
+        assert_eq!(cpu.sp, 0xCFEE);
 
-        cpu.sp += 2;
-
-        assert!(cpu.ret(Flag::NotZero));
+        assert!(cpu.ret(Flag::NotZero).unwrap());
         assert_eq!(cpu.pc, address2);
     }
 
+    // A and F after DAA for every value of A and every combination of N, H and C.
+    // The index holds N, H and C in bits 10, 9 and 8 and A in the lower byte.
+    #[rustfmt::skip]
+    const DAA_RESULTS: [u16; 2048] = [
+        0x0080, 0x0100, 0x0200, 0x0300, 0x0400, 0x0500, 0x0600, 0x0700,
+        0x0800, 0x0900, 0x1000, 0x1100, 0x1200, 0x1300, 0x1400, 0x1500,
+        0x1000, 0x1100, 0x1200, 0x1300, 0x1400, 0x1500, 0x1600, 0x1700,
+        0x1800, 0x1900, 0x2000, 0x2100, 0x2200, 0x2300, 0x2400, 0x2500,
+        0x2000, 0x2100, 0x2200, 0x2300, 0x2400, 0x2500, 0x2600, 0x2700,
+        0x2800, 0x2900, 0x3000, 0x3100, 0x3200, 0x3300, 0x3400, 0x3500,
+        0x3000, 0x3100, 0x3200, 0x3300, 0x3400, 0x3500, 0x3600, 0x3700,
+        0x3800, 0x3900, 0x4000, 0x4100, 0x4200, 0x4300, 0x4400, 0x4500,
+        0x4000, 0x4100, 0x4200, 0x4300, 0x4400, 0x4500, 0x4600, 0x4700,
+        0x4800, 0x4900, 0x5000, 0x5100, 0x5200, 0x5300, 0x5400, 0x5500,
+        0x5000, 0x5100, 0x5200, 0x5300, 0x5400, 0x5500, 0x5600, 0x5700,
+        0x5800, 0x5900, 0x6000, 0x6100, 0x6200, 0x6300, 0x6400, 0x6500,
+        0x6000, 0x6100, 0x6200, 0x6300, 0x6400, 0x6500, 0x6600, 0x6700,
+        0x6800, 0x6900, 0x7000, 0x7100, 0x7200, 0x7300, 0x7400, 0x7500,
+        0x7000, 0x7100, 0x7200, 0x7300, 0x7400, 0x7500, 0x7600, 0x7700,
+        0x7800, 0x7900, 0x8000, 0x8100, 0x8200, 0x8300, 0x8400, 0x8500,
+        0x8000, 0x8100, 0x8200, 0x8300, 0x8400, 0x8500, 0x8600, 0x8700,
+        0x8800, 0x8900, 0x9000, 0x9100, 0x9200, 0x9300, 0x9400, 0x9500,
+        0x9000, 0x9100, 0x9200, 0x9300, 0x9400, 0x9500, 0x9600, 0x9700,
+        0x9800, 0x9900, 0x0090, 0x0110, 0x0210, 0x0310, 0x0410, 0x0510,
+        0x0090, 0x0110, 0x0210, 0x0310, 0x0410, 0x0510, 0x0610, 0x0710,
+        0x0810, 0x0910, 0x1010, 0x1110, 0x1210, 0x1310, 0x1410, 0x1510,
+        0x1010, 0x1110, 0x1210, 0x1310, 0x1410, 0x1510, 0x1610, 0x1710,
+        0x1810, 0x1910, 0x2010, 0x2110, 0x2210, 0x2310, 0x2410, 0x2510,
+        0x2010, 0x2110, 0x2210, 0x2310, 0x2410, 0x2510, 0x2610, 0x2710,
+        0x2810, 0x2910, 0x3010, 0x3110, 0x3210, 0x3310, 0x3410, 0x3510,
+        0x3010, 0x3110, 0x3210, 0x3310, 0x3410, 0x3510, 0x3610, 0x3710,
+        0x3810, 0x3910, 0x4010, 0x4110, 0x4210, 0x4310, 0x4410, 0x4510,
+        0x4010, 0x4110, 0x4210, 0x4310, 0x4410, 0x4510, 0x4610, 0x4710,
+        0x4810, 0x4910, 0x5010, 0x5110, 0x5210, 0x5310, 0x5410, 0x5510,
+        0x5010, 0x5110, 0x5210, 0x5310, 0x5410, 0x5510, 0x5610, 0x5710,
+        0x5810, 0x5910, 0x6010, 0x6110, 0x6210, 0x6310, 0x6410, 0x6510,
+        0x6010, 0x6110, 0x6210, 0x6310, 0x6410, 0x6510, 0x6610, 0x6710,
+        0x6810, 0x6910, 0x7010, 0x7110, 0x7210, 0x7310, 0x7410, 0x7510,
+        0x7010, 0x7110, 0x7210, 0x7310, 0x7410, 0x7510, 0x7610, 0x7710,
+        0x7810, 0x7910, 0x8010, 0x8110, 0x8210, 0x8310, 0x8410, 0x8510,
+        0x8010, 0x8110, 0x8210, 0x8310, 0x8410, 0x8510, 0x8610, 0x8710,
+        0x8810, 0x8910, 0x9010, 0x9110, 0x9210, 0x9310, 0x9410, 0x9510,
+        0x9010, 0x9110, 0x9210, 0x9310, 0x9410, 0x9510, 0x9610, 0x9710,
+        0x9810, 0x9910, 0xA010, 0xA110, 0xA210, 0xA310, 0xA410, 0xA510,
+        0xA010, 0xA110, 0xA210, 0xA310, 0xA410, 0xA510, 0xA610, 0xA710,
+        0xA810, 0xA910, 0xB010, 0xB110, 0xB210, 0xB310, 0xB410, 0xB510,
+        0xB010, 0xB110, 0xB210, 0xB310, 0xB410, 0xB510, 0xB610, 0xB710,
+        0xB810, 0xB910, 0xC010, 0xC110, 0xC210, 0xC310, 0xC410, 0xC510,
+        0xC010, 0xC110, 0xC210, 0xC310, 0xC410, 0xC510, 0xC610, 0xC710,
+        0xC810, 0xC910, 0xD010, 0xD110, 0xD210, 0xD310, 0xD410, 0xD510,
+        0xD010, 0xD110, 0xD210, 0xD310, 0xD410, 0xD510, 0xD610, 0xD710,
+        0xD810, 0xD910, 0xE010, 0xE110, 0xE210, 0xE310, 0xE410, 0xE510,
+        0xE010, 0xE110, 0xE210, 0xE310, 0xE410, 0xE510, 0xE610, 0xE710,
+        0xE810, 0xE910, 0xF010, 0xF110, 0xF210, 0xF310, 0xF410, 0xF510,
+        0xF010, 0xF110, 0xF210, 0xF310, 0xF410, 0xF510, 0xF610, 0xF710,
+        0xF810, 0xF910, 0x0090, 0x0110, 0x0210, 0x0310, 0x0410, 0x0510,
+        0x0090, 0x0110, 0x0210, 0x0310, 0x0410, 0x0510, 0x0610, 0x0710,
+        0x0810, 0x0910, 0x1010, 0x1110, 0x1210, 0x1310, 0x1410, 0x1510,
+        0x1010, 0x1110, 0x1210, 0x1310, 0x1410, 0x1510, 0x1610, 0x1710,
+        0x1810, 0x1910, 0x2010, 0x2110, 0x2210, 0x2310, 0x2410, 0x2510,
+        0x2010, 0x2110, 0x2210, 0x2310, 0x2410, 0x2510, 0x2610, 0x2710,
+        0x2810, 0x2910, 0x3010, 0x3110, 0x3210, 0x3310, 0x3410, 0x3510,
+        0x3010, 0x3110, 0x3210, 0x3310, 0x3410, 0x3510, 0x3610, 0x3710,
+        0x3810, 0x3910, 0x4010, 0x4110, 0x4210, 0x4310, 0x4410, 0x4510,
+        0x4010, 0x4110, 0x4210, 0x4310, 0x4410, 0x4510, 0x4610, 0x4710,
+        0x4810, 0x4910, 0x5010, 0x5110, 0x5210, 0x5310, 0x5410, 0x5510,
+        0x5010, 0x5110, 0x5210, 0x5310, 0x5410, 0x5510, 0x5610, 0x5710,
+        0x5810, 0x5910, 0x6010, 0x6110, 0x6210, 0x6310, 0x6410, 0x6510,
+        0x0600, 0x0700, 0x0800, 0x0900, 0x0A00, 0x0B00, 0x0C00, 0x0D00,
+        0x0E00, 0x0F00, 0x1000, 0x1100, 0x1200, 0x1300, 0x1400, 0x1500,
+        0x1600, 0x1700, 0x1800, 0x1900, 0x1A00, 0x1B00, 0x1C00, 0x1D00,
+        0x1E00, 0x1F00, 0x2000, 0x2100, 0x2200, 0x2300, 0x2400, 0x2500,
+        0x2600, 0x2700, 0x2800, 0x2900, 0x2A00, 0x2B00, 0x2C00, 0x2D00,
+        0x2E00, 0x2F00, 0x3000, 0x3100, 0x3200, 0x3300, 0x3400, 0x3500,
+        0x3600, 0x3700, 0x3800, 0x3900, 0x3A00, 0x3B00, 0x3C00, 0x3D00,
+        0x3E00, 0x3F00, 0x4000, 0x4100, 0x4200, 0x4300, 0x4400, 0x4500,
+        0x4600, 0x4700, 0x4800, 0x4900, 0x4A00, 0x4B00, 0x4C00, 0x4D00,
+        0x4E00, 0x4F00, 0x5000, 0x5100, 0x5200, 0x5300, 0x5400, 0x5500,
+        0x5600, 0x5700, 0x5800, 0x5900, 0x5A00, 0x5B00, 0x5C00, 0x5D00,
+        0x5E00, 0x5F00, 0x6000, 0x6100, 0x6200, 0x6300, 0x6400, 0x6500,
+        0x6600, 0x6700, 0x6800, 0x6900, 0x6A00, 0x6B00, 0x6C00, 0x6D00,
+        0x6E00, 0x6F00, 0x7000, 0x7100, 0x7200, 0x7300, 0x7400, 0x7500,
+        0x7600, 0x7700, 0x7800, 0x7900, 0x7A00, 0x7B00, 0x7C00, 0x7D00,
+        0x7E00, 0x7F00, 0x8000, 0x8100, 0x8200, 0x8300, 0x8400, 0x8500,
+        0x8600, 0x8700, 0x8800, 0x8900, 0x8A00, 0x8B00, 0x8C00, 0x8D00,
+        0x8E00, 0x8F00, 0x9000, 0x9100, 0x9200, 0x9300, 0x9400, 0x9500,
+        0x9600, 0x9700, 0x9800, 0x9900, 0x9A00, 0x9B00, 0x9C00, 0x9D00,
+        0x9E00, 0x9F00, 0x0090, 0x0110, 0x0210, 0x0310, 0x0410, 0x0510,
+        0x0610, 0x0710, 0x0810, 0x0910, 0x0A10, 0x0B10, 0x0C10, 0x0D10,
+        0x0E10, 0x0F10, 0x1010, 0x1110, 0x1210, 0x1310, 0x1410, 0x1510,
+        0x1610, 0x1710, 0x1810, 0x1910, 0x1A10, 0x1B10, 0x1C10, 0x1D10,
+        0x1E10, 0x1F10, 0x2010, 0x2110, 0x2210, 0x2310, 0x2410, 0x2510,
+        0x2610, 0x2710, 0x2810, 0x2910, 0x2A10, 0x2B10, 0x2C10, 0x2D10,
+        0x2E10, 0x2F10, 0x3010, 0x3110, 0x3210, 0x3310, 0x3410, 0x3510,
+        0x3610, 0x3710, 0x3810, 0x3910, 0x3A10, 0x3B10, 0x3C10, 0x3D10,
+        0x3E10, 0x3F10, 0x4010, 0x4110, 0x4210, 0x4310, 0x4410, 0x4510,
+        0x4610, 0x4710, 0x4810, 0x4910, 0x4A10, 0x4B10, 0x4C10, 0x4D10,
+        0x4E10, 0x4F10, 0x5010, 0x5110, 0x5210, 0x5310, 0x5410, 0x5510,
+        0x5610, 0x5710, 0x5810, 0x5910, 0x5A10, 0x5B10, 0x5C10, 0x5D10,
+        0x5E10, 0x5F10, 0x6010, 0x6110, 0x6210, 0x6310, 0x6410, 0x6510,
+        0x6610, 0x6710, 0x6810, 0x6910, 0x6A10, 0x6B10, 0x6C10, 0x6D10,
+        0x6E10, 0x6F10, 0x7010, 0x7110, 0x7210, 0x7310, 0x7410, 0x7510,
+        0x7610, 0x7710, 0x7810, 0x7910, 0x7A10, 0x7B10, 0x7C10, 0x7D10,
+        0x7E10, 0x7F10, 0x8010, 0x8110, 0x8210, 0x8310, 0x8410, 0x8510,
+        0x8610, 0x8710, 0x8810, 0x8910, 0x8A10, 0x8B10, 0x8C10, 0x8D10,
+        0x8E10, 0x8F10, 0x9010, 0x9110, 0x9210, 0x9310, 0x9410, 0x9510,
+        0x9610, 0x9710, 0x9810, 0x9910, 0x9A10, 0x9B10, 0x9C10, 0x9D10,
+        0x9E10, 0x9F10, 0xA010, 0xA110, 0xA210, 0xA310, 0xA410, 0xA510,
+        0xA610, 0xA710, 0xA810, 0xA910, 0xAA10, 0xAB10, 0xAC10, 0xAD10,
+        0xAE10, 0xAF10, 0xB010, 0xB110, 0xB210, 0xB310, 0xB410, 0xB510,
+        0xB610, 0xB710, 0xB810, 0xB910, 0xBA10, 0xBB10, 0xBC10, 0xBD10,
+        0xBE10, 0xBF10, 0xC010, 0xC110, 0xC210, 0xC310, 0xC410, 0xC510,
+        0xC610, 0xC710, 0xC810, 0xC910, 0xCA10, 0xCB10, 0xCC10, 0xCD10,
+        0xCE10, 0xCF10, 0xD010, 0xD110, 0xD210, 0xD310, 0xD410, 0xD510,
+        0xD610, 0xD710, 0xD810, 0xD910, 0xDA10, 0xDB10, 0xDC10, 0xDD10,
+        0xDE10, 0xDF10, 0xE010, 0xE110, 0xE210, 0xE310, 0xE410, 0xE510,
+        0xE610, 0xE710, 0xE810, 0xE910, 0xEA10, 0xEB10, 0xEC10, 0xED10,
+        0xEE10, 0xEF10, 0xF010, 0xF110, 0xF210, 0xF310, 0xF410, 0xF510,
+        0xF610, 0xF710, 0xF810, 0xF910, 0xFA10, 0xFB10, 0xFC10, 0xFD10,
+        0xFE10, 0xFF10, 0x0090, 0x0110, 0x0210, 0x0310, 0x0410, 0x0510,
+        0x0610, 0x0710, 0x0810, 0x0910, 0x0A10, 0x0B10, 0x0C10, 0x0D10,
+        0x0E10, 0x0F10, 0x1010, 0x1110, 0x1210, 0x1310, 0x1410, 0x1510,
+        0x1610, 0x1710, 0x1810, 0x1910, 0x1A10, 0x1B10, 0x1C10, 0x1D10,
+        0x1E10, 0x1F10, 0x2010, 0x2110, 0x2210, 0x2310, 0x2410, 0x2510,
+        0x2610, 0x2710, 0x2810, 0x2910, 0x2A10, 0x2B10, 0x2C10, 0x2D10,
+        0x2E10, 0x2F10, 0x3010, 0x3110, 0x3210, 0x3310, 0x3410, 0x3510,
+        0x3610, 0x3710, 0x3810, 0x3910, 0x3A10, 0x3B10, 0x3C10, 0x3D10,
+        0x3E10, 0x3F10, 0x4010, 0x4110, 0x4210, 0x4310, 0x4410, 0x4510,
+        0x4610, 0x4710, 0x4810, 0x4910, 0x4A10, 0x4B10, 0x4C10, 0x4D10,
+        0x4E10, 0x4F10, 0x5010, 0x5110, 0x5210, 0x5310, 0x5410, 0x5510,
+        0x5610, 0x5710, 0x5810, 0x5910, 0x5A10, 0x5B10, 0x5C10, 0x5D10,
+        0x5E10, 0x5F10, 0x6010, 0x6110, 0x6210, 0x6310, 0x6410, 0x6510,
+        0x00C0, 0x0140, 0x0240, 0x0340, 0x0440, 0x0540, 0x0640, 0x0740,
+        0x0840, 0x0940, 0x0A40, 0x0B40, 0x0C40, 0x0D40, 0x0E40, 0x0F40,
+        0x1040, 0x1140, 0x1240, 0x1340, 0x1440, 0x1540, 0x1640, 0x1740,
+        0x1840, 0x1940, 0x1A40, 0x1B40, 0x1C40, 0x1D40, 0x1E40, 0x1F40,
+        0x2040, 0x2140, 0x2240, 0x2340, 0x2440, 0x2540, 0x2640, 0x2740,
+        0x2840, 0x2940, 0x2A40, 0x2B40, 0x2C40, 0x2D40, 0x2E40, 0x2F40,
+        0x3040, 0x3140, 0x3240, 0x3340, 0x3440, 0x3540, 0x3640, 0x3740,
+        0x3840, 0x3940, 0x3A40, 0x3B40, 0x3C40, 0x3D40, 0x3E40, 0x3F40,
+        0x4040, 0x4140, 0x4240, 0x4340, 0x4440, 0x4540, 0x4640, 0x4740,
+        0x4840, 0x4940, 0x4A40, 0x4B40, 0x4C40, 0x4D40, 0x4E40, 0x4F40,
+        0x5040, 0x5140, 0x5240, 0x5340, 0x5440, 0x5540, 0x5640, 0x5740,
+        0x5840, 0x5940, 0x5A40, 0x5B40, 0x5C40, 0x5D40, 0x5E40, 0x5F40,
+        0x6040, 0x6140, 0x6240, 0x6340, 0x6440, 0x6540, 0x6640, 0x6740,
+        0x6840, 0x6940, 0x6A40, 0x6B40, 0x6C40, 0x6D40, 0x6E40, 0x6F40,
+        0x7040, 0x7140, 0x7240, 0x7340, 0x7440, 0x7540, 0x7640, 0x7740,
+        0x7840, 0x7940, 0x7A40, 0x7B40, 0x7C40, 0x7D40, 0x7E40, 0x7F40,
+        0x8040, 0x8140, 0x8240, 0x8340, 0x8440, 0x8540, 0x8640, 0x8740,
+        0x8840, 0x8940, 0x8A40, 0x8B40, 0x8C40, 0x8D40, 0x8E40, 0x8F40,
+        0x9040, 0x9140, 0x9240, 0x9340, 0x9440, 0x9540, 0x9640, 0x9740,
+        0x9840, 0x9940, 0x9A40, 0x9B40, 0x9C40, 0x9D40, 0x9E40, 0x9F40,
+        0xA040, 0xA140, 0xA240, 0xA340, 0xA440, 0xA540, 0xA640, 0xA740,
+        0xA840, 0xA940, 0xAA40, 0xAB40, 0xAC40, 0xAD40, 0xAE40, 0xAF40,
+        0xB040, 0xB140, 0xB240, 0xB340, 0xB440, 0xB540, 0xB640, 0xB740,
+        0xB840, 0xB940, 0xBA40, 0xBB40, 0xBC40, 0xBD40, 0xBE40, 0xBF40,
+        0xC040, 0xC140, 0xC240, 0xC340, 0xC440, 0xC540, 0xC640, 0xC740,
+        0xC840, 0xC940, 0xCA40, 0xCB40, 0xCC40, 0xCD40, 0xCE40, 0xCF40,
+        0xD040, 0xD140, 0xD240, 0xD340, 0xD440, 0xD540, 0xD640, 0xD740,
+        0xD840, 0xD940, 0xDA40, 0xDB40, 0xDC40, 0xDD40, 0xDE40, 0xDF40,
+        0xE040, 0xE140, 0xE240, 0xE340, 0xE440, 0xE540, 0xE640, 0xE740,
+        0xE840, 0xE940, 0xEA40, 0xEB40, 0xEC40, 0xED40, 0xEE40, 0xEF40,
+        0xF040, 0xF140, 0xF240, 0xF340, 0xF440, 0xF540, 0xF640, 0xF740,
+        0xF840, 0xF940, 0xFA40, 0xFB40, 0xFC40, 0xFD40, 0xFE40, 0xFF40,
+        0xA050, 0xA150, 0xA250, 0xA350, 0xA450, 0xA550, 0xA650, 0xA750,
+        0xA850, 0xA950, 0xAA50, 0xAB50, 0xAC50, 0xAD50, 0xAE50, 0xAF50,
+        0xB050, 0xB150, 0xB250, 0xB350, 0xB450, 0xB550, 0xB650, 0xB750,
+        0xB850, 0xB950, 0xBA50, 0xBB50, 0xBC50, 0xBD50, 0xBE50, 0xBF50,
+        0xC050, 0xC150, 0xC250, 0xC350, 0xC450, 0xC550, 0xC650, 0xC750,
+        0xC850, 0xC950, 0xCA50, 0xCB50, 0xCC50, 0xCD50, 0xCE50, 0xCF50,
+        0xD050, 0xD150, 0xD250, 0xD350, 0xD450, 0xD550, 0xD650, 0xD750,
+        0xD850, 0xD950, 0xDA50, 0xDB50, 0xDC50, 0xDD50, 0xDE50, 0xDF50,
+        0xE050, 0xE150, 0xE250, 0xE350, 0xE450, 0xE550, 0xE650, 0xE750,
+        0xE850, 0xE950, 0xEA50, 0xEB50, 0xEC50, 0xED50, 0xEE50, 0xEF50,
+        0xF050, 0xF150, 0xF250, 0xF350, 0xF450, 0xF550, 0xF650, 0xF750,
+        0xF850, 0xF950, 0xFA50, 0xFB50, 0xFC50, 0xFD50, 0xFE50, 0xFF50,
+        0x00D0, 0x0150, 0x0250, 0x0350, 0x0450, 0x0550, 0x0650, 0x0750,
+        0x0850, 0x0950, 0x0A50, 0x0B50, 0x0C50, 0x0D50, 0x0E50, 0x0F50,
+        0x1050, 0x1150, 0x1250, 0x1350, 0x1450, 0x1550, 0x1650, 0x1750,
+        0x1850, 0x1950, 0x1A50, 0x1B50, 0x1C50, 0x1D50, 0x1E50, 0x1F50,
+        0x2050, 0x2150, 0x2250, 0x2350, 0x2450, 0x2550, 0x2650, 0x2750,
+        0x2850, 0x2950, 0x2A50, 0x2B50, 0x2C50, 0x2D50, 0x2E50, 0x2F50,
+        0x3050, 0x3150, 0x3250, 0x3350, 0x3450, 0x3550, 0x3650, 0x3750,
+        0x3850, 0x3950, 0x3A50, 0x3B50, 0x3C50, 0x3D50, 0x3E50, 0x3F50,
+        0x4050, 0x4150, 0x4250, 0x4350, 0x4450, 0x4550, 0x4650, 0x4750,
+        0x4850, 0x4950, 0x4A50, 0x4B50, 0x4C50, 0x4D50, 0x4E50, 0x4F50,
+        0x5050, 0x5150, 0x5250, 0x5350, 0x5450, 0x5550, 0x5650, 0x5750,
+        0x5850, 0x5950, 0x5A50, 0x5B50, 0x5C50, 0x5D50, 0x5E50, 0x5F50,
+        0x6050, 0x6150, 0x6250, 0x6350, 0x6450, 0x6550, 0x6650, 0x6750,
+        0x6850, 0x6950, 0x6A50, 0x6B50, 0x6C50, 0x6D50, 0x6E50, 0x6F50,
+        0x7050, 0x7150, 0x7250, 0x7350, 0x7450, 0x7550, 0x7650, 0x7750,
+        0x7850, 0x7950, 0x7A50, 0x7B50, 0x7C50, 0x7D50, 0x7E50, 0x7F50,
+        0x8050, 0x8150, 0x8250, 0x8350, 0x8450, 0x8550, 0x8650, 0x8750,
+        0x8850, 0x8950, 0x8A50, 0x8B50, 0x8C50, 0x8D50, 0x8E50, 0x8F50,
+        0x9050, 0x9150, 0x9250, 0x9350, 0x9450, 0x9550, 0x9650, 0x9750,
+        0x9850, 0x9950, 0x9A50, 0x9B50, 0x9C50, 0x9D50, 0x9E50, 0x9F50,
+        0xFA40, 0xFB40, 0xFC40, 0xFD40, 0xFE40, 0xFF40, 0x00C0, 0x0140,
+        0x0240, 0x0340, 0x0440, 0x0540, 0x0640, 0x0740, 0x0840, 0x0940,
+        0x0A40, 0x0B40, 0x0C40, 0x0D40, 0x0E40, 0x0F40, 0x1040, 0x1140,
+        0x1240, 0x1340, 0x1440, 0x1540, 0x1640, 0x1740, 0x1840, 0x1940,
+        0x1A40, 0x1B40, 0x1C40, 0x1D40, 0x1E40, 0x1F40, 0x2040, 0x2140,
+        0x2240, 0x2340, 0x2440, 0x2540, 0x2640, 0x2740, 0x2840, 0x2940,
+        0x2A40, 0x2B40, 0x2C40, 0x2D40, 0x2E40, 0x2F40, 0x3040, 0x3140,
+        0x3240, 0x3340, 0x3440, 0x3540, 0x3640, 0x3740, 0x3840, 0x3940,
+        0x3A40, 0x3B40, 0x3C40, 0x3D40, 0x3E40, 0x3F40, 0x4040, 0x4140,
+        0x4240, 0x4340, 0x4440, 0x4540, 0x4640, 0x4740, 0x4840, 0x4940,
+        0x4A40, 0x4B40, 0x4C40, 0x4D40, 0x4E40, 0x4F40, 0x5040, 0x5140,
+        0x5240, 0x5340, 0x5440, 0x5540, 0x5640, 0x5740, 0x5840, 0x5940,
+        0x5A40, 0x5B40, 0x5C40, 0x5D40, 0x5E40, 0x5F40, 0x6040, 0x6140,
+        0x6240, 0x6340, 0x6440, 0x6540, 0x6640, 0x6740, 0x6840, 0x6940,
+        0x6A40, 0x6B40, 0x6C40, 0x6D40, 0x6E40, 0x6F40, 0x7040, 0x7140,
+        0x7240, 0x7340, 0x7440, 0x7540, 0x7640, 0x7740, 0x7840, 0x7940,
+        0x7A40, 0x7B40, 0x7C40, 0x7D40, 0x7E40, 0x7F40, 0x8040, 0x8140,
+        0x8240, 0x8340, 0x8440, 0x8540, 0x8640, 0x8740, 0x8840, 0x8940,
+        0x8A40, 0x8B40, 0x8C40, 0x8D40, 0x8E40, 0x8F40, 0x9040, 0x9140,
+        0x9240, 0x9340, 0x9440, 0x9540, 0x9640, 0x9740, 0x9840, 0x9940,
+        0x9A40, 0x9B40, 0x9C40, 0x9D40, 0x9E40, 0x9F40, 0xA040, 0xA140,
+        0xA240, 0xA340, 0xA440, 0xA540, 0xA640, 0xA740, 0xA840, 0xA940,
+        0xAA40, 0xAB40, 0xAC40, 0xAD40, 0xAE40, 0xAF40, 0xB040, 0xB140,
+        0xB240, 0xB340, 0xB440, 0xB540, 0xB640, 0xB740, 0xB840, 0xB940,
+        0xBA40, 0xBB40, 0xBC40, 0xBD40, 0xBE40, 0xBF40, 0xC040, 0xC140,
+        0xC240, 0xC340, 0xC440, 0xC540, 0xC640, 0xC740, 0xC840, 0xC940,
+        0xCA40, 0xCB40, 0xCC40, 0xCD40, 0xCE40, 0xCF40, 0xD040, 0xD140,
+        0xD240, 0xD340, 0xD440, 0xD540, 0xD640, 0xD740, 0xD840, 0xD940,
+        0xDA40, 0xDB40, 0xDC40, 0xDD40, 0xDE40, 0xDF40, 0xE040, 0xE140,
+        0xE240, 0xE340, 0xE440, 0xE540, 0xE640, 0xE740, 0xE840, 0xE940,
+        0xEA40, 0xEB40, 0xEC40, 0xED40, 0xEE40, 0xEF40, 0xF040, 0xF140,
+        0xF240, 0xF340, 0xF440, 0xF540, 0xF640, 0xF740, 0xF840, 0xF940,
+        0x9A50, 0x9B50, 0x9C50, 0x9D50, 0x9E50, 0x9F50, 0xA050, 0xA150,
+        0xA250, 0xA350, 0xA450, 0xA550, 0xA650, 0xA750, 0xA850, 0xA950,
+        0xAA50, 0xAB50, 0xAC50, 0xAD50, 0xAE50, 0xAF50, 0xB050, 0xB150,
+        0xB250, 0xB350, 0xB450, 0xB550, 0xB650, 0xB750, 0xB850, 0xB950,
+        0xBA50, 0xBB50, 0xBC50, 0xBD50, 0xBE50, 0xBF50, 0xC050, 0xC150,
+        0xC250, 0xC350, 0xC450, 0xC550, 0xC650, 0xC750, 0xC850, 0xC950,
+        0xCA50, 0xCB50, 0xCC50, 0xCD50, 0xCE50, 0xCF50, 0xD050, 0xD150,
+        0xD250, 0xD350, 0xD450, 0xD550, 0xD650, 0xD750, 0xD850, 0xD950,
+        0xDA50, 0xDB50, 0xDC50, 0xDD50, 0xDE50, 0xDF50, 0xE050, 0xE150,
+        0xE250, 0xE350, 0xE450, 0xE550, 0xE650, 0xE750, 0xE850, 0xE950,
+        0xEA50, 0xEB50, 0xEC50, 0xED50, 0xEE50, 0xEF50, 0xF050, 0xF150,
+        0xF250, 0xF350, 0xF450, 0xF550, 0xF650, 0xF750, 0xF850, 0xF950,
+        0xFA50, 0xFB50, 0xFC50, 0xFD50, 0xFE50, 0xFF50, 0x00D0, 0x0150,
+        0x0250, 0x0350, 0x0450, 0x0550, 0x0650, 0x0750, 0x0850, 0x0950,
+        0x0A50, 0x0B50, 0x0C50, 0x0D50, 0x0E50, 0x0F50, 0x1050, 0x1150,
+        0x1250, 0x1350, 0x1450, 0x1550, 0x1650, 0x1750, 0x1850, 0x1950,
+        0x1A50, 0x1B50, 0x1C50, 0x1D50, 0x1E50, 0x1F50, 0x2050, 0x2150,
+        0x2250, 0x2350, 0x2450, 0x2550, 0x2650, 0x2750, 0x2850, 0x2950,
+        0x2A50, 0x2B50, 0x2C50, 0x2D50, 0x2E50, 0x2F50, 0x3050, 0x3150,
+        0x3250, 0x3350, 0x3450, 0x3550, 0x3650, 0x3750, 0x3850, 0x3950,
+        0x3A50, 0x3B50, 0x3C50, 0x3D50, 0x3E50, 0x3F50, 0x4050, 0x4150,
+        0x4250, 0x4350, 0x4450, 0x4550, 0x4650, 0x4750, 0x4850, 0x4950,
+        0x4A50, 0x4B50, 0x4C50, 0x4D50, 0x4E50, 0x4F50, 0x5050, 0x5150,
+        0x5250, 0x5350, 0x5450, 0x5550, 0x5650, 0x5750, 0x5850, 0x5950,
+        0x5A50, 0x5B50, 0x5C50, 0x5D50, 0x5E50, 0x5F50, 0x6050, 0x6150,
+        0x6250, 0x6350, 0x6450, 0x6550, 0x6650, 0x6750, 0x6850, 0x6950,
+        0x6A50, 0x6B50, 0x6C50, 0x6D50, 0x6E50, 0x6F50, 0x7050, 0x7150,
+        0x7250, 0x7350, 0x7450, 0x7550, 0x7650, 0x7750, 0x7850, 0x7950,
+        0x7A50, 0x7B50, 0x7C50, 0x7D50, 0x7E50, 0x7F50, 0x8050, 0x8150,
+        0x8250, 0x8350, 0x8450, 0x8550, 0x8650, 0x8750, 0x8850, 0x8950,
+        0x8A50, 0x8B50, 0x8C50, 0x8D50, 0x8E50, 0x8F50, 0x9050, 0x9150,
+        0x9250, 0x9350, 0x9450, 0x9550, 0x9650, 0x9750, 0x9850, 0x9950,
+    ];
+
+    #[test]
+    fn test_daa() {
+        let mut cpu = setup();
+
+        for (input, expected) in DAA_RESULTS.iter().enumerate() {
+            cpu.registers.a = input as u8;
+            cpu.registers.set_flag(Flag::Zero, input % 3 == 0);
+            cpu.registers.set_flag(Flag::Sub, input & 0x400 != 0);
+            cpu.registers.set_flag(Flag::HalfCarry, input & 0x200 != 0);
+            cpu.registers.set_flag(Flag::Carry, input & 0x100 != 0);
+
+            cpu.daa();
+
+            let result = shift_left!(cpu.registers.a, 8, u16) | cpu.registers.f as u16;
+            assert_eq!(
+                result,
+                *expected,
+                "A = {:#04X}, N H C = {:03b}",
+                input as u8,
+                input >> 8
+            );
+        }
+    }
+
     #[rstest]
     #[case(1, 0b11111110)]
     #[case(2, 0b11111101)]
@@ -1888,7 +2109,7 @@ mod tests {
         let mut cpu = setup();
 
         cpu.registers.a = a_value;
-        cpu.cpl();
+        cpu.cpl(decoded(OpCode::CPL)).unwrap();
         assert_eq!(cpu.registers.a, expected);
         //Zero and Carry flag are not affected, thus will be 0 because they are initialised to 0
         assert!(!cpu.registers.get_flag(Flag::Zero));
@@ -1932,12 +2153,15 @@ mod tests {
             Target::E => cpu.registers.e = reg_value,
             Target::H => cpu.registers.h = reg_value,
             Target::L => cpu.registers.l = reg_value,
-            Target::HL => cpu
-                .registers
-                .set_combined_register(Target::HL, reg_value as u16),
+            Target::HL => {
+                let _ = cpu.bus.borrow_mut().write(cpu.sp, reg_value);
+                cpu.registers
+                    .set_combined_register(Target::HL, cpu.sp)
+                    .unwrap();
+            }
             _ => panic!("Unsupported register"),
         }
-        cpu.dec(reg);
+        cpu.dec(decoded(OpCode::DEC(reg)), reg).unwrap();
 
         let result = match reg {
             Target::A => cpu.registers.a,
@@ -1947,7 +2171,7 @@ mod tests {
             Target::E => cpu.registers.e,
             Target::H => cpu.registers.h,
             Target::L => cpu.registers.l,
-            Target::HL => cpu.registers.combined_register(Target::HL) as u8,
+            Target::HL => cpu.bus.borrow().read(cpu.sp).unwrap(),
             _ => panic!("Unsupported register"),
         };
 
@@ -1985,14 +2209,14 @@ mod tests {
         if reg == Target::SP {
             cpu.sp = reg_value;
         } else {
-            cpu.registers.set_combined_register(reg, reg_value);
+            cpu.registers.set_combined_register(reg, reg_value).unwrap();
         }
-        cpu.dec_16(reg);
+        cpu.dec_16(reg).unwrap();
 
         if reg == Target::SP {
             assert_eq!(cpu.sp, expected);
         } else {
-            assert_eq!(cpu.registers.combined_register(reg), expected);
+            assert_eq!(cpu.registers.combined_register(reg).unwrap(), expected);
         }
     }
 
@@ -2004,14 +2228,14 @@ mod tests {
     #[case(0, Target::E, 1, false, false, false, false)]
     #[case(0, Target::H, 1, false, false, false, false)]
     #[case(0, Target::L, 1, false, false, false, false)]
-    #[case(255, Target::L, 0, true, false, false, false)]
-    #[case(255, Target::A, 0, true, false, false, false)]
-    #[case(255, Target::B, 0, true, false, false, false)]
-    #[case(255, Target::C, 0, true, false, false, false)]
-    #[case(255, Target::D, 0, true, false, false, false)]
-    #[case(255, Target::E, 0, true, false, false, false)]
-    #[case(255, Target::H, 0, true, false, false, false)]
-    #[case(255, Target::L, 0, true, false, false, false)]
+    #[case(255, Target::L, 0, true, false, true, false)]
+    #[case(255, Target::A, 0, true, false, true, false)]
+    #[case(255, Target::B, 0, true, false, true, false)]
+    #[case(255, Target::C, 0, true, false, true, false)]
+    #[case(255, Target::D, 0, true, false, true, false)]
+    #[case(255, Target::E, 0, true, false, true, false)]
+    #[case(255, Target::H, 0, true, false, true, false)]
+    #[case(255, Target::L, 0, true, false, true, false)]
     #[case(0b1111, Target::A, 0b10000, false, false, true, false)]
     #[case(0b1111, Target::B, 0b10000, false, false, true, false)]
     #[case(0b1111, Target::C, 0b10000, false, false, true, false)]
@@ -2038,7 +2262,7 @@ mod tests {
             Target::L => cpu.registers.l = reg_value,
             _ => panic!("Unsupported register"),
         }
-        cpu.inc(reg);
+        cpu.inc(decoded(OpCode::INC(reg)), reg).unwrap();
 
         let result = match reg {
             Target::A => cpu.registers.a,
@@ -2089,21 +2313,30 @@ mod tests {
             cpu.sp = reg_value;
         } else {
             match reg {
-                Target::HL => cpu.registers.set_combined_register(Target::HL, reg_value),
-                Target::BC => cpu.registers.set_combined_register(Target::BC, reg_value),
-                Target::DE => cpu.registers.set_combined_register(Target::DE, reg_value),
+                Target::HL => cpu
+                    .registers
+                    .set_combined_register(Target::HL, reg_value)
+                    .unwrap(),
+                Target::BC => cpu
+                    .registers
+                    .set_combined_register(Target::BC, reg_value)
+                    .unwrap(),
+                Target::DE => cpu
+                    .registers
+                    .set_combined_register(Target::DE, reg_value)
+                    .unwrap(),
                 _ => panic!("Unsupported register"),
             }
         }
-        cpu.inc_16(reg);
+        cpu.inc_16(reg).unwrap();
 
         let result = if reg == Target::SP {
             cpu.sp
         } else {
             match reg {
-                Target::HL => cpu.registers.combined_register(Target::HL),
-                Target::BC => cpu.registers.combined_register(Target::BC),
-                Target::DE => cpu.registers.combined_register(Target::DE),
+                Target::HL => cpu.registers.combined_register(Target::HL).unwrap(),
+                Target::BC => cpu.registers.combined_register(Target::BC).unwrap(),
+                Target::DE => cpu.registers.combined_register(Target::DE).unwrap(),
                 _ => panic!("Unsupported register"),
             }
         };
@@ -2123,7 +2356,7 @@ mod tests {
             .bus
             .borrow_mut()
             .write(cpu.pc + 2, shift_right!(address, 8, u8));
-        cpu.jp();
+        cpu.jp().unwrap();
 
         assert_eq!(cpu.pc, 100);
     }
@@ -2133,9 +2366,10 @@ mod tests {
         let mut cpu = setup();
 
         cpu.registers
-            .set_combined_register(Target::HL, 0b0000001010001000);
+            .set_combined_register(Target::HL, 0b0000001010001000)
+            .unwrap();
 
-        cpu.jump_hl();
+        cpu.jump_hl().unwrap();
 
         assert_eq!(cpu.pc, 0b0000001010001000);
     }
@@ -2156,7 +2390,7 @@ mod tests {
 
         let _ = cpu.bus.borrow_mut().write_16(cpu.pc + 1, expected);
         cpu.registers.set_flag(flag_to_set, flag_value);
-        cpu.jump_by_flag(jump_flag);
+        cpu.jump_by_flag(jump_flag).unwrap();
 
         assert_eq!(cpu.pc, expected);
     }
@@ -2165,11 +2399,14 @@ mod tests {
     fn test_jruc() {
         let mut cpu = setup();
 
-        let initial = cpu.pc as u8;
-        cpu.write_to_memory(cpu.pc + 1, initial + 100);
-        cpu.jruc();
+        cpu.pc = 0x0100;
+        cpu.write_to_memory(cpu.pc + 1, 100);
+        cpu.jruc().unwrap();
+        assert_eq!(cpu.pc, 0x0100 + 2 + 100);
 
-        assert_eq!(cpu.pc as u8, initial + 100);
+        cpu.write_to_memory(cpu.pc + 1, -10i8 as u8);
+        cpu.jruc().unwrap();
+        assert_eq!(cpu.pc, 0x0100 + 2 + 100 + 2 - 10);
     }
 
     #[rstest]
@@ -2180,12 +2417,12 @@ mod tests {
     fn test_jr(#[case] flag_to_set: Flag, #[case] flag_value: bool, #[case] jump_flag: Flag) {
         let mut cpu = setup();
 
-        let initial = cpu.pc as u8;
-        cpu.write_to_memory(cpu.pc + 1, initial + 100);
+        cpu.pc = 0x0100;
+        cpu.write_to_memory(cpu.pc + 1, -100i8 as u8);
         cpu.registers.set_flag(flag_to_set, flag_value);
-        cpu.jr(jump_flag);
+        assert!(cpu.jr(jump_flag).unwrap());
 
-        assert_eq!(cpu.pc as u8, initial + 100);
+        assert_eq!(cpu.pc, 0x0100 + 2 - 100);
     }
 
     #[rstest]
@@ -2201,8 +2438,11 @@ mod tests {
     #[case(Target::HL)]
     fn test_load(#[case] dst: Target) {
         let mut cpu = setup();
+        for pair in [Target::BC, Target::DE, Target::HL] {
+            cpu.registers.set_combined_register(pair, cpu.sp).unwrap();
+        }
         cpu.registers.a = 100;
-        cpu.load(dst, Target::A);
+        cpu.load(dst, Target::A).unwrap();
         let result = match dst {
             Target::A => cpu.registers.a as u16,
             Target::B => cpu.registers.b as u16,
@@ -2211,15 +2451,7 @@ mod tests {
             Target::E => cpu.registers.e as u16,
             Target::H => cpu.registers.h as u16,
             Target::L => cpu.registers.l as u16,
-            Target::BC => cpu.registers.combined_register(Target::BC),
-            Target::DE => cpu.registers.combined_register(Target::DE),
-            Target::HL => cpu.registers.combined_register(Target::HL),
-            Target::D8 => {
-                unimplemented!();
-            }
-            Target::A16 => {
-                unimplemented!();
-            }
+            Target::BC | Target::DE | Target::HL => cpu.bus.borrow().read(cpu.sp).unwrap() as u16,
             _ => panic!("Unsupported register"),
         };
         assert_eq!(result, 100);
@@ -2228,25 +2460,25 @@ mod tests {
     #[rstest]
     #[case(Target::A, Target::A8)]
     #[case(Target::A8, Target::A)]
+    #[case(Target::A, Target::C)]
+    #[case(Target::C, Target::A)]
     fn test_ldh(#[case] dst: Target, #[case] src: Target) {
         let mut cpu = setup();
+        let _ = cpu.bus.borrow_mut().write(cpu.pc + 1, 0x85);
+        cpu.registers.c = 0x85;
 
         match src {
             Target::A => cpu.registers.a = 5,
-            Target::A8 => {
-                let mut bus = cpu.bus.borrow_mut();
-                let _ = bus.write(cpu.pc + 1, 5);
+            _ => {
+                let _ = cpu.bus.borrow_mut().write(0xFF85, 5);
             }
-            _ => panic!("Unsupported register"),
         }
 
-        println!("cpu: {cpu}");
-        cpu.ldh(dst, src);
+        cpu.ldh(dst, src).unwrap();
 
         let res = match dst {
             Target::A => cpu.registers.a,
-            Target::A8 => cpu.bus.borrow().read(0xFF05).unwrap(),
-            _ => panic!("Unsupported register"),
+            _ => cpu.bus.borrow().read(0xFF85).unwrap(),
         };
 
         assert_eq!(res, 5);
@@ -2273,10 +2505,15 @@ mod tests {
             Target::E => cpu.registers.e = 5,
             Target::H => cpu.registers.h = 5,
             Target::L => cpu.registers.l = 5,
-            Target::HL => cpu.registers.set_combined_register(Target::HL, 5),
+            Target::HL => {
+                let _ = cpu.bus.borrow_mut().write(cpu.sp, 5);
+                cpu.registers
+                    .set_combined_register(Target::HL, cpu.sp)
+                    .unwrap();
+            }
             _ => panic!("Unsupported register"),
         }
-        cpu.or(reg);
+        cpu.or(decoded(OpCode::OR(reg)), reg).unwrap();
 
         assert_eq!(cpu.registers.a, expected);
     }
@@ -2284,22 +2521,25 @@ mod tests {
     #[test]
     fn test_push_and_pop() {
         let mut cpu = setup();
-        cpu.sp += 2;
+        cpu.sp = 0xCFF0;
 
         cpu.registers
-            .set_combined_register(Target::HL, 0b1000100000010001);
+            .set_combined_register(Target::HL, 0b1000100000010001)
+            .unwrap();
 
-        cpu.push(Target::HL);
+        cpu.push(Target::HL).unwrap();
 
+        assert_eq!(cpu.sp, 0xCFEE);
+        assert_eq!(cpu.bus.borrow().read(cpu.sp).unwrap(), 0b00010001);
         assert_eq!(cpu.bus.borrow().read(cpu.sp + 1).unwrap(), 0b10001000);
-        assert_eq!(cpu.bus.borrow().read(cpu.sp + 2).unwrap(), 0b00010001);
 
-        cpu.pop(Target::BC);
+        cpu.pop(Target::BC).unwrap();
 
         assert_eq!(
-            cpu.registers.combined_register(Target::BC),
+            cpu.registers.combined_register(Target::BC).unwrap(),
             0b1000100000010001
         );
+        assert_eq!(cpu.sp, 0xCFF0);
     }
 
     #[rstest]
@@ -2337,12 +2577,15 @@ mod tests {
             Target::E => cpu.registers.e = reg_value,
             Target::H => cpu.registers.h = reg_value,
             Target::L => cpu.registers.l = reg_value,
-            Target::HL => cpu
-                .registers
-                .set_combined_register(Target::HL, reg_value as u16),
+            Target::HL => {
+                let _ = cpu.bus.borrow_mut().write(cpu.sp, reg_value);
+                cpu.registers
+                    .set_combined_register(Target::HL, cpu.sp)
+                    .unwrap();
+            }
             _ => panic!("Unsupported register"),
         }
-        cpu.sub(reg);
+        cpu.sub(decoded(OpCode::SUB(reg)), reg).unwrap();
 
         assert_eq!(cpu.registers.a, expected, "False result");
         assert_eq!(
@@ -2419,12 +2662,15 @@ mod tests {
             Target::E => cpu.registers.e = reg_value,
             Target::H => cpu.registers.h = reg_value,
             Target::L => cpu.registers.l = reg_value,
-            Target::HL => cpu
-                .registers
-                .set_combined_register(Target::HL, reg_value as u16),
+            Target::HL => {
+                let _ = cpu.bus.borrow_mut().write(cpu.sp, reg_value);
+                cpu.registers
+                    .set_combined_register(Target::HL, cpu.sp)
+                    .unwrap();
+            }
             _ => panic!("Unsupported register"),
         }
-        cpu.res(bit_pos, reg);
+        cpu.res(bit_pos, reg).unwrap();
 
         let result = match reg {
             Target::A => cpu.registers.a,
@@ -2434,7 +2680,7 @@ mod tests {
             Target::E => cpu.registers.e,
             Target::H => cpu.registers.h,
             Target::L => cpu.registers.l,
-            Target::HL => cpu.registers.combined_register(Target::HL) as u8,
+            Target::HL => cpu.bus.borrow().read(cpu.sp).unwrap(),
             _ => panic!("Unsupported register"),
         };
 
@@ -2485,10 +2731,15 @@ mod tests {
             Target::E => cpu.registers.e = value as u8,
             Target::H => cpu.registers.h = value as u8,
             Target::L => cpu.registers.l = value as u8,
-            Target::HL => cpu.registers.set_combined_register(Target::HL, value),
+            Target::HL => {
+                let _ = cpu.bus.borrow_mut().write(cpu.sp, value as u8);
+                cpu.registers
+                    .set_combined_register(Target::HL, cpu.sp)
+                    .unwrap();
+            }
             _ => panic!("Unsupported register"),
         }
-        cpu.rl(reg);
+        cpu.rl(reg).unwrap();
 
         let result = match reg {
             Target::A => cpu.registers.a as u16,
@@ -2498,7 +2749,7 @@ mod tests {
             Target::E => cpu.registers.e as u16,
             Target::H => cpu.registers.h as u16,
             Target::L => cpu.registers.l as u16,
-            Target::HL => cpu.registers.combined_register(Target::HL),
+            Target::HL => cpu.bus.borrow().read(cpu.sp).unwrap() as u16,
             _ => panic!("Unsupported register"),
         };
 
@@ -2517,12 +2768,12 @@ mod tests {
         let mut cpu = setup();
 
         cpu.registers.a = 0b10000000;
-        cpu.rla();
+        cpu.rla().unwrap();
 
         assert!(cpu.registers.get_flag(Flag::Carry));
         assert_eq!(cpu.registers.a, 0);
 
-        cpu.rla();
+        cpu.rla().unwrap();
 
         assert!(!cpu.registers.get_flag(Flag::Carry));
         assert_eq!(cpu.registers.a, 1);
@@ -2534,13 +2785,13 @@ mod tests {
 
         cpu.registers.set_flag(Flag::Carry, true);
         cpu.registers.a = 1;
-        cpu.rlc(Target::A);
+        cpu.rlc(Target::A).unwrap();
 
         assert_eq!(cpu.registers.a, 2);
         assert!(!cpu.registers.get_flag(Flag::Carry));
 
         cpu.registers.a = 0b10000000;
-        cpu.rlc(Target::A);
+        cpu.rlc(Target::A).unwrap();
 
         assert_eq!(cpu.registers.a, 1);
         assert!(cpu.registers.get_flag(Flag::Carry));
@@ -2573,12 +2824,15 @@ mod tests {
             Target::E => cpu.registers.e = value1,
             Target::H => cpu.registers.h = value1,
             Target::L => cpu.registers.l = value1,
-            Target::HL => cpu
-                .registers
-                .set_combined_register(Target::HL, value1 as u16),
+            Target::HL => {
+                let _ = cpu.bus.borrow_mut().write(cpu.sp, value1);
+                cpu.registers
+                    .set_combined_register(Target::HL, cpu.sp)
+                    .unwrap();
+            }
             _ => panic!("Unsupported register"),
         }
-        cpu.rr(reg);
+        cpu.rr(reg).unwrap();
 
         let result = match reg {
             Target::A => cpu.registers.a,
@@ -2588,7 +2842,7 @@ mod tests {
             Target::E => cpu.registers.e,
             Target::H => cpu.registers.h,
             Target::L => cpu.registers.l,
-            Target::HL => cpu.registers.combined_register(Target::HL) as u8,
+            Target::HL => cpu.bus.borrow().read(cpu.sp).unwrap(),
             _ => panic!("Unsupported register"),
         };
 
@@ -2603,12 +2857,15 @@ mod tests {
             Target::E => cpu.registers.e = value2,
             Target::H => cpu.registers.h = value2,
             Target::L => cpu.registers.l = value2,
-            Target::HL => cpu
-                .registers
-                .set_combined_register(Target::HL, value1 as u16),
+            Target::HL => {
+                let _ = cpu.bus.borrow_mut().write(cpu.sp, value2);
+                cpu.registers
+                    .set_combined_register(Target::HL, cpu.sp)
+                    .unwrap();
+            }
             _ => panic!("Unsupported register"),
         }
-        cpu.rr(reg);
+        cpu.rr(reg).unwrap();
 
         let result = match reg {
             Target::A => cpu.registers.a,
@@ -2618,7 +2875,7 @@ mod tests {
             Target::E => cpu.registers.e,
             Target::H => cpu.registers.h,
             Target::L => cpu.registers.l,
-            Target::HL => cpu.registers.combined_register(Target::HL) as u8,
+            Target::HL => cpu.bus.borrow().read(cpu.sp).unwrap(),
             _ => panic!("Unsupported register"),
         };
         assert_eq!(result, expected2);
@@ -2630,25 +2887,26 @@ mod tests {
         let mut cpu = setup();
 
         cpu.registers.a = 1;
-        cpu.rra();
+        cpu.rra().unwrap();
 
         assert!(cpu.registers.get_flag(Flag::Carry));
         assert_eq!(cpu.registers.a, 0);
 
-        cpu.rra();
+        cpu.rra().unwrap();
 
         assert!(!cpu.registers.get_flag(Flag::Carry));
         assert_eq!(cpu.registers.a, 0b10000000);
     }
 
     #[rstest]
-    #[case(Target::A, 1, true, 0b10000000, true, 0b11000000, false)]
-    #[case(Target::B, 1, true, 0b10000000, true, 0b11000000, false)]
-    #[case(Target::C, 1, true, 0b10000000, true, 0b11000000, false)]
-    #[case(Target::D, 1, true, 0b10000000, true, 0b11000000, false)]
-    #[case(Target::E, 1, true, 0b10000000, true, 0b11000000, false)]
-    #[case(Target::H, 1, true, 0b10000000, true, 0b11000000, false)]
-    #[case(Target::L, 1, true, 0b10000000, true, 0b11000000, false)]
+    #[case(Target::A, 1, false, 0b10000000, true, 0b01000000, false)]
+    #[case(Target::B, 1, false, 0b10000000, true, 0b01000000, false)]
+    #[case(Target::C, 1, false, 0b10000000, true, 0b01000000, false)]
+    #[case(Target::D, 1, false, 0b10000000, true, 0b01000000, false)]
+    #[case(Target::E, 1, false, 0b10000000, true, 0b01000000, false)]
+    #[case(Target::H, 1, false, 0b10000000, true, 0b01000000, false)]
+    #[case(Target::L, 1, false, 0b10000000, true, 0b01000000, false)]
+    #[case(Target::HL, 1, false, 0b10000000, true, 0b01000000, false)]
     fn test_rrc(
         #[case] reg: Target,
         #[case] value1: u8,
@@ -2669,12 +2927,15 @@ mod tests {
             Target::E => cpu.registers.e = value1,
             Target::H => cpu.registers.h = value1,
             Target::L => cpu.registers.l = value1,
-            Target::HL => cpu
-                .registers
-                .set_combined_register(Target::HL, value1 as u16),
+            Target::HL => {
+                let _ = cpu.bus.borrow_mut().write(cpu.sp, value1);
+                cpu.registers
+                    .set_combined_register(Target::HL, cpu.sp)
+                    .unwrap();
+            }
             _ => panic!("Unsupported register"),
         }
-        cpu.rrc(reg);
+        cpu.rrc(reg).unwrap();
 
         let result = match reg {
             Target::A => cpu.registers.a,
@@ -2684,13 +2945,13 @@ mod tests {
             Target::E => cpu.registers.e,
             Target::H => cpu.registers.h,
             Target::L => cpu.registers.l,
-            Target::HL => cpu.registers.combined_register(Target::HL) as u8,
+            Target::HL => cpu.bus.borrow().read(cpu.sp).unwrap(),
             _ => panic!("Unsupported register"),
         };
         assert_eq!(result, expected1);
         assert_eq!(cpu.registers.get_flag(Flag::Carry), expected_flag1);
 
-        cpu.rrc(reg);
+        cpu.rrc(reg).unwrap();
 
         let result = match reg {
             Target::A => cpu.registers.a,
@@ -2700,7 +2961,7 @@ mod tests {
             Target::E => cpu.registers.e,
             Target::H => cpu.registers.h,
             Target::L => cpu.registers.l,
-            Target::HL => cpu.registers.combined_register(Target::HL) as u8,
+            Target::HL => cpu.bus.borrow().read(cpu.sp).unwrap(),
             _ => panic!("Unsupported register"),
         };
         assert_eq!(result, expected2);
@@ -2712,7 +2973,7 @@ mod tests {
         let mut cpu = setup();
 
         cpu.registers.a = 1;
-        cpu.rrca();
+        cpu.rrca().unwrap();
 
         assert!(cpu.registers.get_flag(Flag::Carry));
         assert_eq!(cpu.registers.a, (1 << ZERO_BIT_POS));
@@ -2746,7 +3007,7 @@ mod tests {
             Target::L => cpu.registers.l = value,
             _ => panic!("Unsupported register"),
         }
-        cpu.sbc(reg);
+        cpu.sbc(decoded(OpCode::SBC(reg)), reg).unwrap();
 
         assert_eq!(cpu.registers.a, expected);
     }
@@ -2762,7 +3023,7 @@ mod tests {
     fn test_set(#[case] reg: Target, #[case] bit_pos: u8) {
         let mut cpu = setup();
 
-        cpu.set(bit_pos, reg);
+        cpu.set(bit_pos, reg).unwrap();
 
         let result = match reg {
             Target::A => cpu.registers.a,
@@ -2785,7 +3046,7 @@ mod tests {
     #[case(Target::E)]
     #[case(Target::H)]
     #[case(Target::L)]
-    fn test_sl(#[case] reg: Target) {
+    fn test_sla_registers(#[case] reg: Target) {
         let mut cpu = setup();
 
         match reg {
@@ -2798,7 +3059,7 @@ mod tests {
             Target::L => cpu.registers.l = 1,
             _ => panic!("Unsupported register"),
         }
-        cpu.sl(reg);
+        cpu.sla(reg).unwrap();
 
         let result = match reg {
             Target::A => cpu.registers.a,
@@ -2822,7 +3083,7 @@ mod tests {
             Target::L => cpu.registers.l = 0b10000000,
             _ => panic!("Unsupported register"),
         }
-        cpu.sl(reg);
+        cpu.sla(reg).unwrap();
 
         let result = match reg {
             Target::A => cpu.registers.a,
@@ -2835,6 +3096,8 @@ mod tests {
             _ => panic!("Unsupported register"),
         };
         assert_eq!(result, 0);
+        assert!(cpu.registers.get_flag(Flag::Carry));
+        assert!(cpu.registers.get_flag(Flag::Zero));
     }
 
     #[test]
@@ -2842,7 +3105,7 @@ mod tests {
         let mut cpu = setup();
 
         cpu.registers.a = 1;
-        cpu.sla(Target::A);
+        cpu.sla(Target::A).unwrap();
 
         assert_eq!(cpu.registers.a, 2);
     }
@@ -2868,7 +3131,7 @@ mod tests {
             Target::L => cpu.registers.l = 2,
             _ => panic!("Unsupported register"),
         }
-        cpu.srl(reg);
+        cpu.srl(reg).unwrap();
 
         let result = match reg {
             Target::A => cpu.registers.a,
@@ -2882,7 +3145,7 @@ mod tests {
         };
         assert_eq!(result, 1);
 
-        cpu.srl(reg);
+        cpu.srl(reg).unwrap();
 
         let result = match reg {
             Target::A => cpu.registers.a,
@@ -2902,7 +3165,7 @@ mod tests {
         let mut cpu = setup();
 
         cpu.registers.a = 0b10000000;
-        cpu.sra(Target::A);
+        cpu.sra(Target::A).unwrap();
 
         assert_eq!(cpu.registers.a, 0b11000000);
     }
@@ -2928,7 +3191,7 @@ mod tests {
             Target::L => cpu.registers.l = 128 + 4,
             _ => panic!("Unsupported register"),
         }
-        cpu.swap(reg);
+        cpu.swap(reg).unwrap();
 
         let result = match reg {
             Target::A => cpu.registers.a,
@@ -2965,8 +3228,1225 @@ mod tests {
             Target::L => cpu.registers.l = 5,
             _ => panic!("Unsupported register"),
         }
-        cpu.xor(reg);
+        cpu.xor(decoded(OpCode::XOR(reg)), reg).unwrap();
 
         assert_eq!(cpu.registers.a, expected);
     }
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_tick_writes_trace() {
+        let mut cpu = setup();
+        let buffer = SharedBuffer::default();
+        cpu.set_tracer(Some(TraceWriter::new(Box::new(buffer.clone()))));
+
+        cpu.registers.a = 0x01;
+        cpu.sp = 0xFFFE;
+        let _ = cpu.bus.borrow_mut().write(1, 0xCB);
+        let _ = cpu.bus.borrow_mut().write(2, 0x37);
+
+        assert!(cpu.tick().unwrap());
+        assert!(cpu.tick().unwrap());
+        assert!(cpu.tick().unwrap());
+
+        let trace = String::from_utf8(buffer.0.borrow().clone()).unwrap();
+        assert_eq!(
+            trace,
+            "A:01 F:00 B:00 C:00 D:00 E:00 H:00 L:00 SP:FFFE PC:0000 PCMEM:00,CB,37,00\n\
+             A:01 F:00 B:00 C:00 D:00 E:00 H:00 L:00 SP:FFFE PC:0001 PCMEM:CB,37,00,00\n"
+        );
+    }
+
+    #[test]
+    fn test_service_interrupt() {
+        let mut cpu = setup();
+        cpu.pc = 0x0123;
+        cpu.sp = 0xCFF0;
+        cpu.interrupts_enabled = true;
+        let _ = cpu.bus.borrow_mut().write(0xFFFF, Interrupt::Serial.bit());
+        let _ = cpu
+            .bus
+            .borrow_mut()
+            .write(0xFF0F, Interrupt::Serial.bit() | Interrupt::Joypad.bit());
+
+        assert!(cpu.tick().unwrap());
+
+        assert_eq!(cpu.pc, Interrupt::Serial.vector());
+        assert_eq!(cpu.sp, 0xCFEE);
+        assert!(!cpu.interrupts_enabled);
+        assert_eq!(cpu.cycles(), 20);
+        assert_eq!(cpu.read_memory(0xCFEE).unwrap(), 0x23);
+        assert_eq!(cpu.read_memory(0xCFEF).unwrap(), 0x01);
+        assert_eq!(cpu.read_memory(0xFF0F).unwrap(), 0b11110000);
+
+        // Disabled interrupts are left pending
+        assert!(cpu.tick().unwrap());
+        assert_eq!(cpu.pc, Interrupt::Serial.vector() + 1);
+
+        let reti = Instruction::byte_from_opcode(OpCode::RETI).unwrap();
+        cpu.pc = cpu
+            .execute(Instruction::fetch(reti, false).unwrap())
+            .unwrap();
+        assert!(cpu.interrupts_enabled);
+        assert_eq!(cpu.pc, 0x0123);
+        assert_eq!(cpu.sp, 0xCFF0);
+    }
+
+    // Serial requested and enabled, interrupts off and code at 0xC000
+    fn setup_pending_serial(code: &[u8]) -> Cpu {
+        let mut cpu = setup();
+        cpu.pc = 0xC000;
+        cpu.sp = 0xCFF0;
+        cpu.interrupts_enabled = false;
+        for (address, byte) in (0xC000..).zip(code) {
+            cpu.write_to_memory(address, *byte);
+        }
+        cpu.write_to_memory(0xFFFF, Interrupt::Serial.bit());
+        cpu.write_to_memory(0xFF0F, Interrupt::Serial.bit());
+        cpu
+    }
+
+    #[test]
+    fn test_ei_waits_for_next_instruction() {
+        // ei, nop
+        let mut cpu = setup_pending_serial(&[0xFB, 0x00]);
+
+        assert!(cpu.step().unwrap());
+        assert!(!cpu.interrupts_enabled);
+        assert!(cpu.step().unwrap());
+        assert_eq!(cpu.pc, 0xC002);
+        assert!(cpu.interrupts_enabled);
+        assert_eq!(cpu.cycles(), 8);
+
+        assert!(cpu.step().unwrap());
+        assert_eq!(cpu.pc, Interrupt::Serial.vector());
+        assert_eq!(cpu.read_memory(0xCFEE).unwrap(), 0x02);
+        assert_eq!(cpu.cycles(), 28);
+    }
+
+    #[test]
+    fn test_ei_di_opens_no_window() {
+        // ei, di, nop
+        let mut cpu = setup_pending_serial(&[0xFB, 0xF3, 0x00]);
+
+        for _ in 0..3 {
+            assert!(cpu.step().unwrap());
+        }
+        assert_eq!(cpu.pc, 0xC003);
+        assert_eq!(cpu.sp, 0xCFF0);
+        assert!(!cpu.interrupts_enabled);
+        assert_eq!(cpu.cycles(), 12);
+    }
+
+    #[test]
+    fn test_ei_before_prefixed_instruction() {
+        // ei, swap a, nop
+        let mut cpu = setup_pending_serial(&[0xFB, 0xCB, 0x37, 0x00]);
+
+        assert!(cpu.step().unwrap());
+        assert!(cpu.step().unwrap());
+        assert_eq!(cpu.pc, 0xC003);
+        assert!(cpu.interrupts_enabled);
+        assert!(cpu.step().unwrap());
+        assert_eq!(cpu.pc, Interrupt::Serial.vector());
+    }
+
+    #[test]
+    fn test_illegal_opcode_locks_up() {
+        let mut cpu = setup();
+        cpu.pc = 0xC000;
+        cpu.write_to_memory(0xC000, 0xD3);
+        cpu.write_to_memory(0xFFFF, Interrupt::Serial.bit());
+        cpu.write_to_memory(0xFF0F, Interrupt::Serial.bit());
+        cpu.interrupts_enabled = false;
+
+        let lock_up = LockUp {
+            pc: 0xC000,
+            opcode: 0xD3,
+        };
+        assert_eq!(
+            cpu.run_until(RunUntil::Stopped),
+            StopReason::LockedUp(lock_up)
+        );
+
+        // Time keeps passing, but nothing is executed and interrupts are ignored
+        cpu.interrupts_enabled = true;
+        assert_eq!(
+            cpu.run_until(RunUntil::Cycles(100)),
+            StopReason::CyclesElapsed
+        );
+        assert_eq!(cpu.lock_up(), Some(lock_up));
+        assert_eq!(cpu.pc, 0xC000);
+        assert_eq!(cpu.cycles(), 104);
+        assert!(cpu.fault().is_none());
+    }
+
+    #[test]
+    fn test_unmapped_read_is_bus_fault() {
+        let mut cpu = setup();
+        cpu.pc = 0x8000;
+
+        assert_eq!(cpu.tick(), Err(CpuError::BusFault(0x8000)));
+        assert_eq!(
+            cpu.run_until(RunUntil::Stopped),
+            StopReason::Faulted(CpuFault {
+                pc: 0x8000,
+                error: CpuError::BusFault(0x8000),
+            })
+        );
+    }
+
+    #[test]
+    fn test_halt_wakes_up_on_pending_interrupt() {
+        let mut cpu = setup();
+        cpu.pc = 0xC000;
+        cpu.interrupts_enabled = false;
+        cpu.write_to_memory(0xC000, 0x76);
+
+        assert!(cpu.step().unwrap());
+        assert!(cpu.is_halted);
+        assert!(cpu.step().unwrap());
+        assert!(cpu.is_halted);
+        assert_eq!(cpu.pc, 0xC001);
+        assert_eq!(cpu.cycles(), 8);
+
+        // Interrupts are disabled, so execution resumes after the HALT
+        cpu.write_to_memory(0xFFFF, Interrupt::Timer.bit());
+        cpu.write_to_memory(0xFF0F, Interrupt::Timer.bit());
+        assert!(cpu.step().unwrap());
+        assert!(!cpu.is_halted);
+        assert_eq!(cpu.pc, 0xC002);
+    }
+
+    // Counts M-cycles and records the M-cycle of every access to it. Reads return the count.
+    struct Stopwatch {
+        m_cycles: u64,
+        accesses: RefCell<Vec<Access>>,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Access {
+        Read(u64, u16),
+        Write(u64, u16),
+    }
+
+    impl Readable<u16, u8> for Stopwatch {
+        fn read(&self, address: u16) -> Result<u8, Box<dyn std::error::Error>> {
+            self.accesses
+                .borrow_mut()
+                .push(Access::Read(self.m_cycles, address));
+            Ok(self.m_cycles as u8)
+        }
+    }
+
+    impl Writeable<u16, u8, u16> for Stopwatch {
+        fn write(&mut self, address: u16, _data: u8) -> Result<(), Box<dyn std::error::Error>> {
+            self.accesses
+                .borrow_mut()
+                .push(Access::Write(self.m_cycles, address));
+            Ok(())
+        }
+
+        fn write_16(&mut self, address: u16, data: u16) -> Result<(), Box<dyn std::error::Error>> {
+            self.write(address, data as u8)?;
+            self.write(address.wrapping_add(1), (data >> 8) as u8)
+        }
+    }
+
+    impl Addressable<u16> for Stopwatch {
+        fn assign_address_range(&mut self, _range: RangeInclusive<usize>) {}
+
+        fn in_range(&self, address: u16) -> bool {
+            (0x8000..=0x80FF).contains(&address)
+        }
+
+        fn address_ranges(&self) -> Vec<RangeInclusive<usize>> {
+            vec![0x8000..=0x80FF]
+        }
+    }
+
+    impl ReadDevice<u16, u8> for Stopwatch {}
+
+    impl WriteDevice<u16, u8, u16> for Stopwatch {}
+
+    impl Clocked for Stopwatch {
+        fn tick(&mut self, cycles: u32) {
+            self.m_cycles += cycles as u64 / 4;
+        }
+    }
+
+    // A cpu with a stopwatch at 0x8000, HL pointing at it and the stack right above it
+    fn setup_stopwatch() -> (Cpu, Rc<RefCell<Stopwatch>>) {
+        let mut cpu = setup();
+        let stopwatch = Rc::new(RefCell::new(Stopwatch {
+            m_cycles: 0,
+            accesses: RefCell::new(vec![]),
+        }));
+        cpu.bus.borrow_mut().connect_readable(stopwatch.clone());
+        cpu.bus.borrow_mut().connect_writeable(stopwatch.clone());
+        cpu.connect_clocked(stopwatch.clone());
+
+        cpu.pc = 0xC000;
+        cpu.sp = 0x8002;
+        cpu.registers
+            .set_combined_register(Target::HL, 0x8000)
+            .unwrap();
+        cpu.registers.set_flag(Flag::Zero, false);
+        (cpu, stopwatch)
+    }
+
+    // The M-cycles count from 1, the fetch of the opcode
+    #[rstest]
+    #[case::ld_a_hl(&[0x7E], 8, &[Access::Read(2, 0x8000)])]
+    #[case::ld_a_a16(&[0xFA, 0x00, 0x80], 16, &[Access::Read(4, 0x8000)])]
+    #[case::ld_a16_a(&[0xEA, 0x00, 0x80], 16, &[Access::Write(4, 0x8000)])]
+    #[case::inc_hl(&[0x34], 12, &[Access::Read(2, 0x8000), Access::Write(3, 0x8000)])]
+    #[case::rlc_hl(&[0xCB, 0x06], 16, &[Access::Read(3, 0x8000), Access::Write(4, 0x8000)])]
+    #[case::bit_hl(&[0xCB, 0x46], 12, &[Access::Read(3, 0x8000)])]
+    #[case::ld_a16_sp(&[0x08, 0x00, 0x80], 20, &[Access::Write(4, 0x8000), Access::Write(5, 0x8001)])]
+    #[case::push(&[0xC5], 16, &[Access::Write(3, 0x8001), Access::Write(4, 0x8000)])]
+    #[case::pop(&[0xC1], 12, &[Access::Read(2, 0x8002), Access::Read(3, 0x8003)])]
+    #[case::call(&[0xCD, 0x00, 0xC1], 24, &[Access::Write(5, 0x8001), Access::Write(6, 0x8000)])]
+    #[case::call_nz(&[0xC4, 0x00, 0xC1], 24, &[Access::Write(5, 0x8001), Access::Write(6, 0x8000)])]
+    #[case::rst(&[0xC7], 16, &[Access::Write(3, 0x8001), Access::Write(4, 0x8000)])]
+    #[case::ret(&[0xC9], 16, &[Access::Read(2, 0x8002), Access::Read(3, 0x8003)])]
+    #[case::ret_nz(&[0xC0], 20, &[Access::Read(3, 0x8002), Access::Read(4, 0x8003)])]
+    #[case::ret_z(&[0xC8], 8, &[])]
+    #[case::add_sp(&[0xE8, 0x05], 16, &[])]
+    fn test_memory_access_timing(
+        #[case] bytes: &[u8],
+        #[case] expected_cycles: u64,
+        #[case] expected: &[Access],
+    ) {
+        let (mut cpu, stopwatch) = setup_stopwatch();
+        for (offset, byte) in bytes.iter().enumerate() {
+            cpu.write_to_memory(0xC000 + offset as u16, *byte);
+        }
+
+        assert!(cpu.step().unwrap());
+        assert_eq!(cpu.cycles(), expected_cycles);
+        assert_eq!(stopwatch.borrow().m_cycles, expected_cycles / 4);
+        assert_eq!(*stopwatch.borrow().accesses.borrow(), expected);
+    }
+
+    #[test]
+    fn test_interrupt_dispatch_timing() {
+        let (mut cpu, stopwatch) = setup_stopwatch();
+        cpu.write_to_memory(0xFFFF, Interrupt::VBlank.bit());
+        cpu.write_to_memory(0xFF0F, Interrupt::VBlank.bit());
+
+        assert!(cpu.step().unwrap());
+        assert_eq!(cpu.pc, Interrupt::VBlank.vector());
+        assert_eq!(cpu.cycles(), 20);
+        assert_eq!(stopwatch.borrow().m_cycles, 5);
+        assert_eq!(
+            *stopwatch.borrow().accesses.borrow(),
+            [Access::Write(3, 0x8001), Access::Write(4, 0x8000)]
+        );
+    }
+
+    // A read sees the value the stopwatch has at the end of the M-cycle it is made in
+    #[test]
+    fn test_read_sees_hardware_mid_instruction() {
+        let (mut cpu, _stopwatch) = setup_stopwatch();
+        cpu.write_to_memory(0xC000, 0xFA);
+        cpu.write_to_memory(0xC001, 0x00);
+        cpu.write_to_memory(0xC002, 0x80);
+
+        assert!(cpu.step().unwrap());
+        assert_eq!(cpu.registers.a, 4);
+    }
+
+    // State an instruction is expected to leave behind, everything else is not checked
+    #[derive(Debug)]
+    enum Expect {
+        Reg(Target, u8),
+        Pair(Target, u16),
+        Mem(u16, u8),
+        // Little endian, like the stack
+        Mem16(u16, u16),
+        Sp(u16),
+        Ime(bool),
+        Halted,
+        // Z, N, H and C as '1' or '0'
+        Flags(&'static str),
+    }
+
+    use Expect::*;
+
+    // Every opcode runs from the same state:
+    // A = 0x81, only the carry flag set, (BC) = 0x11, (DE) = 0x22, (HL) = 0x5A,
+    // (a16) = 0x7766 and 0x1234 on top of the stack.
+    // Immediates are 0x05 for d8/r8, 0x85 for a8 and 0xC550 for d16/a16.
+    #[rstest]
+    #[case(&[0x00], 0xC001, 4, &[Flags("0001")])]
+    #[case(&[0x01, 0x50, 0xC5], 0xC003, 12, &[Pair(Target::BC, 0xC550), Flags("0001")])]
+    #[case(&[0x02], 0xC001, 8, &[Mem(0xC290, 0x81), Flags("0001")])]
+    #[case(&[0x03], 0xC001, 8, &[Reg(Target::C, 0x91), Flags("0001")])]
+    #[case(&[0x04], 0xC001, 4, &[Reg(Target::B, 0xC3), Flags("0001")])]
+    #[case(&[0x05], 0xC001, 4, &[Reg(Target::B, 0xC1), Flags("0101")])]
+    #[case(&[0x06, 0x05], 0xC002, 8, &[Reg(Target::B, 0x05), Flags("0001")])]
+    #[case(&[0x07], 0xC001, 4, &[Reg(Target::A, 0x03), Flags("0001")])]
+    #[case(&[0x08, 0x50, 0xC5], 0xC003, 20, &[Mem16(0xC550, 0xCFF0), Flags("0001")])]
+    #[case(&[0x09], 0xC001, 8, &[Pair(Target::HL, 0x86D0), Flags("0001")])]
+    #[case(&[0x0A], 0xC001, 8, &[Reg(Target::A, 0x11), Flags("0001")])]
+    #[case(&[0x0B], 0xC001, 8, &[Reg(Target::C, 0x8F), Flags("0001")])]
+    #[case(&[0x0C], 0xC001, 4, &[Reg(Target::C, 0x91), Flags("0001")])]
+    #[case(&[0x0D], 0xC001, 4, &[Reg(Target::C, 0x8F), Flags("0111")])]
+    #[case(&[0x0E, 0x05], 0xC002, 8, &[Reg(Target::C, 0x05), Flags("0001")])]
+    #[case(&[0x0F], 0xC001, 4, &[Reg(Target::A, 0xC0), Flags("0001")])]
+    #[case(&[0x10, 0x05], 0xC002, 4, &[Flags("0001")])]
+    #[case(&[0x11, 0x50, 0xC5], 0xC003, 12, &[Pair(Target::DE, 0xC550), Flags("0001")])]
+    #[case(&[0x12], 0xC001, 8, &[Mem(0xC330, 0x81), Flags("0001")])]
+    #[case(&[0x13], 0xC001, 8, &[Reg(Target::E, 0x31), Flags("0001")])]
+    #[case(&[0x14], 0xC001, 4, &[Reg(Target::D, 0xC4), Flags("0001")])]
+    #[case(&[0x15], 0xC001, 4, &[Reg(Target::D, 0xC2), Flags("0101")])]
+    #[case(&[0x16, 0x05], 0xC002, 8, &[Reg(Target::D, 0x05), Flags("0001")])]
+    #[case(&[0x17], 0xC001, 4, &[Reg(Target::A, 0x03), Flags("0001")])]
+    #[case(&[0x18, 0x05], 0xC007, 12, &[Flags("0001")])]
+    #[case(&[0x19], 0xC001, 8, &[Pair(Target::HL, 0x8770), Flags("0001")])]
+    #[case(&[0x1A], 0xC001, 8, &[Reg(Target::A, 0x22), Flags("0001")])]
+    #[case(&[0x1B], 0xC001, 8, &[Reg(Target::E, 0x2F), Flags("0001")])]
+    #[case(&[0x1C], 0xC001, 4, &[Reg(Target::E, 0x31), Flags("0001")])]
+    #[case(&[0x1D], 0xC001, 4, &[Reg(Target::E, 0x2F), Flags("0111")])]
+    #[case(&[0x1E, 0x05], 0xC002, 8, &[Reg(Target::E, 0x05), Flags("0001")])]
+    #[case(&[0x1F], 0xC001, 4, &[Reg(Target::A, 0xC0), Flags("0001")])]
+    #[case(&[0x20, 0x05], 0xC007, 12, &[Flags("0001")])]
+    #[case(&[0x21, 0x50, 0xC5], 0xC003, 12, &[Pair(Target::HL, 0xC550), Flags("0001")])]
+    #[case(&[0x22], 0xC001, 8, &[Reg(Target::L, 0x41), Mem(0xC440, 0x81), Flags("0001")])]
+    #[case(&[0x23], 0xC001, 8, &[Reg(Target::L, 0x41), Flags("0001")])]
+    #[case(&[0x24], 0xC001, 4, &[Reg(Target::H, 0xC5), Flags("0001")])]
+    #[case(&[0x25], 0xC001, 4, &[Reg(Target::H, 0xC3), Flags("0101")])]
+    #[case(&[0x26, 0x05], 0xC002, 8, &[Reg(Target::H, 0x05), Flags("0001")])]
+    #[case(&[0x27], 0xC001, 4, &[Reg(Target::A, 0xE1), Flags("0001")])]
+    #[case(&[0x28, 0x05], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0x29], 0xC001, 8, &[Pair(Target::HL, 0x8880), Flags("0001")])]
+    #[case(&[0x2A], 0xC001, 8, &[Reg(Target::A, 0x5A), Reg(Target::L, 0x41), Flags("0001")])]
+    #[case(&[0x2B], 0xC001, 8, &[Reg(Target::L, 0x3F), Flags("0001")])]
+    #[case(&[0x2C], 0xC001, 4, &[Reg(Target::L, 0x41), Flags("0001")])]
+    #[case(&[0x2D], 0xC001, 4, &[Reg(Target::L, 0x3F), Flags("0111")])]
+    #[case(&[0x2E, 0x05], 0xC002, 8, &[Reg(Target::L, 0x05), Flags("0001")])]
+    #[case(&[0x2F], 0xC001, 4, &[Reg(Target::A, 0x7E), Flags("0111")])]
+    #[case(&[0x30, 0x05], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0x31, 0x50, 0xC5], 0xC003, 12, &[Sp(0xC550), Flags("0001")])]
+    #[case(&[0x32], 0xC001, 8, &[Reg(Target::L, 0x3F), Mem(0xC440, 0x81), Flags("0001")])]
+    #[case(&[0x33], 0xC001, 8, &[Sp(0xCFF1), Flags("0001")])]
+    #[case(&[0x34], 0xC001, 12, &[Mem(0xC440, 0x5B), Flags("0001")])]
+    #[case(&[0x35], 0xC001, 12, &[Mem(0xC440, 0x59), Flags("0101")])]
+    #[case(&[0x36, 0x05], 0xC002, 12, &[Mem(0xC440, 0x05), Flags("0001")])]
+    #[case(&[0x37], 0xC001, 4, &[Flags("0001")])]
+    #[case(&[0x38, 0x05], 0xC007, 12, &[Flags("0001")])]
+    #[case(&[0x39], 0xC001, 8, &[Pair(Target::HL, 0x9430), Flags("0011")])]
+    #[case(&[0x3A], 0xC001, 8, &[Reg(Target::A, 0x5A), Reg(Target::L, 0x3F), Flags("0001")])]
+    #[case(&[0x3B], 0xC001, 8, &[Sp(0xCFEF), Flags("0001")])]
+    #[case(&[0x3C], 0xC001, 4, &[Reg(Target::A, 0x82), Flags("0001")])]
+    #[case(&[0x3D], 0xC001, 4, &[Reg(Target::A, 0x80), Flags("0101")])]
+    #[case(&[0x3E, 0x05], 0xC002, 8, &[Reg(Target::A, 0x05), Flags("0001")])]
+    #[case(&[0x3F], 0xC001, 4, &[Flags("0000")])]
+    #[case(&[0x40], 0xC001, 4, &[Flags("0001")])]
+    #[case(&[0x41], 0xC001, 4, &[Reg(Target::B, 0x90), Flags("0001")])]
+    #[case(&[0x42], 0xC001, 4, &[Reg(Target::B, 0xC3), Flags("0001")])]
+    #[case(&[0x43], 0xC001, 4, &[Reg(Target::B, 0x30), Flags("0001")])]
+    #[case(&[0x44], 0xC001, 4, &[Reg(Target::B, 0xC4), Flags("0001")])]
+    #[case(&[0x45], 0xC001, 4, &[Reg(Target::B, 0x40), Flags("0001")])]
+    #[case(&[0x46], 0xC001, 8, &[Reg(Target::B, 0x5A), Flags("0001")])]
+    #[case(&[0x47], 0xC001, 4, &[Reg(Target::B, 0x81), Flags("0001")])]
+    #[case(&[0x48], 0xC001, 4, &[Reg(Target::C, 0xC2), Flags("0001")])]
+    #[case(&[0x49], 0xC001, 4, &[Flags("0001")])]
+    #[case(&[0x4A], 0xC001, 4, &[Reg(Target::C, 0xC3), Flags("0001")])]
+    #[case(&[0x4B], 0xC001, 4, &[Reg(Target::C, 0x30), Flags("0001")])]
+    #[case(&[0x4C], 0xC001, 4, &[Reg(Target::C, 0xC4), Flags("0001")])]
+    #[case(&[0x4D], 0xC001, 4, &[Reg(Target::C, 0x40), Flags("0001")])]
+    #[case(&[0x4E], 0xC001, 8, &[Reg(Target::C, 0x5A), Flags("0001")])]
+    #[case(&[0x4F], 0xC001, 4, &[Reg(Target::C, 0x81), Flags("0001")])]
+    #[case(&[0x50], 0xC001, 4, &[Reg(Target::D, 0xC2), Flags("0001")])]
+    #[case(&[0x51], 0xC001, 4, &[Reg(Target::D, 0x90), Flags("0001")])]
+    #[case(&[0x52], 0xC001, 4, &[Flags("0001")])]
+    #[case(&[0x53], 0xC001, 4, &[Reg(Target::D, 0x30), Flags("0001")])]
+    #[case(&[0x54], 0xC001, 4, &[Reg(Target::D, 0xC4), Flags("0001")])]
+    #[case(&[0x55], 0xC001, 4, &[Reg(Target::D, 0x40), Flags("0001")])]
+    #[case(&[0x56], 0xC001, 8, &[Reg(Target::D, 0x5A), Flags("0001")])]
+    #[case(&[0x57], 0xC001, 4, &[Reg(Target::D, 0x81), Flags("0001")])]
+    #[case(&[0x58], 0xC001, 4, &[Reg(Target::E, 0xC2), Flags("0001")])]
+    #[case(&[0x59], 0xC001, 4, &[Reg(Target::E, 0x90), Flags("0001")])]
+    #[case(&[0x5A], 0xC001, 4, &[Reg(Target::E, 0xC3), Flags("0001")])]
+    #[case(&[0x5B], 0xC001, 4, &[Flags("0001")])]
+    #[case(&[0x5C], 0xC001, 4, &[Reg(Target::E, 0xC4), Flags("0001")])]
+    #[case(&[0x5D], 0xC001, 4, &[Reg(Target::E, 0x40), Flags("0001")])]
+    #[case(&[0x5E], 0xC001, 8, &[Reg(Target::E, 0x5A), Flags("0001")])]
+    #[case(&[0x5F], 0xC001, 4, &[Reg(Target::E, 0x81), Flags("0001")])]
+    #[case(&[0x60], 0xC001, 4, &[Reg(Target::H, 0xC2), Flags("0001")])]
+    #[case(&[0x61], 0xC001, 4, &[Reg(Target::H, 0x90), Flags("0001")])]
+    #[case(&[0x62], 0xC001, 4, &[Reg(Target::H, 0xC3), Flags("0001")])]
+    #[case(&[0x63], 0xC001, 4, &[Reg(Target::H, 0x30), Flags("0001")])]
+    #[case(&[0x64], 0xC001, 4, &[Flags("0001")])]
+    #[case(&[0x65], 0xC001, 4, &[Reg(Target::H, 0x40), Flags("0001")])]
+    #[case(&[0x66], 0xC001, 8, &[Reg(Target::H, 0x5A), Flags("0001")])]
+    #[case(&[0x67], 0xC001, 4, &[Reg(Target::H, 0x81), Flags("0001")])]
+    #[case(&[0x68], 0xC001, 4, &[Reg(Target::L, 0xC2), Flags("0001")])]
+    #[case(&[0x69], 0xC001, 4, &[Reg(Target::L, 0x90), Flags("0001")])]
+    #[case(&[0x6A], 0xC001, 4, &[Reg(Target::L, 0xC3), Flags("0001")])]
+    #[case(&[0x6B], 0xC001, 4, &[Reg(Target::L, 0x30), Flags("0001")])]
+    #[case(&[0x6C], 0xC001, 4, &[Reg(Target::L, 0xC4), Flags("0001")])]
+    #[case(&[0x6D], 0xC001, 4, &[Flags("0001")])]
+    #[case(&[0x6E], 0xC001, 8, &[Reg(Target::L, 0x5A), Flags("0001")])]
+    #[case(&[0x6F], 0xC001, 4, &[Reg(Target::L, 0x81), Flags("0001")])]
+    #[case(&[0x70], 0xC001, 8, &[Mem(0xC440, 0xC2), Flags("0001")])]
+    #[case(&[0x71], 0xC001, 8, &[Mem(0xC440, 0x90), Flags("0001")])]
+    #[case(&[0x72], 0xC001, 8, &[Mem(0xC440, 0xC3), Flags("0001")])]
+    #[case(&[0x73], 0xC001, 8, &[Mem(0xC440, 0x30), Flags("0001")])]
+    #[case(&[0x74], 0xC001, 8, &[Mem(0xC440, 0xC4), Flags("0001")])]
+    #[case(&[0x75], 0xC001, 8, &[Mem(0xC440, 0x40), Flags("0001")])]
+    #[case(&[0x76], 0xC001, 4, &[Halted, Flags("0001")])]
+    #[case(&[0x77], 0xC001, 8, &[Mem(0xC440, 0x81), Flags("0001")])]
+    #[case(&[0x78], 0xC001, 4, &[Reg(Target::A, 0xC2), Flags("0001")])]
+    #[case(&[0x79], 0xC001, 4, &[Reg(Target::A, 0x90), Flags("0001")])]
+    #[case(&[0x7A], 0xC001, 4, &[Reg(Target::A, 0xC3), Flags("0001")])]
+    #[case(&[0x7B], 0xC001, 4, &[Reg(Target::A, 0x30), Flags("0001")])]
+    #[case(&[0x7C], 0xC001, 4, &[Reg(Target::A, 0xC4), Flags("0001")])]
+    #[case(&[0x7D], 0xC001, 4, &[Reg(Target::A, 0x40), Flags("0001")])]
+    #[case(&[0x7E], 0xC001, 8, &[Reg(Target::A, 0x5A), Flags("0001")])]
+    #[case(&[0x7F], 0xC001, 4, &[Flags("0001")])]
+    #[case(&[0x80], 0xC001, 4, &[Reg(Target::A, 0x43), Flags("0001")])]
+    #[case(&[0x81], 0xC001, 4, &[Reg(Target::A, 0x11), Flags("0001")])]
+    #[case(&[0x82], 0xC001, 4, &[Reg(Target::A, 0x44), Flags("0001")])]
+    #[case(&[0x83], 0xC001, 4, &[Reg(Target::A, 0xB1), Flags("0000")])]
+    #[case(&[0x84], 0xC001, 4, &[Reg(Target::A, 0x45), Flags("0001")])]
+    #[case(&[0x85], 0xC001, 4, &[Reg(Target::A, 0xC1), Flags("0000")])]
+    #[case(&[0x86], 0xC001, 8, &[Reg(Target::A, 0xDB), Flags("0000")])]
+    #[case(&[0x87], 0xC001, 4, &[Reg(Target::A, 0x02), Flags("0001")])]
+    #[case(&[0x88], 0xC001, 4, &[Reg(Target::A, 0x44), Flags("0001")])]
+    #[case(&[0x89], 0xC001, 4, &[Reg(Target::A, 0x12), Flags("0001")])]
+    #[case(&[0x8A], 0xC001, 4, &[Reg(Target::A, 0x45), Flags("0001")])]
+    #[case(&[0x8B], 0xC001, 4, &[Reg(Target::A, 0xB2), Flags("0000")])]
+    #[case(&[0x8C], 0xC001, 4, &[Reg(Target::A, 0x46), Flags("0001")])]
+    #[case(&[0x8D], 0xC001, 4, &[Reg(Target::A, 0xC2), Flags("0000")])]
+    #[case(&[0x8E], 0xC001, 8, &[Reg(Target::A, 0xDC), Flags("0000")])]
+    #[case(&[0x8F], 0xC001, 4, &[Reg(Target::A, 0x03), Flags("0001")])]
+    #[case(&[0x90], 0xC001, 4, &[Reg(Target::A, 0xBF), Flags("0111")])]
+    #[case(&[0x91], 0xC001, 4, &[Reg(Target::A, 0xF1), Flags("0101")])]
+    #[case(&[0x92], 0xC001, 4, &[Reg(Target::A, 0xBE), Flags("0111")])]
+    #[case(&[0x93], 0xC001, 4, &[Reg(Target::A, 0x51), Flags("0100")])]
+    #[case(&[0x94], 0xC001, 4, &[Reg(Target::A, 0xBD), Flags("0111")])]
+    #[case(&[0x95], 0xC001, 4, &[Reg(Target::A, 0x41), Flags("0100")])]
+    #[case(&[0x96], 0xC001, 8, &[Reg(Target::A, 0x27), Flags("0110")])]
+    #[case(&[0x97], 0xC001, 4, &[Reg(Target::A, 0x00), Flags("1100")])]
+    #[case(&[0x98], 0xC001, 4, &[Reg(Target::A, 0xBE), Flags("0111")])]
+    #[case(&[0x99], 0xC001, 4, &[Reg(Target::A, 0xF0), Flags("0101")])]
+    #[case(&[0x9A], 0xC001, 4, &[Reg(Target::A, 0xBD), Flags("0111")])]
+    #[case(&[0x9B], 0xC001, 4, &[Reg(Target::A, 0x50), Flags("0100")])]
+    #[case(&[0x9C], 0xC001, 4, &[Reg(Target::A, 0xBC), Flags("0111")])]
+    #[case(&[0x9D], 0xC001, 4, &[Reg(Target::A, 0x40), Flags("0100")])]
+    #[case(&[0x9E], 0xC001, 8, &[Reg(Target::A, 0x26), Flags("0110")])]
+    #[case(&[0x9F], 0xC001, 4, &[Reg(Target::A, 0xFF), Flags("0111")])]
+    #[case(&[0xA0], 0xC001, 4, &[Reg(Target::A, 0x80), Flags("0010")])]
+    #[case(&[0xA1], 0xC001, 4, &[Reg(Target::A, 0x80), Flags("0010")])]
+    #[case(&[0xA2], 0xC001, 4, &[Flags("0010")])]
+    #[case(&[0xA3], 0xC001, 4, &[Reg(Target::A, 0x00), Flags("1010")])]
+    #[case(&[0xA4], 0xC001, 4, &[Reg(Target::A, 0x80), Flags("0010")])]
+    #[case(&[0xA5], 0xC001, 4, &[Reg(Target::A, 0x00), Flags("1010")])]
+    #[case(&[0xA6], 0xC001, 8, &[Reg(Target::A, 0x00), Flags("1010")])]
+    #[case(&[0xA7], 0xC001, 4, &[Flags("0010")])]
+    #[case(&[0xA8], 0xC001, 4, &[Reg(Target::A, 0x43), Flags("0000")])]
+    #[case(&[0xA9], 0xC001, 4, &[Reg(Target::A, 0x11), Flags("0000")])]
+    #[case(&[0xAA], 0xC001, 4, &[Reg(Target::A, 0x42), Flags("0000")])]
+    #[case(&[0xAB], 0xC001, 4, &[Reg(Target::A, 0xB1), Flags("0000")])]
+    #[case(&[0xAC], 0xC001, 4, &[Reg(Target::A, 0x45), Flags("0000")])]
+    #[case(&[0xAD], 0xC001, 4, &[Reg(Target::A, 0xC1), Flags("0000")])]
+    #[case(&[0xAE], 0xC001, 8, &[Reg(Target::A, 0xDB), Flags("0000")])]
+    #[case(&[0xAF], 0xC001, 4, &[Reg(Target::A, 0x00), Flags("1000")])]
+    #[case(&[0xB0], 0xC001, 4, &[Reg(Target::A, 0xC3), Flags("0000")])]
+    #[case(&[0xB1], 0xC001, 4, &[Reg(Target::A, 0x91), Flags("0000")])]
+    #[case(&[0xB2], 0xC001, 4, &[Reg(Target::A, 0xC3), Flags("0000")])]
+    #[case(&[0xB3], 0xC001, 4, &[Reg(Target::A, 0xB1), Flags("0000")])]
+    #[case(&[0xB4], 0xC001, 4, &[Reg(Target::A, 0xC5), Flags("0000")])]
+    #[case(&[0xB5], 0xC001, 4, &[Reg(Target::A, 0xC1), Flags("0000")])]
+    #[case(&[0xB6], 0xC001, 8, &[Reg(Target::A, 0xDB), Flags("0000")])]
+    #[case(&[0xB7], 0xC001, 4, &[Flags("0000")])]
+    #[case(&[0xB8], 0xC001, 4, &[Flags("0111")])]
+    #[case(&[0xB9], 0xC001, 4, &[Flags("0101")])]
+    #[case(&[0xBA], 0xC001, 4, &[Flags("0111")])]
+    #[case(&[0xBB], 0xC001, 4, &[Flags("0100")])]
+    #[case(&[0xBC], 0xC001, 4, &[Flags("0111")])]
+    #[case(&[0xBD], 0xC001, 4, &[Flags("0100")])]
+    #[case(&[0xBE], 0xC001, 8, &[Flags("0110")])]
+    #[case(&[0xBF], 0xC001, 4, &[Flags("1100")])]
+    #[case(&[0xC0], 0x1234, 20, &[Sp(0xCFF2), Flags("0001")])]
+    #[case(&[0xC1], 0xC001, 12, &[Pair(Target::BC, 0x1234), Sp(0xCFF2), Flags("0001")])]
+    #[case(&[0xC2, 0x50, 0xC5], 0xC550, 16, &[Flags("0001")])]
+    #[case(&[0xC3, 0x50, 0xC5], 0xC550, 16, &[Flags("0001")])]
+    #[case(&[0xC4, 0x50, 0xC5], 0xC550, 24, &[Sp(0xCFEE), Mem16(0xCFEE, 0xC003), Flags("0001")])]
+    #[case(&[0xC5], 0xC001, 16, &[Sp(0xCFEE), Mem16(0xCFEE, 0xC290), Flags("0001")])]
+    #[case(&[0xC6, 0x05], 0xC002, 8, &[Reg(Target::A, 0x86), Flags("0000")])]
+    #[case(&[0xC7], 0x0000, 16, &[Sp(0xCFEE), Mem16(0xCFEE, 0xC001), Flags("0001")])]
+    #[case(&[0xC8], 0xC001, 8, &[Flags("0001")])]
+    #[case(&[0xC9], 0x1234, 16, &[Sp(0xCFF2), Flags("0001")])]
+    #[case(&[0xCA, 0x50, 0xC5], 0xC003, 12, &[Flags("0001")])]
+    #[case(&[0xCC, 0x50, 0xC5], 0xC003, 12, &[Flags("0001")])]
+    #[case(&[0xCD, 0x50, 0xC5], 0xC550, 24, &[Sp(0xCFEE), Mem16(0xCFEE, 0xC003), Flags("0001")])]
+    #[case(&[0xCE, 0x05], 0xC002, 8, &[Reg(Target::A, 0x87), Flags("0000")])]
+    #[case(&[0xCF], 0x0008, 16, &[Sp(0xCFEE), Mem16(0xCFEE, 0xC001), Flags("0001")])]
+    #[case(&[0xD0], 0xC001, 8, &[Flags("0001")])]
+    #[case(&[0xD1], 0xC001, 12, &[Pair(Target::DE, 0x1234), Sp(0xCFF2), Flags("0001")])]
+    #[case(&[0xD2, 0x50, 0xC5], 0xC003, 12, &[Flags("0001")])]
+    #[case(&[0xD4, 0x50, 0xC5], 0xC003, 12, &[Flags("0001")])]
+    #[case(&[0xD5], 0xC001, 16, &[Sp(0xCFEE), Mem16(0xCFEE, 0xC330), Flags("0001")])]
+    #[case(&[0xD6, 0x05], 0xC002, 8, &[Reg(Target::A, 0x7C), Flags("0110")])]
+    #[case(&[0xD7], 0x0010, 16, &[Sp(0xCFEE), Mem16(0xCFEE, 0xC001), Flags("0001")])]
+    #[case(&[0xD8], 0x1234, 20, &[Sp(0xCFF2), Flags("0001")])]
+    #[case(&[0xD9], 0x1234, 16, &[Sp(0xCFF2), Ime(true), Flags("0001")])]
+    #[case(&[0xDA, 0x50, 0xC5], 0xC550, 16, &[Flags("0001")])]
+    #[case(&[0xDC, 0x50, 0xC5], 0xC550, 24, &[Sp(0xCFEE), Mem16(0xCFEE, 0xC003), Flags("0001")])]
+    #[case(&[0xDE, 0x05], 0xC002, 8, &[Reg(Target::A, 0x7B), Flags("0110")])]
+    #[case(&[0xDF], 0x0018, 16, &[Sp(0xCFEE), Mem16(0xCFEE, 0xC001), Flags("0001")])]
+    #[case(&[0xE0, 0x85], 0xC002, 12, &[Mem(0xFF85, 0x81), Flags("0001")])]
+    #[case(&[0xE1], 0xC001, 12, &[Pair(Target::HL, 0x1234), Sp(0xCFF2), Flags("0001")])]
+    #[case(&[0xE2], 0xC001, 8, &[Mem(0xFF90, 0x81), Flags("0001")])]
+    #[case(&[0xE5], 0xC001, 16, &[Sp(0xCFEE), Mem16(0xCFEE, 0xC440), Flags("0001")])]
+    #[case(&[0xE6, 0x05], 0xC002, 8, &[Reg(Target::A, 0x01), Flags("0010")])]
+    #[case(&[0xE7], 0x0020, 16, &[Sp(0xCFEE), Mem16(0xCFEE, 0xC001), Flags("0001")])]
+    #[case(&[0xE8, 0x05], 0xC002, 16, &[Sp(0xCFF5), Flags("0000")])]
+    #[case(&[0xE9], 0xC440, 4, &[Flags("0001")])]
+    #[case(&[0xEA, 0x50, 0xC5], 0xC003, 16, &[Mem(0xC550, 0x81), Flags("0001")])]
+    #[case(&[0xEE, 0x05], 0xC002, 8, &[Reg(Target::A, 0x84), Flags("0000")])]
+    #[case(&[0xEF], 0x0028, 16, &[Sp(0xCFEE), Mem16(0xCFEE, 0xC001), Flags("0001")])]
+    #[case(&[0xF0, 0x85], 0xC002, 12, &[Reg(Target::A, 0x00), Flags("0001")])]
+    #[case(&[0xF1], 0xC001, 12, &[Reg(Target::A, 0x12), Sp(0xCFF2), Flags("0011")])]
+    #[case(&[0xF2], 0xC001, 8, &[Reg(Target::A, 0x00), Flags("0001")])]
+    #[case(&[0xF3], 0xC001, 4, &[Flags("0001")])]
+    #[case(&[0xF5], 0xC001, 16, &[Sp(0xCFEE), Mem16(0xCFEE, 0x8110), Flags("0001")])]
+    #[case(&[0xF6, 0x05], 0xC002, 8, &[Reg(Target::A, 0x85), Flags("0000")])]
+    #[case(&[0xF7], 0x0030, 16, &[Sp(0xCFEE), Mem16(0xCFEE, 0xC001), Flags("0001")])]
+    #[case(&[0xF8, 0x05], 0xC002, 12, &[Pair(Target::HL, 0xCFF5), Flags("0000")])]
+    #[case(&[0xF9], 0xC001, 8, &[Sp(0xC440), Flags("0001")])]
+    #[case(&[0xFA, 0x50, 0xC5], 0xC003, 16, &[Reg(Target::A, 0x66), Flags("0001")])]
+    // EI only takes effect after the next instruction
+    #[case(&[0xFB], 0xC001, 4, &[Ime(false), Flags("0001")])]
+    #[case(&[0xFE, 0x05], 0xC002, 8, &[Flags("0110")])]
+    #[case(&[0xFF], 0x0038, 16, &[Sp(0xCFEE), Mem16(0xCFEE, 0xC001), Flags("0001")])]
+    #[case(&[0xCB, 0x00], 0xC002, 8, &[Reg(Target::B, 0x85), Flags("0001")])]
+    #[case(&[0xCB, 0x01], 0xC002, 8, &[Reg(Target::C, 0x21), Flags("0001")])]
+    #[case(&[0xCB, 0x02], 0xC002, 8, &[Reg(Target::D, 0x87), Flags("0001")])]
+    #[case(&[0xCB, 0x03], 0xC002, 8, &[Reg(Target::E, 0x60), Flags("0000")])]
+    #[case(&[0xCB, 0x04], 0xC002, 8, &[Reg(Target::H, 0x89), Flags("0001")])]
+    #[case(&[0xCB, 0x05], 0xC002, 8, &[Reg(Target::L, 0x80), Flags("0000")])]
+    #[case(&[0xCB, 0x06], 0xC002, 16, &[Mem(0xC440, 0xB4), Flags("0000")])]
+    #[case(&[0xCB, 0x07], 0xC002, 8, &[Reg(Target::A, 0x03), Flags("0001")])]
+    #[case(&[0xCB, 0x08], 0xC002, 8, &[Reg(Target::B, 0x61), Flags("0000")])]
+    #[case(&[0xCB, 0x09], 0xC002, 8, &[Reg(Target::C, 0x48), Flags("0000")])]
+    #[case(&[0xCB, 0x0A], 0xC002, 8, &[Reg(Target::D, 0xE1), Flags("0001")])]
+    #[case(&[0xCB, 0x0B], 0xC002, 8, &[Reg(Target::E, 0x18), Flags("0000")])]
+    #[case(&[0xCB, 0x0C], 0xC002, 8, &[Reg(Target::H, 0x62), Flags("0000")])]
+    #[case(&[0xCB, 0x0D], 0xC002, 8, &[Reg(Target::L, 0x20), Flags("0000")])]
+    #[case(&[0xCB, 0x0E], 0xC002, 16, &[Mem(0xC440, 0x2D), Flags("0000")])]
+    #[case(&[0xCB, 0x0F], 0xC002, 8, &[Reg(Target::A, 0xC0), Flags("0001")])]
+    #[case(&[0xCB, 0x10], 0xC002, 8, &[Reg(Target::B, 0x85), Flags("0001")])]
+    #[case(&[0xCB, 0x11], 0xC002, 8, &[Reg(Target::C, 0x21), Flags("0001")])]
+    #[case(&[0xCB, 0x12], 0xC002, 8, &[Reg(Target::D, 0x87), Flags("0001")])]
+    #[case(&[0xCB, 0x13], 0xC002, 8, &[Reg(Target::E, 0x61), Flags("0000")])]
+    #[case(&[0xCB, 0x14], 0xC002, 8, &[Reg(Target::H, 0x89), Flags("0001")])]
+    #[case(&[0xCB, 0x15], 0xC002, 8, &[Reg(Target::L, 0x81), Flags("0000")])]
+    #[case(&[0xCB, 0x16], 0xC002, 16, &[Mem(0xC440, 0xB5), Flags("0000")])]
+    #[case(&[0xCB, 0x17], 0xC002, 8, &[Reg(Target::A, 0x03), Flags("0001")])]
+    #[case(&[0xCB, 0x18], 0xC002, 8, &[Reg(Target::B, 0xE1), Flags("0000")])]
+    #[case(&[0xCB, 0x19], 0xC002, 8, &[Reg(Target::C, 0xC8), Flags("0000")])]
+    #[case(&[0xCB, 0x1A], 0xC002, 8, &[Reg(Target::D, 0xE1), Flags("0001")])]
+    #[case(&[0xCB, 0x1B], 0xC002, 8, &[Reg(Target::E, 0x98), Flags("0000")])]
+    #[case(&[0xCB, 0x1C], 0xC002, 8, &[Reg(Target::H, 0xE2), Flags("0000")])]
+    #[case(&[0xCB, 0x1D], 0xC002, 8, &[Reg(Target::L, 0xA0), Flags("0000")])]
+    #[case(&[0xCB, 0x1E], 0xC002, 16, &[Mem(0xC440, 0xAD), Flags("0000")])]
+    #[case(&[0xCB, 0x1F], 0xC002, 8, &[Reg(Target::A, 0xC0), Flags("0001")])]
+    #[case(&[0xCB, 0x20], 0xC002, 8, &[Reg(Target::B, 0x84), Flags("0001")])]
+    #[case(&[0xCB, 0x21], 0xC002, 8, &[Reg(Target::C, 0x20), Flags("0001")])]
+    #[case(&[0xCB, 0x22], 0xC002, 8, &[Reg(Target::D, 0x86), Flags("0001")])]
+    #[case(&[0xCB, 0x23], 0xC002, 8, &[Reg(Target::E, 0x60), Flags("0000")])]
+    #[case(&[0xCB, 0x24], 0xC002, 8, &[Reg(Target::H, 0x88), Flags("0001")])]
+    #[case(&[0xCB, 0x25], 0xC002, 8, &[Reg(Target::L, 0x80), Flags("0000")])]
+    #[case(&[0xCB, 0x26], 0xC002, 16, &[Mem(0xC440, 0xB4), Flags("0000")])]
+    #[case(&[0xCB, 0x27], 0xC002, 8, &[Reg(Target::A, 0x02), Flags("0001")])]
+    #[case(&[0xCB, 0x28], 0xC002, 8, &[Reg(Target::B, 0xE1), Flags("0000")])]
+    #[case(&[0xCB, 0x29], 0xC002, 8, &[Reg(Target::C, 0xC8), Flags("0000")])]
+    #[case(&[0xCB, 0x2A], 0xC002, 8, &[Reg(Target::D, 0xE1), Flags("0001")])]
+    #[case(&[0xCB, 0x2B], 0xC002, 8, &[Reg(Target::E, 0x18), Flags("0000")])]
+    #[case(&[0xCB, 0x2C], 0xC002, 8, &[Reg(Target::H, 0xE2), Flags("0000")])]
+    #[case(&[0xCB, 0x2D], 0xC002, 8, &[Reg(Target::L, 0x20), Flags("0000")])]
+    #[case(&[0xCB, 0x2E], 0xC002, 16, &[Mem(0xC440, 0x2D), Flags("0000")])]
+    #[case(&[0xCB, 0x2F], 0xC002, 8, &[Reg(Target::A, 0xC0), Flags("0001")])]
+    #[case(&[0xCB, 0x30], 0xC002, 8, &[Reg(Target::B, 0x2C), Flags("0000")])]
+    #[case(&[0xCB, 0x31], 0xC002, 8, &[Reg(Target::C, 0x09), Flags("0000")])]
+    #[case(&[0xCB, 0x32], 0xC002, 8, &[Reg(Target::D, 0x3C), Flags("0000")])]
+    #[case(&[0xCB, 0x33], 0xC002, 8, &[Reg(Target::E, 0x03), Flags("0000")])]
+    #[case(&[0xCB, 0x34], 0xC002, 8, &[Reg(Target::H, 0x4C), Flags("0000")])]
+    #[case(&[0xCB, 0x35], 0xC002, 8, &[Reg(Target::L, 0x04), Flags("0000")])]
+    #[case(&[0xCB, 0x36], 0xC002, 16, &[Mem(0xC440, 0xA5), Flags("0000")])]
+    #[case(&[0xCB, 0x37], 0xC002, 8, &[Reg(Target::A, 0x18), Flags("0000")])]
+    #[case(&[0xCB, 0x38], 0xC002, 8, &[Reg(Target::B, 0x61), Flags("0000")])]
+    #[case(&[0xCB, 0x39], 0xC002, 8, &[Reg(Target::C, 0x48), Flags("0000")])]
+    #[case(&[0xCB, 0x3A], 0xC002, 8, &[Reg(Target::D, 0x61), Flags("0001")])]
+    #[case(&[0xCB, 0x3B], 0xC002, 8, &[Reg(Target::E, 0x18), Flags("0000")])]
+    #[case(&[0xCB, 0x3C], 0xC002, 8, &[Reg(Target::H, 0x62), Flags("0000")])]
+    #[case(&[0xCB, 0x3D], 0xC002, 8, &[Reg(Target::L, 0x20), Flags("0000")])]
+    #[case(&[0xCB, 0x3E], 0xC002, 16, &[Mem(0xC440, 0x2D), Flags("0000")])]
+    #[case(&[0xCB, 0x3F], 0xC002, 8, &[Reg(Target::A, 0x40), Flags("0001")])]
+    #[case(&[0xCB, 0x40], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x41], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x42], 0xC002, 8, &[Flags("0011")])]
+    #[case(&[0xCB, 0x43], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x44], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x45], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x46], 0xC002, 12, &[Flags("1011")])]
+    #[case(&[0xCB, 0x47], 0xC002, 8, &[Flags("0011")])]
+    #[case(&[0xCB, 0x48], 0xC002, 8, &[Flags("0011")])]
+    #[case(&[0xCB, 0x49], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x4A], 0xC002, 8, &[Flags("0011")])]
+    #[case(&[0xCB, 0x4B], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x4C], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x4D], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x4E], 0xC002, 12, &[Flags("0011")])]
+    #[case(&[0xCB, 0x4F], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x50], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x51], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x52], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x53], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x54], 0xC002, 8, &[Flags("0011")])]
+    #[case(&[0xCB, 0x55], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x56], 0xC002, 12, &[Flags("1011")])]
+    #[case(&[0xCB, 0x57], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x58], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x59], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x5A], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x5B], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x5C], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x5D], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x5E], 0xC002, 12, &[Flags("0011")])]
+    #[case(&[0xCB, 0x5F], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x60], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x61], 0xC002, 8, &[Flags("0011")])]
+    #[case(&[0xCB, 0x62], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x63], 0xC002, 8, &[Flags("0011")])]
+    #[case(&[0xCB, 0x64], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x65], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x66], 0xC002, 12, &[Flags("0011")])]
+    #[case(&[0xCB, 0x67], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x68], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x69], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x6A], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x6B], 0xC002, 8, &[Flags("0011")])]
+    #[case(&[0xCB, 0x6C], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x6D], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x6E], 0xC002, 12, &[Flags("1011")])]
+    #[case(&[0xCB, 0x6F], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x70], 0xC002, 8, &[Flags("0011")])]
+    #[case(&[0xCB, 0x71], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x72], 0xC002, 8, &[Flags("0011")])]
+    #[case(&[0xCB, 0x73], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x74], 0xC002, 8, &[Flags("0011")])]
+    #[case(&[0xCB, 0x75], 0xC002, 8, &[Flags("0011")])]
+    #[case(&[0xCB, 0x76], 0xC002, 12, &[Flags("0011")])]
+    #[case(&[0xCB, 0x77], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x78], 0xC002, 8, &[Flags("0011")])]
+    #[case(&[0xCB, 0x79], 0xC002, 8, &[Flags("0011")])]
+    #[case(&[0xCB, 0x7A], 0xC002, 8, &[Flags("0011")])]
+    #[case(&[0xCB, 0x7B], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x7C], 0xC002, 8, &[Flags("0011")])]
+    #[case(&[0xCB, 0x7D], 0xC002, 8, &[Flags("1011")])]
+    #[case(&[0xCB, 0x7E], 0xC002, 12, &[Flags("1011")])]
+    #[case(&[0xCB, 0x7F], 0xC002, 8, &[Flags("0011")])]
+    #[case(&[0xCB, 0x80], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0x81], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0x82], 0xC002, 8, &[Reg(Target::D, 0xC2), Flags("0001")])]
+    #[case(&[0xCB, 0x83], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0x84], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0x85], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0x86], 0xC002, 16, &[Flags("0001")])]
+    #[case(&[0xCB, 0x87], 0xC002, 8, &[Reg(Target::A, 0x80), Flags("0001")])]
+    #[case(&[0xCB, 0x88], 0xC002, 8, &[Reg(Target::B, 0xC0), Flags("0001")])]
+    #[case(&[0xCB, 0x89], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0x8A], 0xC002, 8, &[Reg(Target::D, 0xC1), Flags("0001")])]
+    #[case(&[0xCB, 0x8B], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0x8C], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0x8D], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0x8E], 0xC002, 16, &[Mem(0xC440, 0x58), Flags("0001")])]
+    #[case(&[0xCB, 0x8F], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0x90], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0x91], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0x92], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0x93], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0x94], 0xC002, 8, &[Reg(Target::H, 0xC0), Flags("0001")])]
+    #[case(&[0xCB, 0x95], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0x96], 0xC002, 16, &[Flags("0001")])]
+    #[case(&[0xCB, 0x97], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0x98], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0x99], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0x9A], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0x9B], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0x9C], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0x9D], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0x9E], 0xC002, 16, &[Mem(0xC440, 0x52), Flags("0001")])]
+    #[case(&[0xCB, 0x9F], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0xA0], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0xA1], 0xC002, 8, &[Reg(Target::C, 0x80), Flags("0001")])]
+    #[case(&[0xCB, 0xA2], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0xA3], 0xC002, 8, &[Reg(Target::E, 0x20), Flags("0001")])]
+    #[case(&[0xCB, 0xA4], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0xA5], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0xA6], 0xC002, 16, &[Mem(0xC440, 0x4A), Flags("0001")])]
+    #[case(&[0xCB, 0xA7], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0xA8], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0xA9], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0xAA], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0xAB], 0xC002, 8, &[Reg(Target::E, 0x10), Flags("0001")])]
+    #[case(&[0xCB, 0xAC], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0xAD], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0xAE], 0xC002, 16, &[Flags("0001")])]
+    #[case(&[0xCB, 0xAF], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0xB0], 0xC002, 8, &[Reg(Target::B, 0x82), Flags("0001")])]
+    #[case(&[0xCB, 0xB1], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0xB2], 0xC002, 8, &[Reg(Target::D, 0x83), Flags("0001")])]
+    #[case(&[0xCB, 0xB3], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0xB4], 0xC002, 8, &[Reg(Target::H, 0x84), Flags("0001")])]
+    #[case(&[0xCB, 0xB5], 0xC002, 8, &[Reg(Target::L, 0x00), Flags("0001")])]
+    #[case(&[0xCB, 0xB6], 0xC002, 16, &[Mem(0xC440, 0x1A), Flags("0001")])]
+    #[case(&[0xCB, 0xB7], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0xB8], 0xC002, 8, &[Reg(Target::B, 0x42), Flags("0001")])]
+    #[case(&[0xCB, 0xB9], 0xC002, 8, &[Reg(Target::C, 0x10), Flags("0001")])]
+    #[case(&[0xCB, 0xBA], 0xC002, 8, &[Reg(Target::D, 0x43), Flags("0001")])]
+    #[case(&[0xCB, 0xBB], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0xBC], 0xC002, 8, &[Reg(Target::H, 0x44), Flags("0001")])]
+    #[case(&[0xCB, 0xBD], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0xBE], 0xC002, 16, &[Flags("0001")])]
+    #[case(&[0xCB, 0xBF], 0xC002, 8, &[Reg(Target::A, 0x01), Flags("0001")])]
+    #[case(&[0xCB, 0xC0], 0xC002, 8, &[Reg(Target::B, 0xC3), Flags("0001")])]
+    #[case(&[0xCB, 0xC1], 0xC002, 8, &[Reg(Target::C, 0x91), Flags("0001")])]
+    #[case(&[0xCB, 0xC2], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0xC3], 0xC002, 8, &[Reg(Target::E, 0x31), Flags("0001")])]
+    #[case(&[0xCB, 0xC4], 0xC002, 8, &[Reg(Target::H, 0xC5), Flags("0001")])]
+    #[case(&[0xCB, 0xC5], 0xC002, 8, &[Reg(Target::L, 0x41), Flags("0001")])]
+    #[case(&[0xCB, 0xC6], 0xC002, 16, &[Mem(0xC440, 0x5B), Flags("0001")])]
+    #[case(&[0xCB, 0xC7], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0xC8], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0xC9], 0xC002, 8, &[Reg(Target::C, 0x92), Flags("0001")])]
+    #[case(&[0xCB, 0xCA], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0xCB], 0xC002, 8, &[Reg(Target::E, 0x32), Flags("0001")])]
+    #[case(&[0xCB, 0xCC], 0xC002, 8, &[Reg(Target::H, 0xC6), Flags("0001")])]
+    #[case(&[0xCB, 0xCD], 0xC002, 8, &[Reg(Target::L, 0x42), Flags("0001")])]
+    #[case(&[0xCB, 0xCE], 0xC002, 16, &[Flags("0001")])]
+    #[case(&[0xCB, 0xCF], 0xC002, 8, &[Reg(Target::A, 0x83), Flags("0001")])]
+    #[case(&[0xCB, 0xD0], 0xC002, 8, &[Reg(Target::B, 0xC6), Flags("0001")])]
+    #[case(&[0xCB, 0xD1], 0xC002, 8, &[Reg(Target::C, 0x94), Flags("0001")])]
+    #[case(&[0xCB, 0xD2], 0xC002, 8, &[Reg(Target::D, 0xC7), Flags("0001")])]
+    #[case(&[0xCB, 0xD3], 0xC002, 8, &[Reg(Target::E, 0x34), Flags("0001")])]
+    #[case(&[0xCB, 0xD4], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0xD5], 0xC002, 8, &[Reg(Target::L, 0x44), Flags("0001")])]
+    #[case(&[0xCB, 0xD6], 0xC002, 16, &[Mem(0xC440, 0x5E), Flags("0001")])]
+    #[case(&[0xCB, 0xD7], 0xC002, 8, &[Reg(Target::A, 0x85), Flags("0001")])]
+    #[case(&[0xCB, 0xD8], 0xC002, 8, &[Reg(Target::B, 0xCA), Flags("0001")])]
+    #[case(&[0xCB, 0xD9], 0xC002, 8, &[Reg(Target::C, 0x98), Flags("0001")])]
+    #[case(&[0xCB, 0xDA], 0xC002, 8, &[Reg(Target::D, 0xCB), Flags("0001")])]
+    #[case(&[0xCB, 0xDB], 0xC002, 8, &[Reg(Target::E, 0x38), Flags("0001")])]
+    #[case(&[0xCB, 0xDC], 0xC002, 8, &[Reg(Target::H, 0xCC), Flags("0001")])]
+    #[case(&[0xCB, 0xDD], 0xC002, 8, &[Reg(Target::L, 0x48), Flags("0001")])]
+    #[case(&[0xCB, 0xDE], 0xC002, 16, &[Flags("0001")])]
+    #[case(&[0xCB, 0xDF], 0xC002, 8, &[Reg(Target::A, 0x89), Flags("0001")])]
+    #[case(&[0xCB, 0xE0], 0xC002, 8, &[Reg(Target::B, 0xD2), Flags("0001")])]
+    #[case(&[0xCB, 0xE1], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0xE2], 0xC002, 8, &[Reg(Target::D, 0xD3), Flags("0001")])]
+    #[case(&[0xCB, 0xE3], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0xE4], 0xC002, 8, &[Reg(Target::H, 0xD4), Flags("0001")])]
+    #[case(&[0xCB, 0xE5], 0xC002, 8, &[Reg(Target::L, 0x50), Flags("0001")])]
+    #[case(&[0xCB, 0xE6], 0xC002, 16, &[Flags("0001")])]
+    #[case(&[0xCB, 0xE7], 0xC002, 8, &[Reg(Target::A, 0x91), Flags("0001")])]
+    #[case(&[0xCB, 0xE8], 0xC002, 8, &[Reg(Target::B, 0xE2), Flags("0001")])]
+    #[case(&[0xCB, 0xE9], 0xC002, 8, &[Reg(Target::C, 0xB0), Flags("0001")])]
+    #[case(&[0xCB, 0xEA], 0xC002, 8, &[Reg(Target::D, 0xE3), Flags("0001")])]
+    #[case(&[0xCB, 0xEB], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0xEC], 0xC002, 8, &[Reg(Target::H, 0xE4), Flags("0001")])]
+    #[case(&[0xCB, 0xED], 0xC002, 8, &[Reg(Target::L, 0x60), Flags("0001")])]
+    #[case(&[0xCB, 0xEE], 0xC002, 16, &[Mem(0xC440, 0x7A), Flags("0001")])]
+    #[case(&[0xCB, 0xEF], 0xC002, 8, &[Reg(Target::A, 0xA1), Flags("0001")])]
+    #[case(&[0xCB, 0xF0], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0xF1], 0xC002, 8, &[Reg(Target::C, 0xD0), Flags("0001")])]
+    #[case(&[0xCB, 0xF2], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0xF3], 0xC002, 8, &[Reg(Target::E, 0x70), Flags("0001")])]
+    #[case(&[0xCB, 0xF4], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0xF5], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0xF6], 0xC002, 16, &[Flags("0001")])]
+    #[case(&[0xCB, 0xF7], 0xC002, 8, &[Reg(Target::A, 0xC1), Flags("0001")])]
+    #[case(&[0xCB, 0xF8], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0xF9], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0xFA], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0xFB], 0xC002, 8, &[Reg(Target::E, 0xB0), Flags("0001")])]
+    #[case(&[0xCB, 0xFC], 0xC002, 8, &[Flags("0001")])]
+    #[case(&[0xCB, 0xFD], 0xC002, 8, &[Reg(Target::L, 0xC0), Flags("0001")])]
+    #[case(&[0xCB, 0xFE], 0xC002, 16, &[Mem(0xC440, 0xDA), Flags("0001")])]
+    #[case(&[0xCB, 0xFF], 0xC002, 8, &[Flags("0001")])]
+    fn test_opcode(
+        #[case] bytes: &[u8],
+        #[case] expected_pc: u16,
+        #[case] expected_cycles: u64,
+        #[case] expected: &[Expect],
+    ) {
+        let mut cpu = setup();
+        cpu.pc = 0xC000;
+        cpu.sp = 0xCFF0;
+        cpu.interrupts_enabled = false;
+        cpu.registers.a = 0x81;
+        for flag in [Flag::Zero, Flag::Sub, Flag::HalfCarry] {
+            cpu.registers.set_flag(flag, false);
+        }
+        cpu.registers.set_flag(Flag::Carry, true);
+        for (pair, address, value) in [
+            (Target::BC, 0xC290, 0x11),
+            (Target::DE, 0xC330, 0x22),
+            (Target::HL, 0xC440, 0x5A),
+        ] {
+            cpu.registers.set_combined_register(pair, address).unwrap();
+            cpu.write_to_memory(address, value);
+        }
+        cpu.write_to_memory(0xC550, 0x66);
+        cpu.write_to_memory(0xC551, 0x77);
+        cpu.write_to_memory(0xCFF0, 0x34);
+        cpu.write_to_memory(0xCFF1, 0x12);
+        for (offset, byte) in bytes.iter().enumerate() {
+            cpu.write_to_memory(cpu.pc + offset as u16, *byte);
+        }
+
+        assert!(cpu.step().unwrap());
+
+        assert_eq!(cpu.pc, expected_pc);
+        assert_eq!(cpu.cycles(), expected_cycles);
+        for expect in expected {
+            match expect {
+                Reg(target, value) => {
+                    assert_eq!(
+                        cpu.registers.get_register(*target).unwrap(),
+                        *value,
+                        "{target}"
+                    )
+                }
+                Pair(target, value) => {
+                    assert_eq!(
+                        cpu.registers.combined_register(*target).unwrap(),
+                        *value,
+                        "{target}"
+                    )
+                }
+                Mem(address, value) => {
+                    assert_eq!(cpu.read_memory(*address).unwrap(), *value, "{address:#06X}")
+                }
+                Mem16(address, value) => {
+                    let low = cpu.read_memory(*address).unwrap() as u16;
+                    let high = cpu.read_memory(address + 1).unwrap() as u16;
+                    assert_eq!(shift_left!(high, 8) | low, *value, "{address:#06X}")
+                }
+                Sp(value) => assert_eq!(cpu.sp, *value),
+                Ime(value) => assert_eq!(cpu.interrupts_enabled, *value),
+                Halted => assert!(cpu.is_halted),
+                Flags(flags) => {
+                    let flags = [Flag::Zero, Flag::Sub, Flag::HalfCarry, Flag::Carry]
+                        .into_iter()
+                        .zip(flags.chars());
+                    for (flag, expected) in flags {
+                        assert_eq!(cpu.registers.get_flag(flag), expected == '1', "{flag}");
+                    }
+                }
+            }
+        }
+    }
+
+    // Result and F after an ALU opcode, worked out with wide integers from the operands and the
+    // flags before it. None for opcodes that are not arithmetic.
+    fn reference_alu(opcode: OpCode, first: u16, second: u16, f: u8) -> Option<(u16, u8)> {
+        let flags = |zero: bool, sub: bool, half_carry: bool, carry: bool| {
+            shift_left!(zero as u8, 7)
+                | shift_left!(sub as u8, 6)
+                | shift_left!(half_carry as u8, 5)
+                | shift_left!(carry as u8, 4)
+        };
+        let zero_in = f & 0b10000000 != 0;
+        let carry_in = f & 0b00010000 != 0;
+        let (first, second) = (first as i32, second as i32);
+
+        let (result, f) = match opcode {
+            OpCode::ADD(_) | OpCode::ADC(_) => {
+                let carry = matches!(opcode, OpCode::ADC(_)) as i32 * carry_in as i32;
+                let sum = first + second + carry;
+                let half_carry = (first & 0xF) + (second & 0xF) + carry > 0xF;
+                (
+                    sum & 0xFF,
+                    flags(sum & 0xFF == 0, false, half_carry, sum > 0xFF),
+                )
+            }
+            OpCode::SUB(_) | OpCode::SBC(_) | OpCode::CP(_) => {
+                let carry = matches!(opcode, OpCode::SBC(_)) as i32 * carry_in as i32;
+                let difference = first - second - carry;
+                let half_carry = (first & 0xF) - (second & 0xF) - carry < 0;
+                let result = match opcode {
+                    OpCode::CP(_) => first,
+                    _ => difference & 0xFF,
+                };
+                let f = flags(difference & 0xFF == 0, true, half_carry, difference < 0);
+                (result, f)
+            }
+            OpCode::AND(_) => {
+                let result = first & second;
+                (result, flags(result == 0, false, true, false))
+            }
+            OpCode::OR(_) => {
+                let result = first | second;
+                (result, flags(result == 0, false, false, false))
+            }
+            OpCode::XOR(_) => {
+                let result = first ^ second;
+                (result, flags(result == 0, false, false, false))
+            }
+            OpCode::INC(_) => {
+                let result = (first + 1) & 0xFF;
+                (
+                    result,
+                    flags(result == 0, false, first & 0xF == 0xF, carry_in),
+                )
+            }
+            OpCode::DEC(_) => {
+                let result = (first - 1) & 0xFF;
+                (result, flags(result == 0, true, first & 0xF == 0, carry_in))
+            }
+            OpCode::ADD16(Target::HL, _) => {
+                let sum = first + second;
+                let half_carry = (first & 0xFFF) + (second & 0xFFF) > 0xFFF;
+                (
+                    sum & 0xFFFF,
+                    flags(zero_in, false, half_carry, sum > 0xFFFF),
+                )
+            }
+            OpCode::ADD16(Target::SP, Target::R8) | OpCode::LD(Target::HL, Target::SP_R8) => {
+                let result = (first + second as u8 as i8 as i32) & 0xFFFF;
+                let half_carry = (first & 0xF) + (second & 0xF) > 0xF;
+                let carry = (first & 0xFF) + second > 0xFF;
+                (result, flags(false, false, half_carry, carry))
+            }
+            _ => return None,
+        };
+        Some((result as u16, f))
+    }
+
+    #[test]
+    fn test_alu_opcodes_against_reference() {
+        // xorshift32, fixed seed so that a failure can be reproduced
+        let mut state = 0x2545_F491u32;
+        let mut random = move || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state
+        };
+
+        for byte in 0..=255 {
+            let Some(instruction) = Instruction::fetch(byte, false) else {
+                continue;
+            };
+            if reference_alu(instruction.opcode, 0, 0, 0).is_none() {
+                continue;
+            }
+
+            for _ in 0..64 {
+                let sample = random();
+                let f = (sample >> 24) as u8 & 0xF0;
+                let mut cpu = setup();
+                cpu.pc = 0xC000;
+                cpu.write_to_memory(0xC000, byte);
+                cpu.registers.f = f;
+                cpu.registers
+                    .set_combined_register(Target::HL, 0xC800)
+                    .unwrap();
+
+                let (first, second) = match instruction.opcode {
+                    OpCode::INC(target) | OpCode::DEC(target) => {
+                        cpu.set_operand(target, sample as u8).unwrap();
+                        (sample as u8 as u16, 0)
+                    }
+                    OpCode::ADD16(Target::HL, src) => {
+                        cpu.registers
+                            .set_combined_register(Target::HL, sample as u16)
+                            .unwrap();
+                        cpu.set_register_16(src, (sample >> 16) as u16).unwrap();
+                        let hl = cpu.registers.combined_register(Target::HL).unwrap();
+                        (hl, cpu.register_16(src).unwrap())
+                    }
+                    OpCode::ADD16(..) | OpCode::LD(..) => {
+                        cpu.sp = sample as u16;
+                        cpu.write_to_memory(0xC001, (sample >> 16) as u8);
+                        (cpu.sp, (sample >> 16) as u8 as u16)
+                    }
+                    OpCode::ADD(src)
+                    | OpCode::ADC(src)
+                    | OpCode::SUB(src)
+                    | OpCode::SBC(src)
+                    | OpCode::AND(src)
+                    | OpCode::OR(src)
+                    | OpCode::XOR(src)
+                    | OpCode::CP(src) => {
+                        cpu.registers.a = sample as u8;
+                        let v = (sample >> 8) as u8;
+                        match src {
+                            Target::D8 => cpu.write_to_memory(0xC001, v),
+                            _ => cpu.set_operand(src, v).unwrap(),
+                        }
+                        (cpu.registers.a as u16, v as u16)
+                    }
+                    opcode => panic!("{opcode:?} has no reference"),
+                };
+
+                let expected = reference_alu(instruction.opcode, first, second, f);
+                cpu.execute(instruction).unwrap();
+                let result = match instruction.opcode {
+                    OpCode::INC(target) | OpCode::DEC(target) => {
+                        cpu.operand(target).unwrap() as u16
+                    }
+                    OpCode::ADD16(Target::SP, _) => cpu.sp,
+                    OpCode::ADD16(..) | OpCode::LD(..) => {
+                        cpu.registers.combined_register(Target::HL).unwrap()
+                    }
+                    _ => cpu.registers.a as u16,
+                };
+                assert_eq!(
+                    Some((result, cpu.registers.f)),
+                    expected,
+                    "{:?} with {first:#06X}, {second:#06X} and F = {f:#04X}",
+                    instruction.opcode
+                );
+            }
+        }
+    }
+
+    // cargo test --release bench_instructions_per_second -- --ignored --nocapture > /dev/null
+    #[test]
+    #[ignore]
+    fn bench_instructions_per_second() {
+        // add b, adc c, sub d, sbc e, and h, xor l, or b, cp c, inc a, dec b, add hl,bc, rl c
+        // and jr back to the start: 13 instructions in 14 ticks
+        let program = [
+            0x80, 0x89, 0x92, 0x9B, 0xA4, 0xAD, 0xB0, 0xB9, 0x3C, 0x05, 0x09, 0xCB, 0x11, 0x18,
+            0xF1,
+        ];
+        const LOOPS: u32 = 20_000;
+
+        let mut cpu = setup();
+        cpu.pc = 0xC000;
+        for (offset, byte) in program.iter().enumerate() {
+            cpu.write_to_memory(0xC000 + offset as u16, *byte);
+        }
+
+        let start = std::time::Instant::now();
+        for _ in 0..LOOPS * 14 {
+            cpu.tick().unwrap();
+        }
+        let elapsed = start.elapsed().as_secs_f64();
+        assert_eq!(cpu.pc, 0xC000);
+
+        let instructions = (LOOPS * 13) as f64;
+        eprintln!("{:.0} instructions/s", instructions / elapsed);
+    }
+
+    fn load(cpu: &mut Cpu, address: u16, bytes: &[u8]) {
+        for (offset, byte) in bytes.iter().enumerate() {
+            cpu.write_to_memory(address + offset as u16, *byte);
+        }
+    }
+
+    // Calls a subroutine in a loop that reads and writes memory, then swaps A and starts over
+    fn setup_loop(block_cache: bool) -> Cpu {
+        let mut cpu = setup();
+        cpu.set_block_cache(block_cache);
+        cpu.pc = 0xC000;
+        cpu.sp = 0xCFF0;
+        // ld b,5, ld hl,0xC800, call 0xC020, dec b, jr nz,-6, swap a, jr -15
+        load(
+            &mut cpu,
+            0xC000,
+            &[
+                0x06, 0x05, 0x21, 0x00, 0xC8, 0xCD, 0x20, 0xC0, 0x05, 0x20, 0xFA, 0xCB, 0x37, 0x18,
+                0xF1,
+            ],
+        );
+        // inc a, ld (hl+),a, rlc c, add a,(hl), ret
+        load(&mut cpu, 0xC020, &[0x3C, 0x22, 0xCB, 0x01, 0x86, 0xC9]);
+        cpu
+    }
+
+    #[test]
+    fn test_block_cache_matches_interpreter() {
+        let mut interpreter = setup_loop(false);
+        let mut cached = setup_loop(true);
+
+        for step in 0..500 {
+            assert!(interpreter.step().unwrap());
+            assert!(cached.step().unwrap());
+            assert_eq!(cached.trace_line(), interpreter.trace_line(), "step {step}");
+            assert_eq!(cached.cycles(), interpreter.cycles(), "step {step}");
+        }
+    }
+
+    #[rstest]
+    // inc a, ld hl,0xC000, ld (hl),0x3D and jr back to what is now dec a
+    #[case(&[0x3C, 0x21, 0x00, 0xC0, 0x36, 0x3D, 0x18, 0xF8], 5, 0x05)]
+    // ld hl,0xC005 and ld (hl),0x3D turn the inc a later in the same block into dec a
+    #[case(&[0x21, 0x05, 0xC0, 0x36, 0x3D, 0x3C, 0x76], 3, 0x04)]
+    fn test_block_cache_sees_self_modifying_code(
+        #[case] code: &[u8],
+        #[case] steps: usize,
+        #[case] expected_a: u8,
+    ) {
+        let mut cpu = setup();
+        cpu.set_block_cache(true);
+        cpu.pc = 0xC000;
+        cpu.registers.a = 0x05;
+        load(&mut cpu, 0xC000, code);
+
+        for _ in 0..steps {
+            assert!(cpu.step().unwrap());
+        }
+        assert_eq!(cpu.registers.a, expected_a);
+    }
+
+    #[test]
+    fn test_block_cache_sees_debugger_writes() {
+        let mut cpu = setup();
+        cpu.set_block_cache(true);
+        cpu.pc = 0xC000;
+        cpu.registers.a = 0x05;
+        // inc a, jr -3
+        load(&mut cpu, 0xC000, &[0x3C, 0x18, 0xFD]);
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+
+        cpu.write_to_memory(0xC000, 0x3D);
+        cpu.step().unwrap();
+        assert_eq!(cpu.registers.a, 0x05);
+    }
+
+    // cargo test --release bench_block_cache -- --ignored --nocapture > /dev/null
+    #[test]
+    #[ignore]
+    fn bench_block_cache() {
+        const CYCLES: u64 = 4_194_304;
+        for (mode, block_cache) in [("interpreter", false), ("block cache", true)] {
+            let mut cpu = setup_loop(block_cache);
+            let mhz = emulated_mhz(&mut cpu, CYCLES);
+            eprintln!("{mode:12} {mhz:8.2} MHz");
+        }
+    }
 }
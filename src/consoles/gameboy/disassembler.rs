@@ -0,0 +1,128 @@
+// Mnemonics for the unprefixed opcodes. Operand placeholders are replaced by the bytes
+// following the opcode: d8 / a8 / r8 take one byte, d16 / a16 take two.
+#[rustfmt::skip]
+const MNEMONICS: [&str; 256] = [
+    // 0x00
+    "NOP", "LD BC,d16", "LD (BC),A", "INC BC", "INC B", "DEC B", "LD B,d8", "RLCA",
+    "LD (a16),SP", "ADD HL,BC", "LD A,(BC)", "DEC BC", "INC C", "DEC C", "LD C,d8", "RRCA",
+    // 0x10
+    "STOP", "LD DE,d16", "LD (DE),A", "INC DE", "INC D", "DEC D", "LD D,d8", "RLA",
+    "JR r8", "ADD HL,DE", "LD A,(DE)", "DEC DE", "INC E", "DEC E", "LD E,d8", "RRA",
+    // 0x20
+    "JR NZ,r8", "LD HL,d16", "LD (HL+),A", "INC HL", "INC H", "DEC H", "LD H,d8", "DAA",
+    "JR Z,r8", "ADD HL,HL", "LD A,(HL+)", "DEC HL", "INC L", "DEC L", "LD L,d8", "CPL",
+    // 0x30
+    "JR NC,r8", "LD SP,d16", "LD (HL-),A", "INC SP", "INC (HL)", "DEC (HL)", "LD (HL),d8", "SCF",
+    "JR C,r8", "ADD HL,SP", "LD A,(HL-)", "DEC SP", "INC A", "DEC A", "LD A,d8", "CCF",
+    // 0x40
+    "LD B,B", "LD B,C", "LD B,D", "LD B,E", "LD B,H", "LD B,L", "LD B,(HL)", "LD B,A",
+    "LD C,B", "LD C,C", "LD C,D", "LD C,E", "LD C,H", "LD C,L", "LD C,(HL)", "LD C,A",
+    // 0x50
+    "LD D,B", "LD D,C", "LD D,D", "LD D,E", "LD D,H", "LD D,L", "LD D,(HL)", "LD D,A",
+    "LD E,B", "LD E,C", "LD E,D", "LD E,E", "LD E,H", "LD E,L", "LD E,(HL)", "LD E,A",
+    // 0x60
+    "LD H,B", "LD H,C", "LD H,D", "LD H,E", "LD H,H", "LD H,L", "LD H,(HL)", "LD H,A",
+    "LD L,B", "LD L,C", "LD L,D", "LD L,E", "LD L,H", "LD L,L", "LD L,(HL)", "LD L,A",
+    // 0x70
+    "LD (HL),B", "LD (HL),C", "LD (HL),D", "LD (HL),E", "LD (HL),H", "LD (HL),L", "HALT", "LD (HL),A",
+    "LD A,B", "LD A,C", "LD A,D", "LD A,E", "LD A,H", "LD A,L", "LD A,(HL)", "LD A,A",
+    // 0x80
+    "ADD A,B", "ADD A,C", "ADD A,D", "ADD A,E", "ADD A,H", "ADD A,L", "ADD A,(HL)", "ADD A,A",
+    "ADC A,B", "ADC A,C", "ADC A,D", "ADC A,E", "ADC A,H", "ADC A,L", "ADC A,(HL)", "ADC A,A",
+    // 0x90
+    "SUB B", "SUB C", "SUB D", "SUB E", "SUB H", "SUB L", "SUB (HL)", "SUB A",
+    "SBC A,B", "SBC A,C", "SBC A,D", "SBC A,E", "SBC A,H", "SBC A,L", "SBC A,(HL)", "SBC A,A",
+    // 0xA0
+    "AND B", "AND C", "AND D", "AND E", "AND H", "AND L", "AND (HL)", "AND A",
+    "XOR B", "XOR C", "XOR D", "XOR E", "XOR H", "XOR L", "XOR (HL)", "XOR A",
+    // 0xB0
+    "OR B", "OR C", "OR D", "OR E", "OR H", "OR L", "OR (HL)", "OR A",
+    "CP B", "CP C", "CP D", "CP E", "CP H", "CP L", "CP (HL)", "CP A",
+    // 0xC0
+    "RET NZ", "POP BC", "JP NZ,a16", "JP a16", "CALL NZ,a16", "PUSH BC", "ADD A,d8", "RST 00H",
+    "RET Z", "RET", "JP Z,a16", "PREFIX CB", "CALL Z,a16", "CALL a16", "ADC A,d8", "RST 08H",
+    // 0xD0
+    "RET NC", "POP DE", "JP NC,a16", "ILLEGAL_D3", "CALL NC,a16", "PUSH DE", "SUB d8", "RST 10H",
+    "RET C", "RETI", "JP C,a16", "ILLEGAL_DB", "CALL C,a16", "ILLEGAL_DD", "SBC A,d8", "RST 18H",
+    // 0xE0
+    "LDH (a8),A", "POP HL", "LD (C),A", "ILLEGAL_E3", "ILLEGAL_E4", "PUSH HL", "AND d8", "RST 20H",
+    "ADD SP,r8", "JP HL", "LD (a16),A", "ILLEGAL_EB", "ILLEGAL_EC", "ILLEGAL_ED", "XOR d8", "RST 28H",
+    // 0xF0
+    "LDH A,(a8)", "POP AF", "LD A,(C)", "DI", "ILLEGAL_F4", "PUSH AF", "OR d8", "RST 30H",
+    "LD HL,SP+r8", "LD SP,HL", "LD A,(a16)", "EI", "ILLEGAL_FC", "ILLEGAL_FD", "CP d8", "RST 38H",
+];
+
+const CB_OPERATIONS: [&str; 8] = ["RLC", "RRC", "RL", "RR", "SLA", "SRA", "SWAP", "SRL"];
+const CB_BIT_OPERATIONS: [&str; 3] = ["BIT", "RES", "SET"];
+const CB_REGISTERS: [&str; 8] = ["B", "C", "D", "E", "H", "L", "(HL)", "A"];
+
+fn disassemble_prefixed(byte: u8) -> String {
+    let register = CB_REGISTERS[(byte & 0b111) as usize];
+    let operation = (byte >> 3) as usize;
+    if operation < 8 {
+        format!("{} {register}", CB_OPERATIONS[operation])
+    } else {
+        let bit = operation & 0b111;
+        format!("{} {bit},{register}", CB_BIT_OPERATIONS[operation / 8 - 1])
+    }
+}
+
+// Disassembles the instruction starting at bytes[0], returning its text and length in bytes.
+// Missing operand bytes are read as 0xFF.
+pub fn disassemble(bytes: &[u8]) -> (String, u8) {
+    let byte = |i: usize| bytes.get(i).copied().unwrap_or(0xFF);
+    let opcode = byte(0);
+
+    if opcode == 0xCB {
+        return (disassemble_prefixed(byte(1)), 2);
+    }
+
+    let mnemonic = MNEMONICS[opcode as usize];
+    let word = (byte(2) as u16) << 8 | byte(1) as u16;
+    if mnemonic.contains("d16") || mnemonic.contains("a16") {
+        let text = mnemonic
+            .replace("d16", &format!("${word:04X}"))
+            .replace("a16", &format!("${word:04X}"));
+        (text, 3)
+    } else if mnemonic.contains("d8") || mnemonic.contains("a8") {
+        let text = mnemonic
+            .replace("d8", &format!("${:02X}", byte(1)))
+            .replace("a8", &format!("$FF{:02X}", byte(1)));
+        (text, 2)
+    } else if mnemonic.contains("r8") {
+        let offset = byte(1) as i8;
+        let text = mnemonic
+            .replace("+r8", &format!("{offset:+}"))
+            .replace("r8", &format!("{offset}"));
+        (text, 2)
+    } else if opcode == 0x10 {
+        (mnemonic.to_string(), 2)
+    } else {
+        (mnemonic.to_string(), 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::disassemble;
+
+    #[rstest]
+    #[case(&[0x00], "NOP", 1)]
+    #[case(&[0x01, 0x34, 0x12], "LD BC,$1234", 3)]
+    #[case(&[0x3E, 0x42], "LD A,$42", 2)]
+    #[case(&[0x18, 0xFE], "JR -2", 2)]
+    #[case(&[0xE0, 0x44], "LDH ($FF44),A", 2)]
+    #[case(&[0xC3, 0x50, 0x01], "JP $0150", 3)]
+    #[case(&[0xF8, 0x02], "LD HL,SP+2", 2)]
+    #[case(&[0xCB, 0x37], "SWAP A", 2)]
+    #[case(&[0xCB, 0x7E], "BIT 7,(HL)", 2)]
+    #[case(&[0xCB, 0x80], "RES 0,B", 2)]
+    #[case(&[0xCB, 0xFF], "SET 7,A", 2)]
+    #[case(&[0xD3], "ILLEGAL_D3", 1)]
+    #[case(&[0xFA], "LD A,($FFFF)", 3)]
+    fn test_disassemble(#[case] bytes: &[u8], #[case] expected: &str, #[case] length: u8) {
+        assert_eq!(disassemble(bytes), (expected.to_string(), length));
+    }
+}
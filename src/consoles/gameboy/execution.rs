@@ -0,0 +1,217 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use super::cpu::{CpuFault, LockUp};
+
+// T-cycles per emulated second
+pub const CYCLES_PER_SECOND: u64 = 4_194_304;
+
+// T-cycles per frame, 154 scanlines of 456 cycles
+pub const CYCLES_PER_FRAME: u64 = 70224;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunUntil {
+    // Runs at least the given number of T-cycles, the last instruction may overshoot
+    Cycles(u64),
+    // Runs until the cycle counter crosses the next frame boundary
+    FrameEnd,
+    // Runs until the pc reaches the address, after executing at least one instruction
+    Pc(u16),
+    // Only a stop request or the cpu stopping ends the run
+    Stopped,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StopReason {
+    CyclesElapsed,
+    FrameEnded,
+    PcReached(u16),
+    StopRequested,
+    // The cpu executed STOP
+    CpuStopped,
+    // The cpu hit an error and is frozen
+    Faulted(CpuFault),
+    // The cpu executed an illegal opcode during this run. Running on lets the rest of the
+    // hardware continue while the cpu stays hung.
+    LockedUp(LockUp),
+}
+
+// Asks a running machine to return from run_until before its next instruction.
+// Clones share the same flag and can be sent to other threads, e.g. a ui or a signal handler.
+#[derive(Debug, Clone, Default)]
+pub struct StopHandle(Arc<AtomicBool>);
+
+impl StopHandle {
+    pub fn new() -> StopHandle {
+        StopHandle::default()
+    }
+
+    pub fn request_stop(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_stop_requested(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    // Clears the request so the next run starts normally
+    fn take_request(&self) -> bool {
+        self.0.swap(false, Ordering::SeqCst)
+    }
+}
+
+// Anything that executes one instruction at a time, implemented by the cpu on its own and by
+// the game boy, which also advances the rest of the hardware on every step
+pub trait Steppable {
+    // Executes one instruction, returns false if the cpu is stopped
+    fn step(&mut self) -> Result<bool, CpuFault>;
+    fn cycles(&self) -> u64;
+    fn pc(&self) -> u16;
+    fn stop_handle(&self) -> &StopHandle;
+    fn lock_up(&self) -> Option<LockUp>;
+
+    fn run_until(&mut self, limit: RunUntil) -> StopReason {
+        let start = self.cycles();
+        let was_locked_up = self.lock_up().is_some();
+        let target_cycles = match limit {
+            RunUntil::Cycles(cycles) => start.saturating_add(cycles),
+            RunUntil::FrameEnd => (start / CYCLES_PER_FRAME + 1) * CYCLES_PER_FRAME,
+            _ => u64::MAX,
+        };
+
+        loop {
+            if self.stop_handle().take_request() {
+                return StopReason::StopRequested;
+            }
+            match self.step() {
+                Ok(true) => {}
+                Ok(false) => return StopReason::CpuStopped,
+                Err(fault) => return StopReason::Faulted(fault),
+            }
+
+            if !was_locked_up && let Some(lock_up) = self.lock_up() {
+                return StopReason::LockedUp(lock_up);
+            }
+
+            match limit {
+                RunUntil::Cycles(_) if self.cycles() >= target_cycles => {
+                    return StopReason::CyclesElapsed;
+                }
+                RunUntil::FrameEnd if self.cycles() >= target_cycles => {
+                    return StopReason::FrameEnded;
+                }
+                RunUntil::Pc(address) if self.pc() == address => {
+                    return StopReason::PcReached(address);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+// Runs the machine for the given number of T-cycles and returns how fast that went in emulated
+// MHz, a dmg runs at 4.19
+pub fn emulated_mhz(machine: &mut impl Steppable, cycles: u64) -> f64 {
+    let start_cycles = machine.cycles();
+    let start = Instant::now();
+    machine.run_until(RunUntil::Cycles(cycles));
+    let elapsed = start.elapsed().as_secs_f64();
+    (machine.cycles() - start_cycles) as f64 / elapsed / 1_000_000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::{CYCLES_PER_FRAME, RunUntil, Steppable, StopHandle, StopReason, emulated_mhz};
+    use crate::consoles::gameboy::cpu::{CpuFault, LockUp};
+
+    // Every instruction takes 4 cycles and advances the pc by one until stop_at
+    struct FakeMachine {
+        cycles: u64,
+        pc: u16,
+        stop_at: u16,
+        stop: StopHandle,
+    }
+
+    impl Steppable for FakeMachine {
+        fn step(&mut self) -> Result<bool, CpuFault> {
+            if self.pc == self.stop_at {
+                return Ok(false);
+            }
+            self.cycles += 4;
+            self.pc += 1;
+            Ok(true)
+        }
+
+        fn cycles(&self) -> u64 {
+            self.cycles
+        }
+
+        fn pc(&self) -> u16 {
+            self.pc
+        }
+
+        fn stop_handle(&self) -> &StopHandle {
+            &self.stop
+        }
+
+        fn lock_up(&self) -> Option<LockUp> {
+            None
+        }
+    }
+
+    fn setup(cycles: u64) -> FakeMachine {
+        FakeMachine {
+            cycles,
+            pc: 0,
+            stop_at: u16::MAX,
+            stop: StopHandle::new(),
+        }
+    }
+
+    #[rstest]
+    #[case(0, RunUntil::Cycles(10), StopReason::CyclesElapsed, 12)]
+    #[case(0, RunUntil::FrameEnd, StopReason::FrameEnded, CYCLES_PER_FRAME)]
+    #[case(CYCLES_PER_FRAME, RunUntil::FrameEnd, StopReason::FrameEnded, 2 * CYCLES_PER_FRAME)]
+    #[case(0, RunUntil::Pc(0x10), StopReason::PcReached(0x10), 0x40)]
+    fn test_run_until(
+        #[case] start: u64,
+        #[case] limit: RunUntil,
+        #[case] reason: StopReason,
+        #[case] cycles: u64,
+    ) {
+        let mut machine = setup(start);
+        assert_eq!(machine.run_until(limit), reason);
+        assert_eq!(machine.cycles(), cycles);
+    }
+
+    #[test]
+    fn test_cpu_stopped() {
+        let mut machine = setup(0);
+        machine.stop_at = 3;
+        assert_eq!(machine.run_until(RunUntil::Stopped), StopReason::CpuStopped);
+        assert_eq!(machine.pc(), 3);
+    }
+
+    #[test]
+    fn test_stop_requested() {
+        let mut machine = setup(0);
+        machine.stop_handle().clone().request_stop();
+        assert_eq!(
+            machine.run_until(RunUntil::Stopped),
+            StopReason::StopRequested
+        );
+        assert_eq!(machine.cycles(), 0);
+        assert!(!machine.stop_handle().is_stop_requested());
+    }
+
+    #[test]
+    fn test_emulated_mhz() {
+        let mut machine = setup(0);
+        let mhz = emulated_mhz(&mut machine, 4000);
+        assert_eq!(machine.cycles(), 4000);
+        assert!(mhz > 0.0);
+    }
+}
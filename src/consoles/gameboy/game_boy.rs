@@ -1,52 +1,517 @@
 use std::cell::RefCell;
+use std::error::Error;
+use std::io::Write;
+use std::ops::RangeInclusive;
 use std::rc::Rc;
 
-use super::super::console::Console;
+use super::super::console::{AddressOutOfRangeError, Buttons, Console, Frame, UnsupportedError};
+use super::execution::{
+    CYCLES_PER_FRAME, CYCLES_PER_SECOND, RunUntil, Steppable, StopHandle, StopReason,
+};
 use super::gbcartridge::GbCartridge;
-use super::instruction::Instruction;
-use super::opcode::OpCode::NOP;
+use super::interrupts::InterruptController;
+use super::io::IoRegisters;
+use super::joypad::Joypad;
+use super::model::Model;
+use super::serial::{Serial, SerialEndpoint};
+use super::trace::TraceWriter;
+use super::unusable::Unusable;
 use crate::consoles::addressable::Addressable;
-use crate::consoles::bus::Bus;
-use crate::consoles::gameboy::cpu::Cpu;
-use crate::consoles::memory::Memory;
-use crate::consoles::memory_map::gameboy::{ROM_BANK_00, WRAM};
+use crate::consoles::bus::{Bus, StrictMode};
+use crate::consoles::gameboy::cpu::{Cpu, CpuFault, LockUp};
+use crate::consoles::memory::{Memory, PowerOnPattern};
+use crate::consoles::memory_map::gameboy::{
+    _UNUSABLE, ECHO_RAM, EXTERNAL_WRAM, H_RAM, OBJECT_ATTRIBUTE_MEMORY, ROM_BANK_00, ROM_BANK_1_N,
+    VRAM, WRAM,
+};
+use crate::consoles::mirror::Mirror;
+use crate::consoles::state::{SaveState, StateError, StateReader, StateWriter};
+use crate::log;
 use crate::utils::conversion::u16_to_u8;
 
-pub type GbMemory = Memory<u16, u8, u16, 0x10000>;
+pub type GbMemory<const N: usize> = Memory<u16, u8, u16, N>;
 pub type GbBus = Bus<u16, u8, u16>;
 
+const fn size(range: &RangeInclusive<usize>) -> usize {
+    *range.end() - *range.start() + 1
+}
+
+const WRAM_SIZE: usize = size(&WRAM) + size(&EXTERNAL_WRAM);
+const VRAM_SIZE: usize = size(&VRAM);
+const OAM_SIZE: usize = size(&OBJECT_ATTRIBUTE_MEMORY);
+const HRAM_SIZE: usize = size(&H_RAM);
+
+pub const SCREEN_WIDTH: usize = 160;
+pub const SCREEN_HEIGHT: usize = 144;
+// What the lcd shows while it is off
+const LCD_OFF: u32 = 0xFFFFFF;
+
+// Changes whenever the layout of a save state does
+const STATE_HEADER: &[u8] = b"GBSTATE\x02";
+
+fn memory<const N: usize>(
+    range: RangeInclusive<usize>,
+    pattern: PowerOnPattern<u8>,
+) -> Rc<RefCell<GbMemory<N>>> {
+    let mut memory = GbMemory::<N>::new(u16_to_u8, pattern.for_region(*range.start()));
+    memory.assign_address_range(range);
+    Rc::new(RefCell::new(memory))
+}
+
 pub struct GameBoy {
     cpu: Cpu,
+    bus: Rc<RefCell<GbBus>>,
+    serial: Rc<RefCell<Serial>>,
+    joypad: Rc<RefCell<Joypad>>,
+    interrupts: Rc<RefCell<InterruptController>>,
+    wram: Rc<RefCell<GbMemory<WRAM_SIZE>>>,
+    vram: Rc<RefCell<GbMemory<VRAM_SIZE>>>,
+    oam: Rc<RefCell<GbMemory<OAM_SIZE>>>,
+    hram: Rc<RefCell<GbMemory<HRAM_SIZE>>>,
+    // There is no ppu yet, so the screen stays the way a switched off lcd looks
+    screen: Vec<u32>,
+    // The state right after power on, which reset goes back to
+    power_on: Vec<u8>,
 }
 
 impl GameBoy {
-    pub fn new(mut cartridge: GbCartridge) -> GameBoy {
-        cartridge.assign_address_range(ROM_BANK_00);
+    pub fn new(cartridge: GbCartridge) -> GameBoy {
+        GameBoy::with_model(cartridge, Model::default())
+    }
 
-        let get_default_value = || Instruction::byte_from_opcode(NOP).unwrap();
-        let memory = Rc::new(RefCell::new(GbMemory::new(
-            u16_to_u8,
-            Some(Box::new(get_default_value)),
+    pub fn from_rom(data: Vec<u8>) -> GameBoy {
+        GameBoy::new(GbCartridge::from_bytes("rom", data))
+    }
+
+    pub fn with_model(cartridge: GbCartridge, model: Model) -> GameBoy {
+        GameBoy::with_power_on(cartridge, model, model.power_on_pattern())
+    }
+
+    // The pattern fills the rams the boot rom leaves alone, vram is cleared by the boot rom
+    pub fn with_power_on(
+        mut cartridge: GbCartridge,
+        model: Model,
+        pattern: PowerOnPattern<u8>,
+    ) -> GameBoy {
+        cartridge.assign_address_range(*ROM_BANK_00.start()..=*ROM_BANK_1_N.end());
+
+        let wram = memory::<WRAM_SIZE>(*WRAM.start()..=*EXTERNAL_WRAM.end(), pattern);
+        let vram = memory::<VRAM_SIZE>(VRAM, PowerOnPattern::Fill(0));
+        let oam = memory::<OAM_SIZE>(OBJECT_ATTRIBUTE_MEMORY, pattern);
+        let hram = memory::<HRAM_SIZE>(H_RAM, pattern);
+        // Echo ram shows 0xC000-0xDDFF again
+        let echo_ram = Rc::new(RefCell::new(Mirror::new(
+            wram.clone(),
+            ECHO_RAM,
+            ECHO_RAM.start() - WRAM.start(),
         )));
-        memory.borrow_mut().assign_address_range(WRAM);
+        let unusable = Rc::new(RefCell::new(Unusable::new(model)));
+
+        let interrupts = Rc::new(RefCell::new(InterruptController::new()));
+        let serial = Rc::new(RefCell::new(Serial::new(interrupts.clone())));
+        let joypad = Rc::new(RefCell::new(Joypad::new(interrupts.clone())));
+        let mut io = IoRegisters::new();
+        io.connect(interrupts.clone());
+        io.connect(serial.clone());
+        io.connect(joypad.clone());
+        let io = Rc::new(RefCell::new(io));
 
         let mut bus = GbBus::new();
-        bus.connect_readable(memory.clone());
-        bus.connect_writeable(memory);
+        bus.set_open_bus(0xFF);
+        bus.prohibit(ECHO_RAM);
+        bus.prohibit(_UNUSABLE);
+        bus.connect_readable(wram.clone());
+        bus.connect_writeable(wram.clone());
+        bus.connect_readable(vram.clone());
+        bus.connect_writeable(vram.clone());
+        bus.connect_readable(oam.clone());
+        bus.connect_writeable(oam.clone());
+        bus.connect_readable(hram.clone());
+        bus.connect_writeable(hram.clone());
+        bus.connect_readable(echo_ram.clone());
+        bus.connect_writeable(echo_ram);
+        bus.connect_readable(unusable.clone());
+        bus.connect_writeable(unusable);
         bus.connect_readable(Rc::new(RefCell::new(cartridge)));
+        bus.connect_readable(interrupts.clone());
+        bus.connect_writeable(interrupts.clone());
+        bus.connect_readable(io.clone());
+        bus.connect_writeable(io);
         let bus = Rc::new(RefCell::new(bus));
-        GameBoy {
-            cpu: Cpu::new(bus.clone()),
+        let mut cpu = Cpu::new(bus.clone());
+        cpu.connect_clocked(serial.clone());
+        cpu.power_up();
+
+        let mut gameboy = GameBoy {
+            cpu,
+            bus,
+            serial,
+            joypad,
+            interrupts,
+            wram,
+            vram,
+            oam,
+            hram,
+            screen: vec![LCD_OFF; SCREEN_WIDTH * SCREEN_HEIGHT],
+            power_on: vec![],
+        };
+        gameboy.power_on = gameboy.save_state();
+        gameboy
+    }
+
+    fn load_devices(&mut self, state: &[u8]) -> Result<(), StateError> {
+        let mut state = StateReader::new(state, STATE_HEADER)?;
+        self.cpu.load_state(&mut state)?;
+        for device in self.devices() {
+            device.borrow_mut().load_state(&mut state)?;
         }
+        state.finish()
+    }
+
+    // Every device with a state, in the order they appear in a save state
+    fn devices(&self) -> [Rc<RefCell<dyn SaveState>>; 7] {
+        [
+            self.interrupts.clone(),
+            self.serial.clone(),
+            self.joypad.clone(),
+            self.wram.clone(),
+            self.vram.clone(),
+            self.oam.clone(),
+            self.hram.clone(),
+        ]
+    }
+
+    pub fn set_strict_mode(&mut self, mode: StrictMode) {
+        self.bus.borrow_mut().set_strict_mode(mode);
+    }
+
+    // See Cpu::set_block_cache
+    pub fn set_block_cache(&mut self, enabled: bool) {
+        self.cpu.set_block_cache(enabled);
+    }
+
+    pub fn connect_serial(&mut self, endpoint: Box<dyn SerialEndpoint>) {
+        self.serial.borrow_mut().connect(endpoint);
+    }
+
+    // Connects the serial ports of two game boys with a link cable. Both have to be stepped
+    // by the caller, the transfer completes when the side using its internal clock finishes.
+    pub fn link(a: &GameBoy, b: &GameBoy) {
+        Serial::link(&a.serial, &b.serial);
+    }
+
+    // The error the cpu froze on, if any
+    pub fn fault(&self) -> Option<&CpuFault> {
+        self.cpu.fault()
+    }
+
+    #[cfg(any(feature = "debugger", test))]
+    pub(super) fn cpu_mut(&mut self) -> &mut Cpu {
+        &mut self.cpu
+    }
+}
+
+impl Steppable for GameBoy {
+    // Executes one instruction, the cpu advances the rest of the hardware as it goes
+    fn step(&mut self) -> Result<bool, CpuFault> {
+        self.cpu.step()
+    }
+
+    fn cycles(&self) -> u64 {
+        self.cpu.cycles()
+    }
+
+    fn pc(&self) -> u16 {
+        self.cpu.pc()
+    }
+
+    fn stop_handle(&self) -> &StopHandle {
+        self.cpu.stop_handle()
+    }
+
+    fn lock_up(&self) -> Option<LockUp> {
+        self.cpu.lock_up()
     }
 }
 
 impl Console for GameBoy {
-    fn save_game(&self, path: String) {}
+    // There is no mbc yet, so no cartridge has ram to keep
+    fn save_game(&self, path: String) -> Result<(), Box<dyn Error>> {
+        Err(Box::new(UnsupportedError {
+            what: format!("Cannot write {path}: cartridge ram is not supported yet"),
+        }))
+    }
+
+    fn load_save(&mut self, path: String) -> Result<(), Box<dyn Error>> {
+        Err(Box::new(UnsupportedError {
+            what: format!("Cannot load {path}: cartridge ram is not supported yet"),
+        }))
+    }
+
+    fn frame_rate(&self) -> f64 {
+        CYCLES_PER_SECOND as f64 / CYCLES_PER_FRAME as f64
+    }
+
+    fn trace_to(&mut self, out: Box<dyn Write>) {
+        self.cpu.set_tracer(Some(TraceWriter::new(out)));
+    }
+
+    fn set_strict_mode(&mut self, mode: StrictMode) {
+        GameBoy::set_strict_mode(self, mode);
+    }
+
+    fn reset(&mut self) {
+        let power_on = std::mem::take(&mut self.power_on);
+        self.load_state(&power_on)
+            .expect("the power on state always loads");
+        self.power_on = power_on;
+    }
+
+    // A locked up or stopped cpu still lets the frame complete
+    fn step_frame(&mut self) -> Result<(), Box<dyn Error>> {
+        let frame_end = (self.cycles() / CYCLES_PER_FRAME + 1) * CYCLES_PER_FRAME;
+        while self.cycles() < frame_end {
+            match self.run_until(RunUntil::Cycles(frame_end - self.cycles())) {
+                StopReason::Faulted(fault) => return Err(Box::new(fault)),
+                // The rest of the hardware keeps running while the cpu hangs
+                StopReason::LockedUp(lock_up) => log!(Cpu, Warn, "{lock_up}"),
+                StopReason::StopRequested => break,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn framebuffer(&self) -> Frame<'_> {
+        Frame {
+            width: SCREEN_WIDTH,
+            height: SCREEN_HEIGHT,
+            pixels: &self.screen,
+        }
+    }
 
-    fn load_save(&self, path: String) {}
+    // There is no apu yet, so there never is anything to play
+    fn audio_samples(&mut self) -> Vec<i16> {
+        vec![]
+    }
+
+    fn set_buttons(&mut self, buttons: Buttons) {
+        self.joypad.borrow_mut().set_buttons(buttons);
+    }
+
+    fn read_memory(&self, address: usize) -> Result<u8, Box<dyn Error>> {
+        let address = u16::try_from(address).map_err(|_| AddressOutOfRangeError { address })?;
+        Ok(self.cpu.read_memory(address)?)
+    }
+
+    fn write_memory(&mut self, address: usize, value: u8) -> Result<(), Box<dyn Error>> {
+        let address = u16::try_from(address).map_err(|_| AddressOutOfRangeError { address })?;
+        Ok(self.cpu.write_memory(address, value)?)
+    }
+
+    fn save_state(&self) -> Vec<u8> {
+        let mut out = StateWriter::new(STATE_HEADER);
+        self.cpu.save_state(&mut out);
+        for device in self.devices() {
+            device.borrow().save_state(&mut out);
+        }
+        out.into_bytes()
+    }
+
+    // Nothing changes unless the whole state loads
+    fn load_state(&mut self, state: &[u8]) -> Result<(), Box<dyn Error>> {
+        let backup = self.save_state();
+        let result = self.load_devices(state);
+        if result.is_err() {
+            self.load_devices(&backup)?;
+        }
+        Ok(result?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::OnceCell;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    use rstest::rstest;
+
+    use super::{GameBoy, SCREEN_HEIGHT, SCREEN_WIDTH};
+    use crate::consoles::console::{Buttons, Console};
+    use crate::consoles::gameboy::execution::{CYCLES_PER_FRAME, Steppable};
+    use crate::consoles::gameboy::{gbcartridge::GbCartridge, model::Model};
+    use crate::consoles::pacing::{Clock, FramePacer, PacerHandle};
+
+    fn setup(model: Model) -> GameBoy {
+        GameBoy::with_model(GbCartridge::from_bytes("test", vec![0; 0x8000]), model)
+    }
+
+    #[test]
+    fn test_echo_ram() {
+        let mut gameboy = setup(Model::Dmg);
+        let cpu = gameboy.cpu_mut();
+
+        cpu.write_to_memory(0xC123, 0x42);
+        assert_eq!(cpu.read_memory(0xE123).unwrap(), 0x42);
+        cpu.write_to_memory(0xFDFF, 0x24);
+        assert_eq!(cpu.read_memory(0xDDFF).unwrap(), 0x24);
+    }
+
+    #[rstest]
+    #[case(Model::Dmg, 0x00)]
+    #[case(Model::Cgb, 0xAA)]
+    fn test_unusable(#[case] model: Model, #[case] expected: u8) {
+        let mut gameboy = setup(model);
+        let cpu = gameboy.cpu_mut();
+
+        cpu.write_to_memory(0xFEA5, 0x42);
+        assert_eq!(cpu.read_memory(0xFEA5).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_save_and_load_state() {
+        let mut gameboy = setup(Model::Dmg);
+        gameboy.write_memory(0xC000, 0x42).unwrap();
+        gameboy.step_frame().unwrap();
+        let state = gameboy.save_state();
+
+        gameboy.write_memory(0xC000, 0x24).unwrap();
+        gameboy.write_memory(0xFF80, 0x24).unwrap();
+        gameboy.step_frame().unwrap();
+        gameboy.load_state(&state).unwrap();
+
+        assert_eq!(gameboy.read_memory(0xC000).unwrap(), 0x42);
+        assert_eq!(gameboy.cycles(), CYCLES_PER_FRAME);
+        assert_eq!(gameboy.save_state(), state);
+    }
+
+    #[rstest]
+    #[case(b"not a save state")]
+    // Cut off in the middle of the rams
+    #[case(&setup(Model::Dmg).save_state()[..0x1000])]
+    fn test_load_state_failure_changes_nothing(#[case] bad_state: &[u8]) {
+        let mut gameboy = setup(Model::Dmg);
+        gameboy.write_memory(0xC000, 0x42).unwrap();
+        let state = gameboy.save_state();
+
+        assert!(gameboy.load_state(bad_state).is_err());
+        assert_eq!(gameboy.save_state(), state);
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut gameboy = setup(Model::Dmg);
+        let power_on = gameboy.read_memory(0xC000).unwrap();
+        gameboy.write_memory(0xC000, !power_on).unwrap();
+        gameboy.step_frame().unwrap();
+
+        gameboy.reset();
+        assert_eq!(gameboy.read_memory(0xC000).unwrap(), power_on);
+        assert_eq!(gameboy.cycles(), 0);
+        assert_eq!(gameboy.pc(), 0x0100);
+    }
+
+    #[test]
+    fn test_step_frame() {
+        let mut gameboy = setup(Model::Dmg);
+        gameboy.step_frame().unwrap();
+        gameboy.step_frame().unwrap();
+        assert_eq!(gameboy.cycles(), 2 * CYCLES_PER_FRAME);
+    }
+
+    // A paused pacer only sleeps once it has no frames left to advance, which is when to stop
+    struct StopOnSleep(Rc<OnceCell<PacerHandle>>);
+
+    impl Clock for StopOnSleep {
+        fn now(&self) -> Duration {
+            Duration::ZERO
+        }
+
+        fn sleep(&mut self, _duration: Duration) {
+            self.0.get().unwrap().stop();
+        }
+    }
+
+    #[test]
+    fn test_run_while_paused_only_advances_frames() {
+        let mut gameboy = setup(Model::Dmg);
+        let stopper = Rc::new(OnceCell::new());
+        let mut pacer =
+            FramePacer::with_clock(gameboy.frame_rate(), Box::new(StopOnSleep(stopper.clone())));
+        let handle = pacer.handle();
+        stopper.set(handle.clone()).unwrap();
+        handle.pause();
+        for _ in 0..3 {
+            handle.advance_frame();
+        }
+
+        gameboy.run(&mut pacer).unwrap();
+        assert_eq!(gameboy.cycles(), 3 * CYCLES_PER_FRAME);
+    }
+
+    #[test]
+    fn test_button_wakes_stopped_cpu() {
+        let mut gameboy = setup(Model::Dmg);
+        // stop, then inc a and jr back to it forever
+        for (address, byte) in (0xC000..).zip([0x10, 0x00, 0x3C, 0x18, 0xFD]) {
+            gameboy.write_memory(address, byte).unwrap();
+        }
+        gameboy.cpu_mut().set_pc(0xC000);
+        // Select the action buttons
+        gameboy.write_memory(0xFF00, 0x10).unwrap();
+
+        gameboy.step_frame().unwrap();
+        assert_eq!(gameboy.cycles(), CYCLES_PER_FRAME);
+        assert_eq!(gameboy.pc(), 0xC002);
+        let a = gameboy.cpu_mut().get_reg_a();
+
+        gameboy.set_buttons(Buttons {
+            a: true,
+            ..Default::default()
+        });
+        gameboy.step_frame().unwrap();
+        assert_ne!(gameboy.cpu_mut().get_reg_a(), a);
+    }
+
+    #[test]
+    fn test_battery_saves_unsupported() {
+        let mut gameboy = setup(Model::Dmg);
+        assert_eq!(
+            gameboy
+                .save_game("test.sav".to_string())
+                .unwrap_err()
+                .to_string(),
+            "Cannot write test.sav: cartridge ram is not supported yet"
+        );
+        assert!(gameboy.load_save("test.sav".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_buttons() {
+        let mut gameboy = setup(Model::Dmg);
+        // Select the action buttons
+        gameboy.write_memory(0xFF00, 0x10).unwrap();
+        gameboy.set_buttons(Buttons {
+            start: true,
+            ..Default::default()
+        });
+        assert_eq!(gameboy.read_memory(0xFF00).unwrap(), 0xD7);
+    }
+
+    #[test]
+    fn test_memory_out_of_range() {
+        let mut gameboy = setup(Model::Dmg);
+        assert!(gameboy.read_memory(0x10000).is_err());
+        assert!(gameboy.write_memory(0x10000, 0).is_err());
+    }
 
-    fn run(&mut self) {
-        self.cpu.run();
+    #[test]
+    fn test_framebuffer() {
+        let mut gameboy = setup(Model::Dmg);
+        let frame = gameboy.framebuffer();
+        assert_eq!((frame.width, frame.height), (SCREEN_WIDTH, SCREEN_HEIGHT));
+        assert_eq!(frame.pixels.len(), SCREEN_WIDTH * SCREEN_HEIGHT);
+        assert!(gameboy.audio_samples().is_empty());
     }
 }
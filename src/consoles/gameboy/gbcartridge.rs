@@ -5,32 +5,65 @@ use std::ops::RangeInclusive;
 use crate::consoles::addressable::Addressable;
 use crate::consoles::bus::ReadDevice;
 use crate::consoles::readable::Readable;
+use crate::log;
 
 use super::super::cartridge::Cartridge;
 use super::super::cartridge::CartridgeNotFoundError;
 use super::instruction::Instruction;
 
+// Header byte holding the rom size, 32 KiB shifted left by its value
+const ROM_SIZE_ADDRESS: usize = 0x148;
+const MIN_ROM_SIZE: usize = 0x8000;
+
 #[derive(Debug, Clone)]
 pub struct GbCartridge {
     path: String,
     data: Vec<u8>,
     address_range: RangeInclusive<usize>,
+    // The size the header declares, the image is padded with 0xFF or mirrored to fill it
+    rom_size: usize,
 }
 
 impl GbCartridge {
     pub fn new(path: &str) -> Result<GbCartridge, Box<dyn Error>> {
         match fs::read(path) {
-            Ok(v) => Ok(GbCartridge {
-                path: path.to_string(),
-                data: v,
-                address_range: (0..=0),
-            }),
+            Ok(v) => Ok(GbCartridge::from_bytes(path, v)),
             Err(e) => Err(Box::new(CartridgeNotFoundError {
-                what: format!("{}{}", "Failed to open file: ", e.to_string()),
+                what: format!("Failed to open file: {e}"),
             })),
         }
     }
 
+    pub fn from_bytes(path: &str, data: Vec<u8>) -> GbCartridge {
+        let rom_size = match data.get(ROM_SIZE_ADDRESS) {
+            Some(&shift) if shift <= 8 => MIN_ROM_SIZE << shift,
+            _ => data.len().next_power_of_two().max(MIN_ROM_SIZE),
+        };
+        if data.len() != rom_size {
+            log!(
+                Mbc,
+                Warn,
+                "{path} is {} bytes but its header declares {rom_size} bytes",
+                data.len()
+            );
+        }
+
+        GbCartridge {
+            path: path.to_string(),
+            data,
+            address_range: (0..=0),
+            rom_size,
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn rom_size(&self) -> usize {
+        self.rom_size
+    }
+
     pub fn print(&self) {
         println!("{:#?}", self.data);
     }
@@ -43,21 +76,22 @@ impl Cartridge for GbCartridge {
 
         for i in self.data.iter() {
             if data_words > 0 {
-                dump = dump + i.to_string().as_str();
+                dump += i.to_string().as_str();
 
                 if data_words > 1 {
-                    dump = dump + ", ";
+                    dump += ", ";
                 } else {
-                    dump = dump + "\n";
+                    dump += "\n";
                 }
 
                 data_words -= 1;
-            } else if let Some(instruction) = Instruction::look_up(*i) {
-                dump = dump + Instruction::mnemonic_as_string(i).as_str() + "\t";
+            } else if let Some(instruction) = Instruction::fetch(*i, false) {
+                dump += Instruction::mnemonic_as_string(i).as_str();
+                dump += "\t";
                 data_words = (instruction.length as i8 - 1).max(0);
 
                 if data_words == 0 {
-                    dump = dump + "\n";
+                    dump += "\n";
                 }
             }
         }
@@ -75,13 +109,20 @@ impl Cartridge for GbCartridge {
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
+
+    fn into_any(self: Box<Self>) -> Box<dyn std::any::Any> {
+        self
+    }
 }
 
 impl ReadDevice<u16, u8> for GbCartridge {}
 
 impl Readable<u16, u8> for GbCartridge {
+    // Addresses past the declared size mirror the rom, the part of the rom missing from the
+    // image reads as open bus
     fn read(&self, address: u16) -> Result<u8, Box<dyn std::error::Error>> {
-        Ok(self.data[address as usize])
+        let index = address as usize % self.rom_size;
+        Ok(self.data.get(index).copied().unwrap_or(0xFF))
     }
 }
 
@@ -93,4 +134,45 @@ impl Addressable<u16> for GbCartridge {
     fn in_range(&self, address: u16) -> bool {
         self.address_range.contains(&(address as usize))
     }
+
+    fn address_ranges(&self) -> Vec<RangeInclusive<usize>> {
+        vec![self.address_range.clone()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::GbCartridge;
+    use crate::consoles::readable::Readable;
+
+    // A rom whose header declares 32 KiB << shift
+    fn rom(length: usize, shift: u8) -> GbCartridge {
+        let mut data: Vec<u8> = (0..length).map(|i| (i >> 8) as u8).collect();
+        if length > 0x148 {
+            data[0x148] = shift;
+        }
+        GbCartridge::from_bytes("test", data)
+    }
+
+    #[rstest]
+    // Smaller than declared, the rest is open bus
+    #[case(rom(0x4000, 0), 0x8000, 0x3FFF, 0x3F)]
+    #[case(rom(0x4000, 0), 0x8000, 0x4000, 0xFF)]
+    // Past the declared size the rom mirrors
+    #[case(rom(0x8000, 0), 0x8000, 0xFF00, 0x7F)]
+    // Without a header the image is rounded up to a power of two
+    #[case(rom(0x100, 0), 0x8000, 0x00FF, 0x00)]
+    #[case(rom(0x100, 0), 0x8000, 0x0100, 0xFF)]
+    #[case(rom(0x8000, 0x02), 0x20000, 0x7FFF, 0x7F)]
+    fn test_read(
+        #[case] cartridge: GbCartridge,
+        #[case] rom_size: usize,
+        #[case] address: u16,
+        #[case] expected: u8,
+    ) {
+        assert_eq!(cartridge.rom_size(), rom_size);
+        assert_eq!(cartridge.read(address).unwrap(), expected);
+    }
 }
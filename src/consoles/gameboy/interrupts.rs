@@ -0,0 +1,153 @@
+use std::error::Error;
+use std::ops::RangeInclusive;
+
+use super::io::{IoDevice, IoRegister};
+use crate::consoles::addressable::Addressable;
+use crate::consoles::bus::{ReadDevice, WriteDevice};
+use crate::consoles::memory_map::gameboy::{INTERRUPT_ENABLE_REGISTER, INTERRUPT_FLAG_REGISTER};
+use crate::consoles::readable::Readable;
+use crate::consoles::state::{SaveState, StateError, StateReader, StateWriter};
+use crate::consoles::writeable::Writeable;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interrupt {
+    VBlank,
+    LcdStat,
+    Timer,
+    Serial,
+    Joypad,
+}
+
+impl Interrupt {
+    // Ordered by priority, the lowest bit is serviced first
+    pub const ALL: [Interrupt; 5] = [
+        Interrupt::VBlank,
+        Interrupt::LcdStat,
+        Interrupt::Timer,
+        Interrupt::Serial,
+        Interrupt::Joypad,
+    ];
+
+    pub fn bit(&self) -> u8 {
+        match self {
+            Interrupt::VBlank => 1 << 0,
+            Interrupt::LcdStat => 1 << 1,
+            Interrupt::Timer => 1 << 2,
+            Interrupt::Serial => 1 << 3,
+            Interrupt::Joypad => 1 << 4,
+        }
+    }
+
+    pub fn vector(&self) -> u16 {
+        match self {
+            Interrupt::VBlank => 0x40,
+            Interrupt::LcdStat => 0x48,
+            Interrupt::Timer => 0x50,
+            Interrupt::Serial => 0x58,
+            Interrupt::Joypad => 0x60,
+        }
+    }
+
+    // The highest priority interrupt that is both requested and enabled
+    pub fn pending(flags: u8, enable: u8) -> Option<Interrupt> {
+        Interrupt::ALL
+            .into_iter()
+            .find(|interrupt| flags & enable & interrupt.bit() != 0)
+    }
+}
+
+// IF (0xFF0F) and IE (0xFFFF), shared by every device that can request an interrupt
+#[derive(Debug, Default)]
+pub struct InterruptController {
+    flags: u8,
+    enable: u8,
+}
+
+impl InterruptController {
+    pub fn new() -> InterruptController {
+        InterruptController::default()
+    }
+
+    pub fn request(&mut self, interrupt: Interrupt) {
+        self.flags |= interrupt.bit();
+    }
+}
+
+// IF, the upper three bits are unused
+impl IoDevice for InterruptController {
+    fn io_registers(&self) -> Vec<IoRegister> {
+        vec![IoRegister::new(
+            INTERRUPT_FLAG_REGISTER as u16,
+            0b11111,
+            0b11111,
+        )]
+    }
+
+    fn read_register(&self, _address: u16) -> u8 {
+        self.flags
+    }
+
+    fn write_register(&mut self, _address: u16, value: u8) {
+        self.flags = value;
+    }
+}
+
+// IE lies outside the io registers and sits on the bus directly
+impl Readable<u16, u8> for InterruptController {
+    fn read(&self, _address: u16) -> Result<u8, Box<dyn Error>> {
+        Ok(self.enable)
+    }
+}
+
+impl Writeable<u16, u8, u16> for InterruptController {
+    fn write(&mut self, _address: u16, data: u8) -> Result<(), Box<dyn Error>> {
+        self.enable = data;
+        Ok(())
+    }
+
+    fn write_16(&mut self, address: u16, data: u16) -> Result<(), Box<dyn Error>> {
+        self.write(address, data as u8)
+    }
+}
+
+impl Addressable<u16> for InterruptController {
+    fn assign_address_range(&mut self, _range: RangeInclusive<usize>) {}
+
+    fn in_range(&self, address: u16) -> bool {
+        address as usize == INTERRUPT_ENABLE_REGISTER
+    }
+
+    fn address_ranges(&self) -> Vec<RangeInclusive<usize>> {
+        vec![INTERRUPT_ENABLE_REGISTER..=INTERRUPT_ENABLE_REGISTER]
+    }
+}
+
+impl SaveState for InterruptController {
+    fn save_state(&self, out: &mut StateWriter) {
+        out.write_u8(self.flags);
+        out.write_u8(self.enable);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), StateError> {
+        self.flags = state.read_u8()?;
+        self.enable = state.read_u8()?;
+        Ok(())
+    }
+}
+
+impl ReadDevice<u16, u8> for InterruptController {}
+
+impl WriteDevice<u16, u8, u16> for InterruptController {}
+
+#[test]
+fn test_pending_priority() {
+    assert_eq!(Interrupt::pending(0b11111, 0), None);
+    assert_eq!(
+        Interrupt::pending(0b11000, 0b11111),
+        Some(Interrupt::Serial)
+    );
+    assert_eq!(
+        Interrupt::pending(0b11111, 0b10110),
+        Some(Interrupt::LcdStat)
+    );
+}
@@ -0,0 +1,207 @@
+use std::cell::RefCell;
+use std::error::Error;
+use std::ops::RangeInclusive;
+use std::rc::Rc;
+
+use crate::consoles::addressable::Addressable;
+use crate::consoles::bus::{ReadDevice, WriteDevice};
+use crate::consoles::memory_map::gameboy::IO_REGISTERS;
+use crate::consoles::readable::Readable;
+use crate::consoles::writeable::Writeable;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IoRegister {
+    pub address: u16,
+    // Bits that read back what the device holds, the others read as 1
+    pub read_mask: u8,
+    // Bits the cpu can change, the others keep the value the device holds
+    pub write_mask: u8,
+}
+
+impl IoRegister {
+    pub const fn new(address: u16, read_mask: u8, write_mask: u8) -> IoRegister {
+        IoRegister {
+            address,
+            read_mask,
+            write_mask,
+        }
+    }
+}
+
+// A hardware block with registers in 0xFF00-0xFF7F. The device only sees the values it holds,
+// the masks are applied by IoRegisters.
+pub trait IoDevice {
+    fn io_registers(&self) -> Vec<IoRegister>;
+    fn read_register(&self, address: u16) -> u8;
+    // Receives the value with the write mask applied, side effects of the write happen here
+    fn write_register(&mut self, address: u16, value: u8);
+}
+
+type Slot = (IoRegister, Rc<RefCell<dyn IoDevice>>);
+
+// Dispatches accesses to 0xFF00-0xFF7F to the device that registered the address.
+// Addresses nobody registered read as 0xFF and ignore writes.
+pub struct IoRegisters {
+    registers: Vec<Option<Slot>>,
+    address_range: RangeInclusive<usize>,
+}
+
+impl IoRegisters {
+    pub fn new() -> IoRegisters {
+        IoRegisters {
+            registers: vec![None; IO_REGISTERS.count()],
+            address_range: IO_REGISTERS,
+        }
+    }
+
+    // Registers every register of the device, a register can only belong to one device
+    pub fn connect(&mut self, device: Rc<RefCell<dyn IoDevice>>) {
+        for register in device.borrow().io_registers() {
+            let slot = self
+                .slot(register.address)
+                .and_then(|index| self.registers.get_mut(index))
+                .unwrap_or_else(|| panic!("{:#06X} is not an io register", register.address));
+            assert!(
+                slot.is_none(),
+                "{:#06X} is already registered",
+                register.address
+            );
+            *slot = Some((register, device.clone()));
+        }
+    }
+
+    fn slot(&self, address: u16) -> Option<usize> {
+        let index = (address as usize).checked_sub(*self.address_range.start())?;
+        (index < self.registers.len()).then_some(index)
+    }
+
+    fn register(&self, address: u16) -> Option<&Slot> {
+        self.registers.get(self.slot(address)?)?.as_ref()
+    }
+}
+
+impl Default for IoRegisters {
+    fn default() -> Self {
+        IoRegisters::new()
+    }
+}
+
+impl Readable<u16, u8> for IoRegisters {
+    fn read(&self, address: u16) -> Result<u8, Box<dyn Error>> {
+        Ok(match self.register(address) {
+            Some((register, device)) => {
+                device.borrow().read_register(address) | !register.read_mask
+            }
+            None => 0xFF,
+        })
+    }
+}
+
+impl Writeable<u16, u8, u16> for IoRegisters {
+    fn write(&mut self, address: u16, data: u8) -> Result<(), Box<dyn Error>> {
+        if let Some((register, device)) = self.register(address) {
+            let mut device = device.borrow_mut();
+            let kept = device.read_register(address) & !register.write_mask;
+            device.write_register(address, kept | (data & register.write_mask));
+        }
+        Ok(())
+    }
+
+    fn write_16(&mut self, address: u16, data: u16) -> Result<(), Box<dyn Error>> {
+        self.write(address, data as u8)?;
+        self.write(address.wrapping_add(1), (data >> 8) as u8)
+    }
+}
+
+impl Addressable<u16> for IoRegisters {
+    fn assign_address_range(&mut self, range: RangeInclusive<usize>) {
+        self.address_range = range;
+    }
+
+    fn in_range(&self, address: u16) -> bool {
+        self.address_range.contains(&(address as usize))
+    }
+
+    fn address_ranges(&self) -> Vec<RangeInclusive<usize>> {
+        vec![self.address_range.clone()]
+    }
+}
+
+impl ReadDevice<u16, u8> for IoRegisters {}
+
+impl WriteDevice<u16, u8, u16> for IoRegisters {}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::{IoDevice, IoRegister, IoRegisters};
+    use crate::consoles::readable::Readable;
+    use crate::consoles::writeable::Writeable;
+
+    // 0xFF10 has three unused bits, 0xFF11 has a read only low nibble and counts its writes
+    #[derive(Default)]
+    struct Device {
+        values: [u8; 2],
+        writes: usize,
+    }
+
+    impl IoDevice for Device {
+        fn io_registers(&self) -> Vec<IoRegister> {
+            vec![
+                IoRegister::new(0xFF10, 0b00011111, 0b00011111),
+                IoRegister::new(0xFF11, 0xFF, 0xF0),
+            ]
+        }
+
+        fn read_register(&self, address: u16) -> u8 {
+            self.values[(address - 0xFF10) as usize]
+        }
+
+        fn write_register(&mut self, address: u16, value: u8) {
+            self.values[(address - 0xFF10) as usize] = value;
+            self.writes += 1;
+        }
+    }
+
+    fn setup() -> (IoRegisters, Rc<RefCell<Device>>) {
+        let device = Rc::new(RefCell::new(Device::default()));
+        let mut io = IoRegisters::new();
+        io.connect(device.clone());
+        (io, device)
+    }
+
+    #[test]
+    fn test_unused_bits_read_as_one() {
+        let (mut io, device) = setup();
+        io.write(0xFF10, 0xFF).unwrap();
+        assert_eq!(device.borrow().values[0], 0b00011111);
+        io.write(0xFF10, 0x00).unwrap();
+        assert_eq!(io.read(0xFF10).unwrap(), 0b11100000);
+    }
+
+    #[test]
+    fn test_read_only_bits_keep_their_value() {
+        let (mut io, device) = setup();
+        device.borrow_mut().values[1] = 0x05;
+        io.write(0xFF11, 0xAA).unwrap();
+        assert_eq!(io.read(0xFF11).unwrap(), 0xA5);
+        assert_eq!(device.borrow().writes, 1);
+    }
+
+    #[test]
+    fn test_unregistered() {
+        let (mut io, device) = setup();
+        io.write(0xFF12, 0x00).unwrap();
+        assert_eq!(io.read(0xFF12).unwrap(), 0xFF);
+        assert_eq!(device.borrow().writes, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "0xFF10 is already registered")]
+    fn test_register_twice() {
+        let (mut io, _) = setup();
+        io.connect(Rc::new(RefCell::new(Device::default())));
+    }
+}
@@ -0,0 +1,179 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::interrupts::{Interrupt, InterruptController};
+use super::io::{IoDevice, IoRegister};
+use crate::consoles::console::Buttons;
+use crate::consoles::memory_map::gameboy::JOYPAD;
+use crate::consoles::state::{SaveState, StateError, StateReader, StateWriter};
+
+const P1: u16 = JOYPAD as u16;
+// Writing 0 to one of these selects the buttons it stands for
+const SELECT_DIRECTIONS: u8 = 1 << 4;
+const SELECT_ACTIONS: u8 = 1 << 5;
+const SELECT: u8 = SELECT_DIRECTIONS | SELECT_ACTIONS;
+
+// Right, left, up and down in the order of the lower nibble, as 1 bits
+fn directions(buttons: Buttons) -> u8 {
+    buttons.right as u8
+        | (buttons.left as u8) << 1
+        | (buttons.up as u8) << 2
+        | (buttons.down as u8) << 3
+}
+
+// A, b, select and start in the order of the lower nibble, as 1 bits
+fn actions(buttons: Buttons) -> u8 {
+    buttons.a as u8
+        | (buttons.b as u8) << 1
+        | (buttons.select as u8) << 2
+        | (buttons.start as u8) << 3
+}
+
+// P1 (0xFF00). The buttons are wired as two rows, the cpu selects rows and reads the buttons
+// held down on them as 0 bits in the lower nibble.
+pub struct Joypad {
+    select: u8,
+    buttons: Buttons,
+    interrupts: Rc<RefCell<InterruptController>>,
+}
+
+impl Joypad {
+    pub fn new(interrupts: Rc<RefCell<InterruptController>>) -> Joypad {
+        Joypad {
+            select: SELECT,
+            buttons: Buttons::default(),
+            interrupts,
+        }
+    }
+
+    pub fn set_buttons(&mut self, buttons: Buttons) {
+        self.update(|joypad| joypad.buttons = buttons);
+    }
+
+    // The held buttons on the selected rows, as 1 bits
+    fn pressed(&self) -> u8 {
+        let mut pressed = 0;
+        if self.select & SELECT_DIRECTIONS == 0 {
+            pressed |= directions(self.buttons);
+        }
+        if self.select & SELECT_ACTIONS == 0 {
+            pressed |= actions(self.buttons);
+        }
+        pressed
+    }
+
+    // Any line of the lower nibble going low requests the interrupt, whether a button went down
+    // or a row with a held button got selected
+    fn update(&mut self, change: impl FnOnce(&mut Joypad)) {
+        let before = self.pressed();
+        change(self);
+        if self.pressed() & !before != 0 {
+            self.interrupts.borrow_mut().request(Interrupt::Joypad);
+        }
+    }
+}
+
+impl IoDevice for Joypad {
+    // The upper two bits are unused
+    fn io_registers(&self) -> Vec<IoRegister> {
+        vec![IoRegister::new(P1, 0b0011_1111, SELECT)]
+    }
+
+    fn read_register(&self, _address: u16) -> u8 {
+        self.select | (!self.pressed() & 0x0F)
+    }
+
+    fn write_register(&mut self, _address: u16, value: u8) {
+        self.update(|joypad| joypad.select = value & SELECT);
+    }
+}
+
+// The buttons are input, they stay the way the frontend last set them
+impl SaveState for Joypad {
+    fn save_state(&self, out: &mut StateWriter) {
+        out.write_u8(self.select);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), StateError> {
+        self.select = state.read_u8()? & SELECT;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use rstest::rstest;
+
+    use super::{Joypad, P1};
+    use crate::consoles::console::Buttons;
+    use crate::consoles::gameboy::interrupts::{Interrupt, InterruptController};
+    use crate::consoles::gameboy::io::{IoDevice, IoRegisters};
+    use crate::consoles::readable::Readable;
+    use crate::consoles::writeable::Writeable;
+
+    fn setup() -> (
+        Rc<RefCell<Joypad>>,
+        IoRegisters,
+        Rc<RefCell<InterruptController>>,
+    ) {
+        let interrupts = Rc::new(RefCell::new(InterruptController::new()));
+        let joypad = Rc::new(RefCell::new(Joypad::new(interrupts.clone())));
+        let mut io = IoRegisters::new();
+        io.connect(joypad.clone());
+        (joypad, io, interrupts)
+    }
+
+    fn joypad_requested(interrupts: &Rc<RefCell<InterruptController>>) -> bool {
+        interrupts.borrow().read_register(0xFF0F) & Interrupt::Joypad.bit() != 0
+    }
+
+    #[rstest]
+    // Nothing selected reads as nothing held
+    #[case(0x30, Buttons { a: true, right: true, ..Default::default() }, 0xFF)]
+    #[case(0x20, Buttons { right: true, down: true, ..Default::default() }, 0xE6)]
+    #[case(0x20, Buttons { a: true, ..Default::default() }, 0xEF)]
+    #[case(0x10, Buttons { a: true, start: true, up: true, ..Default::default() }, 0xD6)]
+    // Both rows at once
+    #[case(0x00, Buttons { b: true, left: true, ..Default::default() }, 0xCD)]
+    fn test_read(#[case] select: u8, #[case] buttons: Buttons, #[case] expected: u8) {
+        let (joypad, mut io, _) = setup();
+        joypad.borrow_mut().set_buttons(buttons);
+        io.write(P1, select).unwrap();
+        assert_eq!(io.read(P1).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_press_on_selected_row_requests_interrupt() {
+        let (joypad, mut io, interrupts) = setup();
+        io.write(P1, 0x10).unwrap();
+
+        joypad.borrow_mut().set_buttons(Buttons {
+            up: true,
+            ..Default::default()
+        });
+        assert!(!joypad_requested(&interrupts));
+
+        joypad.borrow_mut().set_buttons(Buttons {
+            up: true,
+            start: true,
+            ..Default::default()
+        });
+        assert!(joypad_requested(&interrupts));
+    }
+
+    #[test]
+    fn test_selecting_held_row_requests_interrupt() {
+        let (joypad, mut io, interrupts) = setup();
+        joypad.borrow_mut().set_buttons(Buttons {
+            left: true,
+            ..Default::default()
+        });
+        assert!(!joypad_requested(&interrupts));
+
+        io.write(P1, 0x20).unwrap();
+        assert!(joypad_requested(&interrupts));
+    }
+}
@@ -1,10 +1,31 @@
+mod alu;
+pub mod backend;
+mod block_cache;
+pub mod clock;
+#[cfg(feature = "debugger")]
+pub mod conformance;
 mod cpu;
+#[cfg(feature = "debugger")]
+pub mod disassembler;
+pub mod execution;
 pub mod game_boy;
 pub mod gbcartridge;
 mod instruction;
+pub mod interrupts;
+pub mod io;
+pub mod joypad;
+pub mod model;
 mod opcode;
 mod registers;
+pub mod serial;
+#[cfg(feature = "debugger")]
+pub mod single_step;
 mod target;
+pub mod trace;
+#[cfg(feature = "debugger")]
+pub mod trace_diff;
+mod unusable;
 
+pub use cpu::{CpuError, CpuFault, LockUp};
 pub use instruction::Instruction as GbInstruction;
 pub use opcode::OpCode as GbOpCode;
@@ -0,0 +1,49 @@
+use crate::consoles::memory::PowerOnPattern;
+
+// The hardware being emulated, only consulted where the models behave differently
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Model {
+    #[default]
+    Dmg,
+    // Modelled after revision E and later, earlier revisions differ in undocumented behaviour
+    Cgb,
+}
+
+impl Model {
+    // Ram comes up in a different random state on every model, the seed keeps runs reproducible
+    pub fn power_on_pattern(&self) -> PowerOnPattern<u8> {
+        match self {
+            Model::Dmg => PowerOnPattern::Random(0x9F3A_61C7_0D2B_84E5),
+            Model::Cgb => PowerOnPattern::Random(0x5C1E_B7A2_3F90_D468),
+        }
+    }
+
+    // What a read of 0xFEA0-0xFEFF returns while the ppu does not block the oam
+    pub fn unusable_read(&self, address: u16) -> u8 {
+        match self {
+            Model::Dmg => 0x00,
+            // The high nibble of the lower address byte, twice. FEAx reads 0xAA.
+            Model::Cgb => {
+                let nibble = (address as u8) >> 4;
+                (nibble << 4) | nibble
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::Model;
+
+    #[rstest]
+    #[case(Model::Dmg, 0xFEA0, 0x00)]
+    #[case(Model::Dmg, 0xFEFF, 0x00)]
+    #[case(Model::Cgb, 0xFEA0, 0xAA)]
+    #[case(Model::Cgb, 0xFEB7, 0xBB)]
+    #[case(Model::Cgb, 0xFEFF, 0xFF)]
+    fn test_unusable_read(#[case] model: Model, #[case] address: u16, #[case] expected: u8) {
+        assert_eq!(model.unusable_read(address), expected);
+    }
+}
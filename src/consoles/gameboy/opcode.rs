@@ -17,12 +17,13 @@ pub enum OpCode {
     CCF,        //Toggle value of carry flag
     CP(Target), // like sub but result not stored back into a
     CPL,
-    DDA,
+    DAA,
     DEC(Target),
     DEC16(Target),
     DisableInterrupt,
     EnableInterrupt,
     HALT,
+    ILLEGAL, // fills the table slots of undefined opcodes
     INC(Target),
     INC16(Target),
     JUMP(Flag),
@@ -63,10 +64,6 @@ pub enum OpCode {
     SWAP(Target), //swap upper and lower nibble
 
     XOR(Target),
-    EndOfProgram,
-
-    STORE(Target, Target), // These instructions do not exist for the game boy and are only used as convenience instructions
-    STORE16(Target, Target), // First arg dst address 16 bit, seconds arg src direct value 8 / 16 bit
 }
 
 impl Display for OpCode {
@@ -83,12 +80,13 @@ impl Display for OpCode {
             Self::CCF => "CCF",
             Self::CP(_) => "CP",
             Self::CPL => "CPL",
-            Self::DDA => "DDA",
+            Self::DAA => "DAA",
             Self::DEC(_) => "DEC",
             Self::DEC16(_) => "DEC16",
             Self::DisableInterrupt => "DisableInterrupt",
             Self::EnableInterrupt => "EnableInterrupt",
             Self::HALT => "HALT",
+            Self::ILLEGAL => "ILLEGAL",
             Self::INC(_) => "INC",
             Self::INC16(_) => "INC16",
             Self::JUMP(_) => "JUMP",
@@ -128,9 +126,6 @@ impl Display for OpCode {
             Self::SUB(_) => "SUB",
             Self::SWAP(_) => "SWAP",
             Self::XOR(_) => "XOR",
-            Self::EndOfProgram => "EndOfProgram",
-            Self::STORE(_, _) => "STORE",
-            Self::STORE16(_, _) => "STORE16",
         };
 
         write!(f, "{s}")
@@ -1,5 +1,6 @@
 use std::fmt::Display;
 
+use super::cpu::CpuError;
 use super::target::Target;
 use crate::utils::conversion;
 
@@ -32,6 +33,21 @@ pub enum Flag {
     NotSub,
 }
 
+impl Flag {
+    pub fn negated(&self) -> Flag {
+        match self {
+            Self::Zero => Self::NotZero,
+            Self::Carry => Self::NotCarry,
+            Self::HalfCarry => Self::NotHalfCarry,
+            Self::Sub => Self::NotSub,
+            Self::NotZero => Self::Zero,
+            Self::NotCarry => Self::Carry,
+            Self::NotHalfCarry => Self::HalfCarry,
+            Self::NotSub => Self::Sub,
+        }
+    }
+}
+
 impl Display for Flag {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let s = match self {
@@ -48,6 +64,14 @@ impl Display for Flag {
     }
 }
 
+fn not_8bit(reg: Target) -> CpuError {
+    CpuError::UnsupportedOperand(format!("{reg} is not an 8 bit register"))
+}
+
+fn not_16bit(reg: Target) -> CpuError {
+    CpuError::UnsupportedOperand(format!("{reg} is not a 16 bit register"))
+}
+
 impl Registers {
     pub fn new() -> Registers {
         Registers {
@@ -81,28 +105,25 @@ impl Registers {
             Flag::Carry => (self.f & (1 << CARRY_BIT_POS)) >> CARRY_BIT_POS,
             Flag::HalfCarry => (self.f & (1 << HALF_CARRY_BIT_POS)) >> HALF_CARRY_BIT_POS,
             Flag::Sub => (self.f & (1 << SUB_BIT_POS)) >> SUB_BIT_POS,
-            _ => {
-                panic!("Unimplemented");
-            }
+            _ => self.filter_flag(flag.negated()) ^ 1,
         }
     }
 
+    // Setting a negated flag, e.g. NotZero, clears the underlying flag
     pub fn set_flag(&mut self, flag: Flag, v: bool) {
         let mut mask = match flag {
             Flag::Zero => 1 << ZERO_BIT_POS,
             Flag::Carry => 1 << CARRY_BIT_POS,
             Flag::HalfCarry => 1 << HALF_CARRY_BIT_POS,
             Flag::Sub => 1 << SUB_BIT_POS,
-            _ => {
-                panic!("Invalid flag as Input!");
-            }
+            _ => return self.set_flag(flag.negated(), !v),
         };
 
         if !v {
             mask = !mask;
-            self.f = self.f & mask;
+            self.f &= mask;
         } else {
-            self.f = self.f | mask;
+            self.f |= mask;
         }
     }
 
@@ -114,24 +135,22 @@ impl Registers {
         }
     }
 
-    pub fn get_bit(&self, reg: Target, bit: &u32) -> bool {
+    pub fn get_bit(&self, reg: Target, bit: &u32) -> Result<bool, CpuError> {
         let mask = 1 << bit;
         match reg {
-            Target::A => (self.a & mask) >= 1,
-            Target::B => (self.b & mask) >= 1,
-            Target::C => (self.c & mask) >= 1,
-            Target::D => (self.d & mask) >= 1,
-            Target::E => (self.e & mask) >= 1,
-            Target::F => (self.f & mask) >= 1,
-            Target::L => (self.l & mask) >= 1,
-            Target::H => (self.h & mask) >= 1,
-            _ => {
-                panic!("Unimplemented");
-            }
+            Target::A => Ok((self.a & mask) >= 1),
+            Target::B => Ok((self.b & mask) >= 1),
+            Target::C => Ok((self.c & mask) >= 1),
+            Target::D => Ok((self.d & mask) >= 1),
+            Target::E => Ok((self.e & mask) >= 1),
+            Target::F => Ok((self.f & mask) >= 1),
+            Target::L => Ok((self.l & mask) >= 1),
+            Target::H => Ok((self.h & mask) >= 1),
+            _ => Err(not_8bit(reg)),
         }
     }
 
-    pub fn set_bit(&mut self, reg: Target, bit: &u32, v: u8) {
+    pub fn set_bit(&mut self, reg: Target, bit: &u32, v: u8) -> Result<(), CpuError> {
         if v == 1 {
             let mask = v << bit;
             match reg {
@@ -143,13 +162,10 @@ impl Registers {
                 Target::F => self.f |= mask,
                 Target::L => self.l |= mask,
                 Target::H => self.h |= mask,
-                _ => {
-                    panic!("Unimplemented");
-                }
+                _ => return Err(not_8bit(reg)),
             }
         } else {
-            let mut mask = 0b1111111;
-            mask = mask ^ (1 << bit);
+            let mask = !(1 << bit);
             match reg {
                 Target::A => self.a &= mask,
                 Target::B => self.b &= mask,
@@ -159,27 +175,26 @@ impl Registers {
                 Target::F => self.f &= mask,
                 Target::L => self.l &= mask,
                 Target::H => self.h &= mask,
-                _ => {
-                    panic!("Unimplemented");
-                }
+                _ => return Err(not_8bit(reg)),
             }
         }
+        Ok(())
     }
 
-    pub fn get_register(&self, reg: Target) -> u8 {
+    pub fn get_register(&self, reg: Target) -> Result<u8, CpuError> {
         match reg {
-            Target::A => self.a,
-            Target::B => self.b,
-            Target::C => self.c,
-            Target::D => self.d,
-            Target::E => self.e,
-            Target::H => self.h,
-            Target::L => self.l,
-            _ => panic!("Invalid 8 bit register requesterd!"),
+            Target::A => Ok(self.a),
+            Target::B => Ok(self.b),
+            Target::C => Ok(self.c),
+            Target::D => Ok(self.d),
+            Target::E => Ok(self.e),
+            Target::H => Ok(self.h),
+            Target::L => Ok(self.l),
+            _ => Err(not_8bit(reg)),
         }
     }
 
-    pub fn set_register(&mut self, reg: Target, value: u8) {
+    pub fn set_register(&mut self, reg: Target, value: u8) -> Result<(), CpuError> {
         match reg {
             Target::A => self.a = value,
             Target::B => self.b = value,
@@ -188,22 +203,22 @@ impl Registers {
             Target::E => self.e = value,
             Target::H => self.h = value,
             Target::L => self.l = value,
-            _ => panic!("Invalid 8 bit register requesterd!"),
+            _ => return Err(not_8bit(reg)),
         }
+        Ok(())
     }
-    pub fn combined_register(&self, reg: Target) -> u16 {
+
+    pub fn combined_register(&self, reg: Target) -> Result<u16, CpuError> {
         match reg {
-            Target::HL => ((self.h as u16) << 8) | self.l as u16,
-            Target::BC => ((self.b as u16) << 8) | self.c as u16,
-            Target::DE => ((self.d as u16) << 8) | self.e as u16,
-            Target::AF => ((self.a as u16) << 8) | self.f as u16,
-            _ => {
-                panic!("Unimplemented {:#?}", reg);
-            }
+            Target::HL => Ok(((self.h as u16) << 8) | self.l as u16),
+            Target::BC => Ok(((self.b as u16) << 8) | self.c as u16),
+            Target::DE => Ok(((self.d as u16) << 8) | self.e as u16),
+            Target::AF => Ok(((self.a as u16) << 8) | self.f as u16),
+            _ => Err(not_16bit(reg)),
         }
     }
 
-    pub fn set_combined_register(&mut self, reg: Target, v: u16) {
+    pub fn set_combined_register(&mut self, reg: Target, v: u16) -> Result<(), CpuError> {
         match reg {
             Target::HL => {
                 self.h = ((v & 0b1111111100000000) >> 8) as u8;
@@ -221,41 +236,36 @@ impl Registers {
                 self.a = ((v & 0b1111111100000000) >> 8) as u8;
                 self.f = (v & 0b11111111) as u8;
             }
-            _ => {
-                panic!("Unimplemented {:#?}", reg);
-            }
+            _ => return Err(not_16bit(reg)),
         }
+        Ok(())
     }
 
-    pub fn register_as_bit_string(&self, reg: Target) -> String {
+    pub fn register_as_bit_string(&self, reg: Target) -> Result<String, CpuError> {
         match reg {
-            Target::A => conversion::u8_as_bit_string(self.a),
-            Target::B => conversion::u8_as_bit_string(self.b),
-            Target::C => conversion::u8_as_bit_string(self.c),
-            Target::D => conversion::u8_as_bit_string(self.d),
-            Target::E => conversion::u8_as_bit_string(self.e),
-            Target::F => conversion::u8_as_bit_string(self.f),
-            Target::L => conversion::u8_as_bit_string(self.l),
-            Target::H => conversion::u8_as_bit_string(self.h),
-            _ => {
-                panic!("Unimplemented");
-            }
+            Target::A => Ok(conversion::u8_as_bit_string(self.a)),
+            Target::B => Ok(conversion::u8_as_bit_string(self.b)),
+            Target::C => Ok(conversion::u8_as_bit_string(self.c)),
+            Target::D => Ok(conversion::u8_as_bit_string(self.d)),
+            Target::E => Ok(conversion::u8_as_bit_string(self.e)),
+            Target::F => Ok(conversion::u8_as_bit_string(self.f)),
+            Target::L => Ok(conversion::u8_as_bit_string(self.l)),
+            Target::H => Ok(conversion::u8_as_bit_string(self.h)),
+            _ => Err(not_8bit(reg)),
         }
     }
 
-    pub fn register_as_hex_string(&self, reg: Target) -> String {
+    pub fn register_as_hex_string(&self, reg: Target) -> Result<String, CpuError> {
         match reg {
-            Target::A => conversion::u8_as_hex_string(self.a),
-            Target::B => conversion::u8_as_hex_string(self.b),
-            Target::C => conversion::u8_as_hex_string(self.c),
-            Target::D => conversion::u8_as_hex_string(self.d),
-            Target::E => conversion::u8_as_hex_string(self.e),
-            Target::F => conversion::u8_as_hex_string(self.f),
-            Target::L => conversion::u8_as_hex_string(self.l),
-            Target::H => conversion::u8_as_hex_string(self.h),
-            _ => {
-                panic!("Unimplemented");
-            }
+            Target::A => Ok(conversion::u8_as_hex_string(self.a)),
+            Target::B => Ok(conversion::u8_as_hex_string(self.b)),
+            Target::C => Ok(conversion::u8_as_hex_string(self.c)),
+            Target::D => Ok(conversion::u8_as_hex_string(self.d)),
+            Target::E => Ok(conversion::u8_as_hex_string(self.e)),
+            Target::F => Ok(conversion::u8_as_hex_string(self.f)),
+            Target::L => Ok(conversion::u8_as_hex_string(self.l)),
+            Target::H => Ok(conversion::u8_as_hex_string(self.h)),
+            _ => Err(not_8bit(reg)),
         }
     }
 
@@ -271,10 +281,7 @@ impl Registers {
     }
 
     pub fn is_16bit_target(&self, reg: Target) -> bool {
-        match reg {
-            Target::SP | Target::SP_R8 | Target::D16 => return true,
-            _ => return false,
-        }
+        matches!(reg, Target::SP | Target::SP_R8 | Target::D16)
     }
 }
 
@@ -282,22 +289,46 @@ impl Registers {
 fn test_bit() {
     let mut reg = Registers::new();
     reg.a = 1;
-    assert!(reg.get_bit(Target::A, &0));
+    assert!(reg.get_bit(Target::A, &0).unwrap());
 
     reg.a = 2;
-    assert!(!reg.get_bit(Target::A, &0));
-    assert!(reg.get_bit(Target::A, &1));
+    assert!(!reg.get_bit(Target::A, &0).unwrap());
+    assert!(reg.get_bit(Target::A, &1).unwrap());
+}
+
+#[test]
+fn test_negated_flags() {
+    let mut reg = Registers::new();
+    reg.set_flag(Flag::NotZero, false);
+    assert!(reg.get_flag(Flag::Zero));
+    assert_eq!(reg.filter_flag(Flag::NotZero), 0);
+
+    reg.set_flag(Flag::NotZero, true);
+    assert!(!reg.get_flag(Flag::Zero));
+    assert_eq!(reg.filter_flag(Flag::NotZero), 1);
+}
+
+#[test]
+fn test_invalid_register() {
+    let mut reg = Registers::new();
+    assert!(reg.get_register(Target::HL).is_err());
+    assert!(reg.set_register(Target::SP, 0).is_err());
+    assert!(reg.combined_register(Target::A).is_err());
+    assert!(reg.set_combined_register(Target::D8, 0).is_err());
 }
 
 #[test]
 fn test_set_bit() {
     let mut reg = Registers::new();
-    reg.set_bit(Target::A, &0, 1);
+    reg.set_bit(Target::A, &0, 1).unwrap();
     assert!(reg.a == 1);
-    reg.set_bit(Target::A, &2, 1);
+    reg.set_bit(Target::A, &2, 1).unwrap();
     assert!(reg.a == 5);
-    reg.set_bit(Target::A, &0, 0);
+    reg.set_bit(Target::A, &0, 0).unwrap();
     assert!(reg.a == 4);
+    reg.set_bit(Target::A, &7, 1).unwrap();
+    reg.set_bit(Target::A, &2, 0).unwrap();
+    assert!(reg.a == 0x80);
 }
 
 impl Display for Registers {
@@ -316,7 +347,7 @@ fn test_convert_to_bit_string() {
 
     reg.a = 5;
 
-    assert!(reg.register_as_bit_string(Target::A).as_str().as_bytes() == "0b00000101".as_bytes());
+    assert!(reg.register_as_bit_string(Target::A).unwrap().as_bytes() == "0b00000101".as_bytes());
 }
 
 #[test]
@@ -325,9 +356,9 @@ fn test_convert_to_hex_string() {
 
     reg.a = 128;
 
-    assert!(reg.register_as_hex_string(Target::A).as_str().as_bytes() == "0x80".as_bytes());
+    assert!(reg.register_as_hex_string(Target::A).unwrap().as_bytes() == "0x80".as_bytes());
 
     reg.a = 128 + 15;
 
-    assert!(reg.register_as_hex_string(Target::A).as_str().as_bytes() == "0x8F".as_bytes());
+    assert!(reg.register_as_hex_string(Target::A).unwrap().as_bytes() == "0x8F".as_bytes());
 }
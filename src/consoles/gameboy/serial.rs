@@ -0,0 +1,280 @@
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+use super::clock::Clocked;
+use super::interrupts::{Interrupt, InterruptController};
+use super::io::{IoDevice, IoRegister};
+use crate::consoles::memory_map::gameboy::SERIAL_TRANSFER;
+use crate::consoles::state::{SaveState, StateError, StateReader, StateWriter};
+
+const SB: u16 = *SERIAL_TRANSFER.start() as u16;
+const SC: u16 = *SERIAL_TRANSFER.end() as u16;
+const TRANSFER_START: u8 = 1 << 7;
+const INTERNAL_CLOCK: u8 = 1 << 0;
+// The internal clock runs at 8192 Hz, so shifting out 8 bits takes 8 * 512 T-cycles
+const CYCLES_PER_TRANSFER: u32 = 8 * 512;
+
+// The other end of the link cable. exchange is called once the side driving the clock has
+// shifted out a whole byte and returns the byte shifted in from the other end.
+pub trait SerialEndpoint {
+    fn exchange(&mut self, data: u8) -> u8;
+}
+
+// Nothing plugged in, the data line is pulled high
+pub struct Disconnected;
+
+impl SerialEndpoint for Disconnected {
+    fn exchange(&mut self, _data: u8) -> u8 {
+        0xFF
+    }
+}
+
+// Collects everything sent over serial, which is how test roms such as blargg's report results
+#[derive(Clone, Default)]
+pub struct SerialCapture {
+    output: Rc<RefCell<Vec<u8>>>,
+}
+
+impl SerialCapture {
+    pub fn new() -> SerialCapture {
+        SerialCapture::default()
+    }
+
+    pub fn bytes(&self) -> Vec<u8> {
+        self.output.borrow().clone()
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.output.borrow()).into_owned()
+    }
+}
+
+impl SerialEndpoint for SerialCapture {
+    fn exchange(&mut self, data: u8) -> u8 {
+        self.output.borrow_mut().push(data);
+        0xFF
+    }
+}
+
+// One end of an in-process link cable, see Serial::link
+pub struct LinkPort {
+    peer: Weak<RefCell<Serial>>,
+}
+
+impl SerialEndpoint for LinkPort {
+    fn exchange(&mut self, data: u8) -> u8 {
+        match self.peer.upgrade() {
+            Some(peer) => peer.borrow_mut().receive(data),
+            None => 0xFF,
+        }
+    }
+}
+
+// SB (0xFF01) and SC (0xFF02)
+pub struct Serial {
+    data: u8,
+    control: u8,
+    cycles_left: u32,
+    endpoint: Box<dyn SerialEndpoint>,
+    interrupts: Rc<RefCell<InterruptController>>,
+}
+
+impl Serial {
+    pub fn new(interrupts: Rc<RefCell<InterruptController>>) -> Serial {
+        Serial {
+            data: 0,
+            control: 0,
+            cycles_left: 0,
+            endpoint: Box::new(Disconnected),
+            interrupts,
+        }
+    }
+
+    pub fn connect(&mut self, endpoint: Box<dyn SerialEndpoint>) {
+        self.endpoint = endpoint;
+    }
+
+    // Plugs a and b into each other, whichever side uses its internal clock drives the transfer
+    pub fn link(a: &Rc<RefCell<Serial>>, b: &Rc<RefCell<Serial>>) {
+        a.borrow_mut().connect(Box::new(LinkPort {
+            peer: Rc::downgrade(b),
+        }));
+        b.borrow_mut().connect(Box::new(LinkPort {
+            peer: Rc::downgrade(a),
+        }));
+    }
+
+    fn is_transferring(&self) -> bool {
+        self.control & TRANSFER_START != 0
+    }
+
+    fn uses_internal_clock(&self) -> bool {
+        self.control & INTERNAL_CLOCK != 0
+    }
+
+    fn finish_transfer(&mut self) {
+        self.control &= !TRANSFER_START;
+        self.interrupts.borrow_mut().request(Interrupt::Serial);
+    }
+
+    // Called when the other end drives the clock. The byte is shifted in even when no transfer
+    // was requested, but only a requested transfer completes and raises the interrupt.
+    fn receive(&mut self, data: u8) -> u8 {
+        let sent = self.data;
+        self.data = data;
+        if self.is_transferring() && !self.uses_internal_clock() {
+            self.finish_transfer();
+        }
+        sent
+    }
+}
+
+impl Clocked for Serial {
+    // Only a transfer driven by the internal clock advances
+    fn tick(&mut self, cycles: u32) {
+        if !self.is_transferring() || !self.uses_internal_clock() {
+            return;
+        }
+
+        self.cycles_left = self.cycles_left.saturating_sub(cycles);
+        if self.cycles_left == 0 {
+            self.data = self.endpoint.exchange(self.data);
+            self.finish_transfer();
+        }
+    }
+}
+
+impl IoDevice for Serial {
+    fn io_registers(&self) -> Vec<IoRegister> {
+        vec![
+            IoRegister::new(SB, 0xFF, 0xFF),
+            // Bits 1 - 6 of SC are unused
+            IoRegister::new(
+                SC,
+                TRANSFER_START | INTERNAL_CLOCK,
+                TRANSFER_START | INTERNAL_CLOCK,
+            ),
+        ]
+    }
+
+    fn read_register(&self, address: u16) -> u8 {
+        match address {
+            SB => self.data,
+            _ => self.control,
+        }
+    }
+
+    fn write_register(&mut self, address: u16, value: u8) {
+        match address {
+            SB => self.data = value,
+            _ => {
+                self.control = value;
+                if self.is_transferring() && self.uses_internal_clock() {
+                    self.cycles_left = CYCLES_PER_TRANSFER;
+                }
+            }
+        }
+    }
+}
+
+// Whatever is plugged into the port stays plugged in
+impl SaveState for Serial {
+    fn save_state(&self, out: &mut StateWriter) {
+        out.write_u8(self.data);
+        out.write_u8(self.control);
+        out.write_u32(self.cycles_left);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), StateError> {
+        self.data = state.read_u8()?;
+        self.control = state.read_u8()?;
+        self.cycles_left = state.read_u32()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::consoles::{
+        gameboy::{
+            clock::Clocked,
+            interrupts::{Interrupt, InterruptController},
+            io::{IoDevice, IoRegisters},
+        },
+        readable::Readable,
+        writeable::Writeable,
+    };
+
+    use super::{CYCLES_PER_TRANSFER, SB, SC, Serial, SerialCapture};
+
+    fn setup() -> (
+        Rc<RefCell<Serial>>,
+        IoRegisters,
+        Rc<RefCell<InterruptController>>,
+    ) {
+        let interrupts = Rc::new(RefCell::new(InterruptController::new()));
+        let serial = Rc::new(RefCell::new(Serial::new(interrupts.clone())));
+        let mut io = IoRegisters::new();
+        io.connect(serial.clone());
+        (serial, io, interrupts)
+    }
+
+    fn serial_requested(interrupts: &Rc<RefCell<InterruptController>>) -> bool {
+        interrupts.borrow().read_register(0xFF0F) & Interrupt::Serial.bit() != 0
+    }
+
+    #[test]
+    fn test_internal_clock_transfer() {
+        let (serial, mut io, interrupts) = setup();
+        let capture = SerialCapture::new();
+        serial.borrow_mut().connect(Box::new(capture.clone()));
+
+        io.write(SB, b'P').unwrap();
+        io.write(SC, 0x81).unwrap();
+        assert_eq!(io.read(SC).unwrap(), 0xFF);
+
+        serial.borrow_mut().tick(CYCLES_PER_TRANSFER - 1);
+        assert!(capture.bytes().is_empty());
+        assert!(!serial_requested(&interrupts));
+
+        serial.borrow_mut().tick(1);
+        assert_eq!(capture.text(), "P");
+        assert_eq!(io.read(SB).unwrap(), 0xFF);
+        assert_eq!(io.read(SC).unwrap(), 0x7F);
+        assert!(serial_requested(&interrupts));
+    }
+
+    #[test]
+    fn test_external_clock_waits_for_peer() {
+        let (serial, mut io, interrupts) = setup();
+        io.write(SB, 0x42).unwrap();
+        io.write(SC, 0x80).unwrap();
+
+        serial.borrow_mut().tick(CYCLES_PER_TRANSFER * 4);
+        assert_eq!(io.read(SB).unwrap(), 0x42);
+        assert_eq!(io.read(SC).unwrap(), 0xFE);
+        assert!(!serial_requested(&interrupts));
+    }
+
+    #[test]
+    fn test_link() {
+        let (master, mut master_io, master_interrupts) = setup();
+        let (slave, mut slave_io, slave_interrupts) = setup();
+        Serial::link(&master, &slave);
+
+        slave_io.write(SB, 0x55).unwrap();
+        slave_io.write(SC, 0x80).unwrap();
+        master_io.write(SB, 0xAA).unwrap();
+        master_io.write(SC, 0x81).unwrap();
+
+        master.borrow_mut().tick(CYCLES_PER_TRANSFER);
+
+        assert_eq!(master_io.read(SB).unwrap(), 0x55);
+        assert_eq!(slave_io.read(SB).unwrap(), 0xAA);
+        assert_eq!(slave_io.read(SC).unwrap(), 0x7E);
+        assert!(serial_requested(&master_interrupts));
+        assert!(serial_requested(&slave_interrupts));
+    }
+}
@@ -0,0 +1,642 @@
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt::Display;
+use std::fs;
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::rc::Rc;
+
+use serde::Deserialize;
+
+use super::clock::Clocked;
+use super::cpu::{Cpu, CpuError};
+use super::execution::Steppable;
+use super::instruction::Instruction;
+use super::trace::TraceLine;
+use crate::consoles::addressable::Addressable;
+use crate::consoles::bus::{Bus, ReadDevice, WriteDevice};
+use crate::consoles::readable::Readable;
+use crate::consoles::writeable::Writeable;
+
+// Runs the SingleStepTests sm83 vectors (https://github.com/SingleStepTests/sm83). Every json
+// file holds the test cases of one opcode, each with the state before and after executing it
+// and what the cpu put on the bus in every M-cycle.
+//
+// The vectors are recorded with the fetch of the next opcode overlapping the current
+// instruction: the opcode at pc - 1 has already been fetched when a case starts and its last
+// cycle fetches the opcode after the instruction, so their pc is one ahead of ours on both ends.
+// Our fetch of the opcode is the M-cycle before their first one.
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TestState {
+    pub pc: u16,
+    pub sp: u16,
+    pub a: u8,
+    pub b: u8,
+    pub c: u8,
+    pub d: u8,
+    pub e: u8,
+    pub f: u8,
+    pub h: u8,
+    pub l: u8,
+    #[serde(default)]
+    pub ime: u8,
+    #[serde(default)]
+    pub ie: Option<u8>,
+    pub ram: Vec<(u16, u8)>,
+}
+
+impl TestState {
+    fn trace_line(&self) -> TraceLine {
+        TraceLine {
+            a: self.a,
+            f: self.f,
+            b: self.b,
+            c: self.c,
+            d: self.d,
+            e: self.e,
+            h: self.h,
+            l: self.l,
+            sp: self.sp,
+            pc: self.pc,
+            pc_mem: [0; 4],
+        }
+    }
+}
+
+// Address, data and the r/w/m pins, any of them null while the bus is idle
+type Cycle = (Option<u16>, Option<u8>, String);
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TestCase {
+    pub name: String,
+    pub initial: TestState,
+    #[serde(rename = "final")]
+    pub expected: TestState,
+    pub cycles: Vec<Option<Cycle>>,
+}
+
+impl TestCase {
+    // The reads and writes in the order the cpu made them along with the M-cycle they were
+    // made in, idle cycles left out
+    pub fn bus_activity(&self) -> Vec<(usize, BusAccess)> {
+        self.cycles
+            .iter()
+            .enumerate()
+            .filter_map(|(index, cycle)| {
+                let (address, data, pins) = cycle.as_ref()?;
+                match (address, data, pins.as_bytes()) {
+                    (Some(address), Some(data), [b'r', ..]) => {
+                        Some((index, BusAccess::Read(*address, *data)))
+                    }
+                    (Some(address), Some(data), [_, b'w', ..]) => {
+                        Some((index, BusAccess::Write(*address, *data)))
+                    }
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BusAccess {
+    Read(u16, u8),
+    Write(u16, u8),
+}
+
+impl Display for BusAccess {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BusAccess::Read(address, data) => write!(f, "read {data:#04X} from {address:#06X}"),
+            BusAccess::Write(address, data) => write!(f, "write {data:#04X} to {address:#06X}"),
+        }
+    }
+}
+
+// Plain ram over the whole address space, recording every access and the M-cycle it was made
+// in while asked to
+struct FlatMemory {
+    data: Vec<u8>,
+    recording: bool,
+    cycles: u64,
+    accesses: RefCell<Vec<(u64, BusAccess)>>,
+}
+
+impl FlatMemory {
+    fn new() -> FlatMemory {
+        FlatMemory {
+            data: vec![0; 0x10000],
+            recording: false,
+            cycles: 0,
+            accesses: RefCell::new(vec![]),
+        }
+    }
+
+    fn record(&self, access: BusAccess) {
+        if self.recording {
+            self.accesses.borrow_mut().push((self.cycles / 4, access));
+        }
+    }
+}
+
+impl Clocked for FlatMemory {
+    fn tick(&mut self, cycles: u32) {
+        self.cycles += cycles as u64;
+    }
+}
+
+impl Readable<u16, u8> for FlatMemory {
+    fn read(&self, address: u16) -> Result<u8, Box<dyn Error>> {
+        let data = self.data[address as usize];
+        self.record(BusAccess::Read(address, data));
+        Ok(data)
+    }
+}
+
+impl Writeable<u16, u8, u16> for FlatMemory {
+    fn write(&mut self, address: u16, data: u8) -> Result<(), Box<dyn Error>> {
+        self.data[address as usize] = data;
+        self.record(BusAccess::Write(address, data));
+        Ok(())
+    }
+
+    fn write_16(&mut self, address: u16, data: u16) -> Result<(), Box<dyn Error>> {
+        self.write(address, data as u8)?;
+        self.write(address.wrapping_add(1), (data >> 8) as u8)
+    }
+}
+
+impl Addressable<u16> for FlatMemory {
+    fn assign_address_range(&mut self, _range: RangeInclusive<usize>) {}
+
+    fn in_range(&self, _address: u16) -> bool {
+        true
+    }
+
+    fn address_ranges(&self) -> Vec<RangeInclusive<usize>> {
+        vec![0x0000..=0xFFFF]
+    }
+}
+
+impl ReadDevice<u16, u8> for FlatMemory {}
+
+impl WriteDevice<u16, u8, u16> for FlatMemory {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mismatch {
+    Error(CpuError),
+    Register {
+        name: &'static str,
+        expected: u16,
+        actual: u16,
+    },
+    Ime {
+        expected: bool,
+        actual: bool,
+    },
+    Ram {
+        address: u16,
+        expected: u8,
+        actual: u8,
+    },
+    Cycles {
+        expected: u64,
+        actual: u64,
+    },
+    // The first access that differs, None once one side has run out of accesses
+    BusActivity {
+        index: usize,
+        expected: Option<BusAccess>,
+        actual: Option<BusAccess>,
+    },
+    // The first access made in the wrong M-cycle, counted from the start of the case
+    AccessCycle {
+        access: BusAccess,
+        expected: usize,
+        actual: usize,
+    },
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let access = |access: &Option<BusAccess>| match access {
+            Some(access) => access.to_string(),
+            None => "nothing".to_string(),
+        };
+        match self {
+            Mismatch::Error(e) => write!(f, "{e}"),
+            Mismatch::Register {
+                name,
+                expected,
+                actual,
+            } => write!(f, "{name} is {actual:#X} instead of {expected:#X}"),
+            Mismatch::Ime { expected, actual } => {
+                write!(f, "IME is {actual} instead of {expected}")
+            }
+            Mismatch::Ram {
+                address,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "({address:#06X}) is {actual:#04X} instead of {expected:#04X}"
+            ),
+            Mismatch::Cycles { expected, actual } => {
+                write!(f, "took {actual} cycles instead of {expected}")
+            }
+            Mismatch::BusActivity {
+                index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "access {index} was {} instead of {}",
+                access(actual),
+                access(expected)
+            ),
+            Mismatch::AccessCycle {
+                access,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{access} happened in M-cycle {actual} instead of {expected}"
+            ),
+        }
+    }
+}
+
+fn registers(line: &TraceLine) -> [(&'static str, u16); 10] {
+    [
+        ("A", line.a as u16),
+        ("F", line.f as u16),
+        ("B", line.b as u16),
+        ("C", line.c as u16),
+        ("D", line.d as u16),
+        ("E", line.e as u16),
+        ("H", line.h as u16),
+        ("L", line.l as u16),
+        ("SP", line.sp),
+        ("PC", line.pc),
+    ]
+}
+
+// Executes the already fetched instruction at pc, returns the pc after it
+fn execute(cpu: &mut Cpu, memory: &Rc<RefCell<FlatMemory>>, pc: u16) -> Result<u16, CpuError> {
+    let opcode = memory.borrow().data[pc as usize];
+    memory.borrow_mut().recording = true;
+
+    let (opcode, prefixed) = if opcode == 0xCB {
+        (memory.borrow().data[pc.wrapping_add(1) as usize], true)
+    } else {
+        (opcode, false)
+    };
+    let instruction =
+        Instruction::fetch(opcode, prefixed).ok_or(CpuError::UnknownOpcode { opcode, prefixed })?;
+    cpu.execute(instruction)
+}
+
+// Runs one test case, an empty result means it passed
+pub fn run_case(case: &TestCase) -> Vec<Mismatch> {
+    let memory = Rc::new(RefCell::new(FlatMemory::new()));
+    let mut bus = Bus::<u16, u8, u16>::new();
+    bus.connect_readable(memory.clone());
+    bus.connect_writeable(memory.clone());
+    let mut cpu = Cpu::new(Rc::new(RefCell::new(bus)));
+    cpu.connect_clocked(memory.clone());
+
+    let initial = &case.initial;
+    for (address, data) in initial.ram.iter() {
+        memory.borrow_mut().data[*address as usize] = *data;
+    }
+    if let Some(ie) = initial.ie {
+        memory.borrow_mut().data[0xFFFF] = ie;
+    }
+    let mut line = initial.trace_line();
+    line.pc = initial.pc.wrapping_sub(1);
+    cpu.load_trace_line(&line);
+    cpu.set_interrupts_enabled(initial.ime != 0);
+
+    match execute(&mut cpu, &memory, line.pc) {
+        Ok(pc) => {
+            // The fetch of the next opcode, in the M-cycle after the instruction
+            memory.borrow_mut().tick(4);
+            let _ = cpu.read_memory(pc);
+            cpu.set_pc(pc.wrapping_add(1));
+        }
+        Err(e) => return vec![Mismatch::Error(e)],
+    }
+    memory.borrow_mut().recording = false;
+
+    let mut mismatches = vec![];
+    let mut actual = cpu.trace_line();
+    actual.pc_mem = [0; 4];
+    let expected = case.expected.trace_line();
+    for ((name, expected), (_, actual)) in registers(&expected).into_iter().zip(registers(&actual))
+    {
+        if expected != actual {
+            mismatches.push(Mismatch::Register {
+                name,
+                expected,
+                actual,
+            });
+        }
+    }
+
+    if (case.expected.ime != 0) != cpu.interrupts_enabled() {
+        mismatches.push(Mismatch::Ime {
+            expected: case.expected.ime != 0,
+            actual: cpu.interrupts_enabled(),
+        });
+    }
+
+    for (address, expected) in case.expected.ram.iter() {
+        let actual = memory.borrow().data[*address as usize];
+        if actual != *expected {
+            mismatches.push(Mismatch::Ram {
+                address: *address,
+                expected: *expected,
+                actual,
+            });
+        }
+    }
+
+    let cycles = case.cycles.len() as u64 * 4;
+    if cpu.cycles() != cycles {
+        mismatches.push(Mismatch::Cycles {
+            expected: cycles,
+            actual: cpu.cycles(),
+        });
+    }
+
+    // Their M-cycles start after the fetch of the opcode, which is our first access
+    let (expected_cycles, expected): (Vec<usize>, Vec<BusAccess>) =
+        case.bus_activity().into_iter().unzip();
+    let (actual_cycles, actual): (Vec<usize>, Vec<BusAccess>) = memory
+        .borrow()
+        .accesses
+        .borrow()
+        .iter()
+        .skip(1)
+        .map(|(cycle, access)| ((*cycle as usize).saturating_sub(2), *access))
+        .unzip();
+    if let Some(index) = (0..expected.len().max(actual.len()))
+        .find(|index| expected.get(*index) != actual.get(*index))
+    {
+        mismatches.push(Mismatch::BusActivity {
+            index,
+            expected: expected.get(index).copied(),
+            actual: actual.get(index).copied(),
+        });
+    } else if let Some(index) =
+        (0..expected.len()).find(|index| expected_cycles[*index] != actual_cycles[*index])
+    {
+        mismatches.push(Mismatch::AccessCycle {
+            access: expected[index],
+            expected: expected_cycles[index],
+            actual: actual_cycles[index],
+        });
+    }
+
+    mismatches
+}
+
+pub struct FileResult {
+    pub name: String,
+    pub passed: usize,
+    pub total: usize,
+    // The name and mismatches of the first case that failed
+    pub first_failure: Option<(String, Vec<Mismatch>)>,
+}
+
+impl FileResult {
+    pub fn run(name: String, cases: &[TestCase]) -> FileResult {
+        let mut result = FileResult {
+            name,
+            passed: 0,
+            total: cases.len(),
+            first_failure: None,
+        };
+        for case in cases {
+            let mismatches = run_case(case);
+            if mismatches.is_empty() {
+                result.passed += 1;
+            } else if result.first_failure.is_none() {
+                result.first_failure = Some((case.name.clone(), mismatches));
+            }
+        }
+        result
+    }
+}
+
+pub struct SingleStepReport {
+    pub results: Vec<FileResult>,
+}
+
+impl SingleStepReport {
+    // Runs every .json file in dir, sorted by name
+    pub fn run(dir: &Path) -> Result<SingleStepReport, Box<dyn Error>> {
+        let mut paths = vec![];
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|suffix| suffix == "json") {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+
+        let mut results = vec![];
+        for path in paths {
+            let cases: Vec<TestCase> = serde_json::from_str(&fs::read_to_string(&path)?)?;
+            let name = path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned();
+            results.push(FileResult::run(name, &cases));
+        }
+        Ok(SingleStepReport { results })
+    }
+
+    // Files in which every case passed
+    pub fn passed(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|result| result.passed == result.total)
+            .count()
+    }
+}
+
+impl Display for SingleStepReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let width = self
+            .results
+            .iter()
+            .map(|result| result.name.len())
+            .max()
+            .unwrap_or(0);
+
+        for result in self.results.iter() {
+            write!(
+                f,
+                "{:width$}  {:>5}/{:<5}",
+                result.name, result.passed, result.total
+            )?;
+            if let Some((case, mismatches)) = &result.first_failure {
+                let mismatches: Vec<String> = mismatches.iter().map(|m| m.to_string()).collect();
+                write!(f, "  {case}: {}", mismatches.join(", "))?;
+            }
+            writeln!(f)?;
+        }
+        write!(f, "{}/{} files passed", self.passed(), self.results.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BusAccess, FileResult, Mismatch, TestCase, run_case};
+
+    fn case(json: &str) -> TestCase {
+        serde_json::from_str(json).unwrap()
+    }
+
+    // LD B,d8
+    const LD_B_D8: &str = r#"{
+        "name": "06 0000",
+        "initial": {
+            "pc": 49153, "sp": 53232, "a": 1, "b": 0, "c": 19, "d": 0, "e": 216, "f": 176,
+            "h": 1, "l": 77, "ime": 0, "ie": 0,
+            "ram": [[49152, 6], [49153, 66], [49154, 0]]
+        },
+        "final": {
+            "pc": 49155, "sp": 53232, "a": 1, "b": 66, "c": 19, "d": 0, "e": 216, "f": 176,
+            "h": 1, "l": 77, "ime": 0,
+            "ram": [[49152, 6], [49153, 66], [49154, 0]]
+        },
+        "cycles": [[49153, 66, "r-m"], [49154, 0, "r-m"]]
+    }"#;
+
+    // LD (HL),A
+    const LD_HL_A: &str = r#"{
+        "name": "77 0000",
+        "initial": {
+            "pc": 49153, "sp": 53232, "a": 153, "b": 0, "c": 0, "d": 0, "e": 0, "f": 0,
+            "h": 193, "l": 0, "ime": 0, "ie": 0,
+            "ram": [[49152, 119], [49153, 0], [49408, 0]]
+        },
+        "final": {
+            "pc": 49154, "sp": 53232, "a": 153, "b": 0, "c": 0, "d": 0, "e": 0, "f": 0,
+            "h": 193, "l": 0, "ime": 0,
+            "ram": [[49152, 119], [49153, 0], [49408, 153]]
+        },
+        "cycles": [[49408, 153, "-wm"], [49153, 0, "r-m"]]
+    }"#;
+
+    // SWAP A
+    const SWAP_A: &str = r#"{
+        "name": "cb 37 0000",
+        "initial": {
+            "pc": 49153, "sp": 53232, "a": 18, "b": 0, "c": 0, "d": 0, "e": 0, "f": 112,
+            "h": 0, "l": 0, "ime": 1, "ie": 0,
+            "ram": [[49152, 203], [49153, 55], [49154, 0]]
+        },
+        "final": {
+            "pc": 49155, "sp": 53232, "a": 33, "b": 0, "c": 0, "d": 0, "e": 0, "f": 0,
+            "h": 0, "l": 0, "ime": 1,
+            "ram": [[49152, 203], [49153, 55], [49154, 0]]
+        },
+        "cycles": [[49153, 55, "r-m"], [49154, 0, "r-m"]]
+    }"#;
+
+    #[test]
+    fn test_bus_activity() {
+        let mut case = case(LD_HL_A);
+        case.cycles.insert(1, None);
+        case.cycles.insert(1, Some((None, None, "---".to_string())));
+        assert_eq!(
+            case.bus_activity(),
+            vec![
+                (0, BusAccess::Write(0xC100, 0x99)),
+                (3, BusAccess::Read(0xC001, 0x00))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_passing_cases() {
+        for json in [LD_B_D8, LD_HL_A, SWAP_A] {
+            let case = case(json);
+            assert_eq!(run_case(&case), vec![], "{}", case.name);
+        }
+    }
+
+    #[test]
+    fn test_mismatches() {
+        let mut case = case(LD_B_D8);
+        case.expected.b = 0x43;
+        case.expected.ram[1] = (0xC001, 0x43);
+        case.cycles
+            .push(Some((Some(0xC003), Some(0), "r-m".to_string())));
+
+        assert_eq!(
+            run_case(&case),
+            vec![
+                Mismatch::Register {
+                    name: "B",
+                    expected: 0x43,
+                    actual: 0x42
+                },
+                Mismatch::Ram {
+                    address: 0xC001,
+                    expected: 0x43,
+                    actual: 0x42
+                },
+                Mismatch::Cycles {
+                    expected: 12,
+                    actual: 8
+                },
+                Mismatch::BusActivity {
+                    index: 2,
+                    expected: Some(BusAccess::Read(0xC003, 0)),
+                    actual: None
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_access_cycle_mismatch() {
+        let mut case = case(LD_HL_A);
+        case.cycles.insert(0, None);
+
+        assert_eq!(
+            run_case(&case),
+            vec![
+                Mismatch::Cycles {
+                    expected: 12,
+                    actual: 8
+                },
+                Mismatch::AccessCycle {
+                    access: BusAccess::Write(0xC100, 0x99),
+                    expected: 1,
+                    actual: 0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_file_result() {
+        let mut failing = case(LD_B_D8);
+        failing.name = "06 0001".to_string();
+        failing.expected.a = 2;
+
+        let result = FileResult::run("06.json".to_string(), &[case(LD_B_D8), failing]);
+        assert_eq!(result.passed, 1);
+        assert_eq!(result.total, 2);
+        let (name, mismatches) = result.first_failure.unwrap();
+        assert_eq!(name, "06 0001");
+        assert_eq!(mismatches[0].to_string(), "A is 0x1 instead of 0x2");
+    }
+}
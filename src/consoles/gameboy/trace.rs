@@ -0,0 +1,205 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::io::Write;
+use std::str::FromStr;
+
+use super::registers::Registers;
+
+// One line of an instruction trace in the format used by gameboy-doctor and most other
+// emulators, so a run can be diffed against their reference logs:
+// A:01 F:B0 B:00 C:13 D:00 E:D8 H:01 L:4D SP:FFFE PC:0100 PCMEM:00,C3,13,02
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceLine {
+    pub a: u8,
+    pub f: u8,
+    pub b: u8,
+    pub c: u8,
+    pub d: u8,
+    pub e: u8,
+    pub h: u8,
+    pub l: u8,
+    pub sp: u16,
+    pub pc: u16,
+    pub pc_mem: [u8; 4],
+}
+
+impl TraceLine {
+    pub fn new(registers: &Registers, sp: u16, pc: u16, pc_mem: [u8; 4]) -> TraceLine {
+        TraceLine {
+            a: registers.a,
+            f: registers.f,
+            b: registers.b,
+            c: registers.c,
+            d: registers.d,
+            e: registers.e,
+            h: registers.h,
+            l: registers.l,
+            sp,
+            pc,
+            pc_mem,
+        }
+    }
+
+    // Names of the fields that differ between self and other
+    pub fn differences(&self, other: &TraceLine) -> Vec<&'static str> {
+        let mut fields = vec![];
+        let registers = [
+            ("A", self.a, other.a),
+            ("F", self.f, other.f),
+            ("B", self.b, other.b),
+            ("C", self.c, other.c),
+            ("D", self.d, other.d),
+            ("E", self.e, other.e),
+            ("H", self.h, other.h),
+            ("L", self.l, other.l),
+        ];
+        for (name, mine, theirs) in registers {
+            if mine != theirs {
+                fields.push(name);
+            }
+        }
+        if self.sp != other.sp {
+            fields.push("SP");
+        }
+        if self.pc != other.pc {
+            fields.push("PC");
+        }
+        if self.pc_mem != other.pc_mem {
+            fields.push("PCMEM");
+        }
+        fields
+    }
+}
+
+impl Display for TraceLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "A:{:02X} F:{:02X} B:{:02X} C:{:02X} D:{:02X} E:{:02X} H:{:02X} L:{:02X} SP:{:04X} PC:{:04X} PCMEM:{:02X},{:02X},{:02X},{:02X}",
+            self.a,
+            self.f,
+            self.b,
+            self.c,
+            self.d,
+            self.e,
+            self.h,
+            self.l,
+            self.sp,
+            self.pc,
+            self.pc_mem[0],
+            self.pc_mem[1],
+            self.pc_mem[2],
+            self.pc_mem[3],
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct TraceParseError(String);
+
+impl Display for TraceParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid trace line: {}", self.0)
+    }
+}
+
+impl Error for TraceParseError {}
+
+impl FromStr for TraceLine {
+    type Err = TraceParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || TraceParseError(s.to_string());
+        let mut line = TraceLine::new(&Registers::new(), 0, 0, [0; 4]);
+        let mut seen = 0;
+
+        for field in s.split_whitespace() {
+            let (name, value) = field.split_once(':').ok_or_else(error)?;
+            let byte = || u8::from_str_radix(value, 16).map_err(|_| error());
+            let word = || u16::from_str_radix(value, 16).map_err(|_| error());
+            match name {
+                "A" => line.a = byte()?,
+                "F" => line.f = byte()?,
+                "B" => line.b = byte()?,
+                "C" => line.c = byte()?,
+                "D" => line.d = byte()?,
+                "E" => line.e = byte()?,
+                "H" => line.h = byte()?,
+                "L" => line.l = byte()?,
+                "SP" => line.sp = word()?,
+                "PC" => line.pc = word()?,
+                "PCMEM" => {
+                    let bytes = value
+                        .split(',')
+                        .map(|b| u8::from_str_radix(b, 16).map_err(|_| error()))
+                        .collect::<Result<Vec<u8>, _>>()?;
+                    line.pc_mem = bytes.try_into().map_err(|_| error())?;
+                }
+                _ => return Err(error()),
+            }
+            seen += 1;
+        }
+
+        if seen != 11 {
+            return Err(error());
+        }
+
+        Ok(line)
+    }
+}
+
+pub struct TraceWriter {
+    out: Box<dyn Write>,
+}
+
+impl TraceWriter {
+    pub fn new(out: Box<dyn Write>) -> TraceWriter {
+        TraceWriter { out }
+    }
+
+    pub fn write_line(&mut self, line: &TraceLine) -> std::io::Result<()> {
+        writeln!(self.out, "{line}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::consoles::gameboy::registers::Registers;
+
+    use super::TraceLine;
+
+    const LINE: &str = "A:01 F:B0 B:00 C:13 D:00 E:D8 H:01 L:4D SP:FFFE PC:0100 PCMEM:00,C3,13,02";
+
+    #[test]
+    fn test_format_trace_line() {
+        let mut registers = Registers::new();
+        registers.a = 0x01;
+        registers.f = 0xB0;
+        registers.c = 0x13;
+        registers.e = 0xD8;
+        registers.h = 0x01;
+        registers.l = 0x4D;
+
+        let line = TraceLine::new(&registers, 0xFFFE, 0x0100, [0x00, 0xC3, 0x13, 0x02]);
+        assert_eq!(line.to_string(), LINE);
+    }
+
+    #[test]
+    fn test_parse_trace_line() {
+        let line: TraceLine = LINE.parse().unwrap();
+        assert_eq!(line.to_string(), LINE);
+        assert!("A:01 F:B0".parse::<TraceLine>().is_err());
+        assert!(LINE.replace("FFFE", "FFXE").parse::<TraceLine>().is_err());
+    }
+
+    #[test]
+    fn test_differences() {
+        let expected: TraceLine = LINE.parse().unwrap();
+        let mut actual = expected;
+        assert!(expected.differences(&actual).is_empty());
+
+        actual.f = 0x80;
+        actual.pc_mem[3] = 0xFF;
+        assert_eq!(expected.differences(&actual), vec!["F", "PCMEM"]);
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt::Display;
+use std::io::BufRead;
+
+use super::cpu::{CpuFault, LockUp};
+use super::disassembler::disassemble;
+use super::execution::Steppable;
+use super::game_boy::GameBoy;
+use super::trace::TraceLine;
+
+pub struct Divergence {
+    pub line_number: usize,
+    pub history: Vec<TraceLine>,
+    pub expected: TraceLine,
+    pub actual: TraceLine,
+}
+
+pub enum TraceDiff {
+    // Every line of the reference matched, holds the number of lines compared
+    Identical(usize),
+    Diverged(Divergence),
+    // The cpu stopped before the reference ended, holds the last line number compared
+    CpuStopped(usize),
+    // The cpu hit an error while executing the instruction after the given line
+    Faulted(usize, CpuFault),
+    // The instruction after the given line is illegal and locked up the cpu
+    LockedUp(usize, LockUp),
+}
+
+fn describe(line: &TraceLine) -> String {
+    format!("{line}  {}", disassemble(&line.pc_mem).0)
+}
+
+impl Display for TraceDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TraceDiff::Identical(lines) => write!(f, "No divergence in {lines} lines"),
+            TraceDiff::CpuStopped(line_number) => {
+                write!(f, "Cpu stopped after line {line_number} of the reference")
+            }
+            TraceDiff::Faulted(line_number, fault) => {
+                write!(f, "Cpu faulted after line {line_number}: {fault}")
+            }
+            TraceDiff::LockedUp(line_number, lock_up) => {
+                write!(f, "{lock_up} after line {line_number}")
+            }
+            TraceDiff::Diverged(divergence) => {
+                writeln!(
+                    f,
+                    "Diverged at line {} in {}",
+                    divergence.line_number,
+                    divergence
+                        .expected
+                        .differences(&divergence.actual)
+                        .join(", ")
+                )?;
+                writeln!(f, "Preceding instructions:")?;
+                for line in divergence.history.iter() {
+                    writeln!(f, "          {}", describe(line))?;
+                }
+                writeln!(f, "Expected: {}", describe(&divergence.expected))?;
+                write!(f, "Actual:   {}", describe(&divergence.actual))
+            }
+        }
+    }
+}
+
+// Runs the game boy one instruction per reference line until its state differs from the
+// reference. The first line seeds the registers, since reference logs start after the boot rom.
+// context is the number of matching instructions kept to show before a divergence.
+pub fn diff_trace<R: BufRead>(
+    gameboy: &mut GameBoy,
+    reference: R,
+    context: usize,
+) -> Result<TraceDiff, Box<dyn Error>> {
+    let cpu = gameboy.cpu_mut();
+    let mut history = VecDeque::with_capacity(context + 1);
+    let mut compared = 0;
+    let mut seeded = false;
+
+    for (i, line) in reference.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let expected: TraceLine = line.parse()?;
+
+        if !seeded {
+            cpu.load_trace_line(&expected);
+            seeded = true;
+        }
+
+        let actual = cpu.trace_line();
+        if expected != actual {
+            return Ok(TraceDiff::Diverged(Divergence {
+                line_number: i + 1,
+                history: history.into_iter().collect(),
+                expected,
+                actual,
+            }));
+        }
+
+        compared = i + 1;
+        history.push_back(actual);
+        if history.len() > context {
+            history.pop_front();
+        }
+
+        match cpu.step() {
+            Ok(true) => {}
+            Ok(false) => return Ok(TraceDiff::CpuStopped(compared)),
+            Err(fault) => return Ok(TraceDiff::Faulted(compared, fault)),
+        }
+        if let Some(lock_up) = cpu.lock_up() {
+            return Ok(TraceDiff::LockedUp(compared, lock_up));
+        }
+    }
+
+    Ok(TraceDiff::Identical(compared))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::consoles::gameboy::{game_boy::GameBoy, gbcartridge::GbCartridge};
+
+    use super::{TraceDiff, diff_trace};
+
+    fn setup() -> GameBoy {
+        GameBoy::new(GbCartridge::from_bytes("test", vec![0; 0x4000]))
+    }
+
+    #[test]
+    fn test_identical_trace() {
+        let mut gameboy = setup();
+        let reference = "A:01 F:B0 B:00 C:13 D:00 E:D8 H:01 L:4D SP:FFFE PC:0100 PCMEM:00,00,00,00\n\
+                         A:01 F:B0 B:00 C:13 D:00 E:D8 H:01 L:4D SP:FFFE PC:0101 PCMEM:00,00,00,00\n";
+
+        let result = diff_trace(&mut gameboy, reference.as_bytes(), 4).unwrap();
+        assert!(matches!(result, TraceDiff::Identical(2)));
+    }
+
+    #[test]
+    fn test_diverged_trace() {
+        let mut gameboy = setup();
+        let reference = "A:01 F:B0 B:00 C:13 D:00 E:D8 H:01 L:4D SP:FFFE PC:0100 PCMEM:00,00,00,00\n\
+                         A:01 F:B0 B:00 C:13 D:00 E:D8 H:01 L:4D SP:FFFE PC:0101 PCMEM:00,00,00,00\n\
+                         A:01 F:B0 B:00 C:13 D:00 E:D8 H:01 L:4D SP:FFFE PC:0102 PCMEM:00,00,00,00\n\
+                         A:02 F:B0 B:00 C:13 D:00 E:D8 H:01 L:4D SP:FFFE PC:0103 PCMEM:00,00,00,00\n";
+
+        let result = diff_trace(&mut gameboy, reference.as_bytes(), 2).unwrap();
+        match result {
+            TraceDiff::Diverged(divergence) => {
+                assert_eq!(divergence.line_number, 4);
+                assert_eq!(divergence.history.len(), 2);
+                assert_eq!(divergence.history[0].pc, 0x0101);
+                assert_eq!(divergence.actual.a, 0x01);
+                assert_eq!(
+                    divergence.expected.differences(&divergence.actual),
+                    vec!["A"]
+                );
+            }
+            _ => panic!("Expected a divergence"),
+        }
+    }
+}
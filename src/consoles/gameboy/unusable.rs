@@ -0,0 +1,59 @@
+use std::error::Error;
+use std::ops::RangeInclusive;
+
+use super::model::Model;
+use crate::consoles::addressable::Addressable;
+use crate::consoles::bus::{ReadDevice, WriteDevice};
+use crate::consoles::memory_map::gameboy::_UNUSABLE;
+use crate::consoles::readable::Readable;
+use crate::consoles::writeable::Writeable;
+
+// 0xFEA0-0xFEFF, nothing is connected here but reads do not float like the open bus.
+// What they return depends on the model, writes are ignored.
+pub struct Unusable {
+    model: Model,
+    address_range: RangeInclusive<usize>,
+}
+
+impl Unusable {
+    pub fn new(model: Model) -> Unusable {
+        Unusable {
+            model,
+            address_range: _UNUSABLE,
+        }
+    }
+}
+
+impl Readable<u16, u8> for Unusable {
+    fn read(&self, address: u16) -> Result<u8, Box<dyn Error>> {
+        Ok(self.model.unusable_read(address))
+    }
+}
+
+impl Writeable<u16, u8, u16> for Unusable {
+    fn write(&mut self, _address: u16, _data: u8) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn write_16(&mut self, _address: u16, _data: u16) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+}
+
+impl Addressable<u16> for Unusable {
+    fn assign_address_range(&mut self, range: RangeInclusive<usize>) {
+        self.address_range = range;
+    }
+
+    fn in_range(&self, address: u16) -> bool {
+        self.address_range.contains(&(address as usize))
+    }
+
+    fn address_ranges(&self) -> Vec<RangeInclusive<usize>> {
+        vec![self.address_range.clone()]
+    }
+}
+
+impl ReadDevice<u16, u8> for Unusable {}
+
+impl WriteDevice<u16, u8, u16> for Unusable {}
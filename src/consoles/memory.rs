@@ -1,4 +1,3 @@
-use function_name::named;
 use std::error::Error;
 use std::fmt::{Debug, Display, LowerHex};
 use std::marker::PhantomData;
@@ -9,6 +8,7 @@ use num_traits::{AsPrimitive, FromPrimitive, NumCast, ToPrimitive};
 use crate::consoles::addressable::Addressable;
 use crate::consoles::bus::{ReadDevice, WriteDevice};
 use crate::consoles::readable::Readable;
+use crate::consoles::state::{SaveState, StateError, StateReader, StateWriter};
 use crate::consoles::writeable::Writeable;
 use crate::log;
 #[allow(unused_imports)]
@@ -35,6 +35,24 @@ where
 
 impl<A> Error for MemoryError<A> where A: Debug {}
 
+// What a memory holds when it is powered on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerOnPattern<V> {
+    Fill(V),
+    // Reproducible noise from the seed, real ram comes up in a random state
+    Random(u64),
+}
+
+impl<V> PowerOnPattern<V> {
+    // Gives every region its own noise while keeping a single seed per machine
+    pub fn for_region(self, start: usize) -> Self {
+        match self {
+            PowerOnPattern::Random(seed) => PowerOnPattern::Random(seed ^ start as u64),
+            fill => fill,
+        }
+    }
+}
+
 pub struct Memory<A, V, DV, const N: usize> {
     address_type: PhantomData<A>,
     d_value_type: PhantomData<DV>,
@@ -58,29 +76,28 @@ where
         + AsPrimitive<V>,
     DV: NumCast + Shr<i32> + BitAnd<u16> + Debug,
 {
-    pub fn new(
-        conversion: fn(DV) -> Option<(V, V)>,
-        get_default_value: Option<Box<dyn Fn() -> V>>,
-    ) -> Self {
-        let mut memory = Memory::<A, V, DV, N> {
+    pub fn new(conversion: fn(DV) -> Option<(V, V)>, pattern: PowerOnPattern<V>) -> Self {
+        let memory = match pattern {
+            PowerOnPattern::Fill(value) => [value; N],
+            PowerOnPattern::Random(seed) => {
+                // xorshift64, the state must not be zero
+                let mut state = seed | 1;
+                std::array::from_fn(|_| {
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+                    V::from_u8((state >> 56) as u8).unwrap_or_default()
+                })
+            }
+        };
+
+        Memory::<A, V, DV, N> {
             address_type: PhantomData,
             d_value_type: PhantomData,
             address_range: (0..=0),
-            memory: [V::default(); N],
+            memory,
             conversion,
-        };
-
-        if let Some(get_default_value) = get_default_value {
-            (0..N).into_iter().for_each(|i| {
-                let _ = <Memory<A, V, DV, N> as Writeable<A, V, DV>>::write(
-                    &mut memory,
-                    NumCast::from(i as u16).unwrap(),
-                    NumCast::from(get_default_value()).unwrap(),
-                );
-            });
         }
-
-        memory
     }
 
     pub fn as_hex_dump(&self) -> Vec<String> {
@@ -92,24 +109,44 @@ where
     }
 }
 
+impl<A, V, DV, const N: usize> Memory<A, V, DV, N>
+where
+    A: ToPrimitive,
+{
+    // Where the address is stored, none if it lies outside the memory
+    fn index(&self, address: &A) -> Option<usize> {
+        let index = address
+            .to_usize()?
+            .checked_sub(*self.address_range.start())?;
+        (index < N).then_some(index)
+    }
+}
+
 impl<A, V, DV, const N: usize> Readable<A, V> for Memory<A, V, DV, N>
 where
     A: NumCast + AsPrimitive<A> + ToPrimitive + Debug,
     V: Copy,
 {
-    #[named]
     fn read(&self, address: A) -> Result<V, Box<dyn Error>> {
-        log!("address: {address:?}");
-        match address.to_usize() {
-            Some(address) => {
-                let index = address - self.address_range.start();
-                Ok(self.memory[index])
-            }
+        log!(Bus, Trace, "read address: {address:?}");
+        match self.index(&address) {
+            Some(index) => Ok(self.memory[index]),
             None => Err(Box::new(MemoryError::ReadError::<A>(address))),
         }
     }
 }
 
+// Only the contents, where the memory is mapped comes from how the console is wired up
+impl<A, DV, const N: usize> SaveState for Memory<A, u8, DV, N> {
+    fn save_state(&self, out: &mut StateWriter) {
+        out.write_bytes(&self.memory);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), StateError> {
+        state.read_bytes(&mut self.memory)
+    }
+}
+
 impl<A, V, DV, const N: usize> ReadDevice<A, V> for Memory<A, V, DV, N>
 where
     A: NumCast + AsPrimitive<A> + Clone + Copy + Debug,
@@ -124,9 +161,8 @@ where
     DV: NumCast + Shr<i32> + BitAnd<u16> + Debug,
 {
     fn write(&mut self, address: A, data: V) -> Result<(), Box<dyn Error>> {
-        match address.to_usize() {
-            Some(address) => {
-                let index = address - self.address_range.start();
+        match self.index(&address) {
+            Some(index) => {
                 self.memory[index] = data;
                 Ok(())
             }
@@ -134,13 +170,11 @@ where
         }
     }
 
-    #[named]
     fn write_16(&mut self, address: A, data: DV) -> Result<(), Box<dyn Error>> {
-        log!("address: {address:?} data: {data:?}");
-        match address.to_usize() {
-            Some(addr) => match (self.conversion)(data) {
+        log!(Bus, Trace, "write_16 address: {address:?} data: {data:?}");
+        match self.index(&address).filter(|index| index + 1 < N) {
+            Some(index) => match (self.conversion)(data) {
                 Some((upper, lower)) => {
-                    let index = addr - self.address_range.start();
                     self.memory[index] = NumCast::from(lower).unwrap();
                     self.memory[index + 1] = NumCast::from(upper).unwrap();
                     Ok(())
@@ -174,4 +208,57 @@ where
             None => false,
         }
     }
+
+    fn address_ranges(&self) -> Vec<RangeInclusive<usize>> {
+        vec![self.address_range.clone()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::{Memory, PowerOnPattern};
+    use crate::consoles::addressable::Addressable;
+    use crate::consoles::readable::Readable;
+    use crate::consoles::writeable::Writeable;
+    use crate::utils::conversion::u16_to_u8;
+
+    fn setup(pattern: PowerOnPattern<u8>) -> Memory<u16, u8, u16, 0x80> {
+        let mut memory = Memory::new(u16_to_u8, pattern);
+        memory.assign_address_range(0xFF80..=0xFFFF);
+        memory
+    }
+
+    #[rstest]
+    #[case(0xFF7F, false)]
+    #[case(0xFF80, true)]
+    #[case(0xFFFF, true)]
+    #[case(0x0000, false)]
+    fn test_index_checked(#[case] address: u16, #[case] valid: bool) {
+        let mut memory = setup(PowerOnPattern::Fill(0));
+        assert_eq!(memory.write(address, 0x42).is_ok(), valid);
+        assert_eq!(memory.read(address).ok(), valid.then_some(0x42));
+    }
+
+    #[test]
+    fn test_write_16_at_the_end() {
+        let mut memory = setup(PowerOnPattern::Fill(0));
+        assert!(memory.write_16(0xFFFE, 0x1234).is_ok());
+        assert!(memory.write_16(0xFFFF, 0x1234).is_err());
+        assert_eq!(memory.read(0xFFFF).unwrap(), 0x12);
+    }
+
+    #[test]
+    fn test_power_on_pattern() {
+        let memory = setup(PowerOnPattern::Fill(0xFF));
+        assert!((0xFF80..=0xFFFF).all(|address| memory.read(address).unwrap() == 0xFF));
+
+        let random = setup(PowerOnPattern::Random(1234));
+        let again = setup(PowerOnPattern::Random(1234));
+        let other = setup(PowerOnPattern::Random(1234).for_region(0xC000));
+        assert_eq!(random.memory, again.memory);
+        assert_ne!(random.memory, other.memory);
+        assert!(random.memory.iter().any(|byte| *byte != random.memory[0]));
+    }
 }
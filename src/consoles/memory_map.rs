@@ -13,6 +13,9 @@ pub mod gameboy {
     pub const OBJECT_ATTRIBUTE_MEMORY: RangeInclusive<usize> = 0xFE00..=0xFE9F;
     pub const _UNUSABLE: RangeInclusive<usize> = 0xFEA0..=0xFEFF; // Nintendo says not to use this
     pub const IO_REGISTERS: RangeInclusive<usize> = 0xFF00..=0xFF7F;
+    pub const JOYPAD: usize = 0xFF00;
+    pub const SERIAL_TRANSFER: RangeInclusive<usize> = 0xFF01..=0xFF02;
+    pub const INTERRUPT_FLAG_REGISTER: usize = 0xFF0F;
     pub const H_RAM: RangeInclusive<usize> = 0xFF80..=0xFFFE;
     pub const INTERRUPT_ENABLE_REGISTER: usize = 0xFFFF;
 }
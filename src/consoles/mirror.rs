@@ -0,0 +1,152 @@
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt::Display;
+use std::ops::RangeInclusive;
+use std::rc::Rc;
+
+use num_traits::{NumCast, ToPrimitive};
+
+use super::addressable::Addressable;
+use super::bus::{ReadDevice, WriteDevice};
+use super::readable::Readable;
+use super::writeable::Writeable;
+
+#[derive(Debug)]
+struct MirrorError(usize);
+
+impl Display for MirrorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Address {:#X} is not mirrored", self.0)
+    }
+}
+
+impl Error for MirrorError {}
+
+// Makes a device answer to a second address range, an access to the mirror goes to the
+// device at the address minus offset
+pub struct Mirror<D: ?Sized> {
+    device: Rc<RefCell<D>>,
+    address_range: RangeInclusive<usize>,
+    offset: usize,
+}
+
+impl<D: ?Sized> Mirror<D> {
+    pub fn new(
+        device: Rc<RefCell<D>>,
+        address_range: RangeInclusive<usize>,
+        offset: usize,
+    ) -> Self {
+        Mirror {
+            device,
+            address_range,
+            offset,
+        }
+    }
+
+    fn mirrored<A>(&self, address: A) -> Result<A, Box<dyn Error>>
+    where
+        A: NumCast + ToPrimitive,
+    {
+        let address = address.to_usize().unwrap_or(usize::MAX);
+        address
+            .checked_sub(self.offset)
+            .and_then(NumCast::from)
+            .ok_or_else(|| Box::new(MirrorError(address)) as Box<dyn Error>)
+    }
+}
+
+impl<A, V, D> Readable<A, V> for Mirror<D>
+where
+    A: NumCast + ToPrimitive,
+    D: Readable<A, V> + ?Sized,
+{
+    fn read(&self, address: A) -> Result<V, Box<dyn Error>> {
+        self.device.borrow().read(self.mirrored(address)?)
+    }
+}
+
+impl<A, V, DV, D> Writeable<A, V, DV> for Mirror<D>
+where
+    A: NumCast + ToPrimitive,
+    D: Writeable<A, V, DV> + ?Sized,
+{
+    fn write(&mut self, address: A, data: V) -> Result<(), Box<dyn Error>> {
+        let address = self.mirrored(address)?;
+        self.device.borrow_mut().write(address, data)
+    }
+
+    fn write_16(&mut self, address: A, data: DV) -> Result<(), Box<dyn Error>> {
+        let address = self.mirrored(address)?;
+        self.device.borrow_mut().write_16(address, data)
+    }
+}
+
+impl<A, D> Addressable<A> for Mirror<D>
+where
+    A: ToPrimitive,
+    D: ?Sized,
+{
+    fn assign_address_range(&mut self, range: RangeInclusive<usize>) {
+        self.address_range = range;
+    }
+
+    fn in_range(&self, address: A) -> bool {
+        address
+            .to_usize()
+            .is_some_and(|address| self.address_range.contains(&address))
+    }
+
+    fn address_ranges(&self) -> Vec<RangeInclusive<usize>> {
+        vec![self.address_range.clone()]
+    }
+}
+
+impl<A, V, D> ReadDevice<A, V> for Mirror<D>
+where
+    A: NumCast + ToPrimitive,
+    D: Readable<A, V> + ?Sized,
+{
+}
+
+impl<A, V, DV, D> WriteDevice<A, V, DV> for Mirror<D>
+where
+    A: NumCast + ToPrimitive,
+    D: Writeable<A, V, DV> + ?Sized,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::Mirror;
+    use crate::consoles::addressable::Addressable;
+    use crate::consoles::memory::{Memory, PowerOnPattern};
+    use crate::consoles::readable::Readable;
+    use crate::consoles::writeable::Writeable;
+    use crate::utils::conversion::u16_to_u8;
+
+    #[test]
+    fn test_mirror() {
+        let memory = Rc::new(RefCell::new(Memory::<u16, u8, u16, 0x100>::new(
+            u16_to_u8,
+            PowerOnPattern::Fill(0),
+        )));
+        memory.borrow_mut().assign_address_range(0xC000..=0xC0FF);
+        let mut mirror = Mirror::new(memory.clone(), 0xE000..=0xE0FF, 0x2000);
+
+        memory.borrow_mut().write(0xC012, 0x42).unwrap();
+        assert_eq!(mirror.read(0xE012).unwrap(), 0x42);
+
+        mirror.write(0xE0FF, 0x24).unwrap();
+        assert_eq!(memory.borrow().read(0xC0FF).unwrap(), 0x24);
+
+        mirror.write_16(0xE020, 0x1234).unwrap();
+        assert_eq!(memory.borrow().read(0xC020).unwrap(), 0x34);
+        assert_eq!(memory.borrow().read(0xC021).unwrap(), 0x12);
+
+        assert!(mirror.in_range(0xE0FF));
+        assert!(!mirror.in_range(0xE100));
+    }
+}
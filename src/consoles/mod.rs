@@ -1,11 +1,21 @@
+// The bus and memory building blocks are only put to use by the consoles
+#![cfg_attr(not(feature = "gameboy"), allow(dead_code))]
+
 mod addressable;
 mod bus;
 pub mod cartridge;
 pub mod console;
 pub mod fake_cartridge;
-mod gameboy;
+#[cfg(feature = "gameboy")]
+pub mod gameboy;
 mod memory;
 mod memory_map;
+mod mirror;
+pub mod pacing;
 mod readable;
+pub mod registry;
+pub mod state;
 mod static_data;
 mod writeable;
+
+pub use bus::StrictMode;
@@ -0,0 +1,480 @@
+use std::error::Error;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::console::{Console, SAMPLE_RATE};
+
+// Console::run emulates a frame whenever the pacer says one is due. Frames are due at the
+// console's own frame rate times the speed, measured against a clock that never goes back.
+
+// A host that falls further behind than this drops the frames it missed instead of rushing
+// through them
+const MAX_LATE_FRAMES: f64 = 4.0;
+// How much audio the sink should have queued, in frames. Less and it may run dry, more and the
+// sound lags behind the picture.
+const AUDIO_LATENCY_FRAMES: f64 = 3.0;
+// The most a frame is stretched or shortened by to bring the audio back to the latency above
+const MAX_AUDIO_CORRECTION: f64 = 0.125;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Speed {
+    // Relative to the console's own frame rate, 2.0 is twice as fast
+    Multiplier(f64),
+    // As fast as the host can emulate, there is no sound
+    Unthrottled,
+}
+
+impl Speed {
+    pub const REAL_TIME: Speed = Speed::Multiplier(1.0);
+}
+
+impl Default for Speed {
+    fn default() -> Self {
+        Speed::REAL_TIME
+    }
+}
+
+// A multiplier such as 0.5 or 2, or max for unthrottled
+impl FromStr for Speed {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "max" => Ok(Speed::Unthrottled),
+            s => match s.parse::<f64>() {
+                Ok(multiplier) if multiplier.is_finite() && multiplier > 0.0 => {
+                    Ok(Speed::Multiplier(multiplier))
+                }
+                _ => Err(format!(
+                    "Invalid speed {s}, expected a multiplier above 0 or max"
+                )),
+            },
+        }
+    }
+}
+
+// Time since some fixed point, it must never go back
+pub trait Clock {
+    fn now(&self) -> Duration;
+    fn sleep(&mut self, duration: Duration);
+}
+
+#[derive(Debug)]
+pub struct MonotonicClock {
+    start: Instant,
+}
+
+impl MonotonicClock {
+    pub fn new() -> MonotonicClock {
+        MonotonicClock {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for MonotonicClock {
+    fn default() -> Self {
+        MonotonicClock::new()
+    }
+}
+
+impl Clock for MonotonicClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    fn sleep(&mut self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
+
+// Where the samples of Console::audio_samples go to be played, e.g. a sound card's queue
+pub trait AudioSink {
+    fn play(&mut self, samples: &[i16]);
+    // Samples handed to play that have not been played yet
+    fn queued(&self) -> usize;
+}
+
+#[derive(Debug, Default)]
+struct Controls {
+    speed: Speed,
+    paused: bool,
+    frames_to_advance: u32,
+    stop: bool,
+}
+
+// Controls the pacer from anywhere. Clones share the same controls and can be sent to other
+// threads, e.g. the one handling input.
+#[derive(Debug, Clone, Default)]
+pub struct PacerHandle(Arc<Mutex<Controls>>);
+
+impl PacerHandle {
+    fn controls(&self) -> MutexGuard<'_, Controls> {
+        // The controls are plain values, a panic elsewhere cannot leave them half changed
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn set_speed(&self, speed: Speed) {
+        self.controls().speed = speed;
+    }
+
+    pub fn speed(&self) -> Speed {
+        self.controls().speed
+    }
+
+    pub fn pause(&self) {
+        self.controls().paused = true;
+    }
+
+    pub fn resume(&self) {
+        let mut controls = self.controls();
+        controls.paused = false;
+        controls.frames_to_advance = 0;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.controls().paused
+    }
+
+    // Emulates one more frame while paused, does nothing otherwise
+    pub fn advance_frame(&self) {
+        let mut controls = self.controls();
+        if controls.paused {
+            controls.frames_to_advance += 1;
+        }
+    }
+
+    // Makes Console::run return before the next frame
+    pub fn stop(&self) {
+        self.controls().stop = true;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pace {
+    Frame,
+    Paused,
+    Stop,
+}
+
+pub struct FramePacer {
+    frame_rate: f64,
+    clock: Box<dyn Clock>,
+    audio: Option<Box<dyn AudioSink>>,
+    handle: PacerHandle,
+    // When the next frame is due on the clock, none after a pause or a stop
+    deadline: Option<Duration>,
+}
+
+impl FramePacer {
+    pub fn new(frame_rate: f64) -> FramePacer {
+        FramePacer::with_clock(frame_rate, Box::new(MonotonicClock::new()))
+    }
+
+    pub fn with_clock(frame_rate: f64, clock: Box<dyn Clock>) -> FramePacer {
+        FramePacer {
+            frame_rate,
+            clock,
+            audio: None,
+            handle: PacerHandle::default(),
+            deadline: None,
+        }
+    }
+
+    pub fn handle(&self) -> PacerHandle {
+        self.handle.clone()
+    }
+
+    // Sound is on while there is a sink
+    pub fn set_audio(&mut self, audio: Option<Box<dyn AudioSink>>) {
+        self.audio = audio;
+    }
+
+    fn frame_time(&self) -> f64 {
+        1.0 / self.frame_rate
+    }
+
+    // Waits until the next frame is due and says what to do then. While paused it waits a frame
+    // at a time, so that it notices being resumed or stopped.
+    pub fn wait_for_frame(&mut self) -> Pace {
+        let mut controls = self.handle.controls();
+        if controls.stop {
+            // Running again starts afresh
+            controls.stop = false;
+            self.deadline = None;
+            return Pace::Stop;
+        }
+        if controls.paused {
+            // Pacing starts over once resumed, the time spent paused is not caught up on
+            self.deadline = None;
+            if controls.frames_to_advance > 0 {
+                controls.frames_to_advance -= 1;
+                return Pace::Frame;
+            }
+            drop(controls);
+            self.clock.sleep(Duration::from_secs_f64(self.frame_time()));
+            return Pace::Paused;
+        }
+        let speed = controls.speed;
+        drop(controls);
+
+        let frame_time = match speed {
+            Speed::Multiplier(multiplier) => self.frame_time() / multiplier,
+            Speed::Unthrottled => 0.0,
+        };
+        let now = self.clock.now();
+        let due = match self.deadline {
+            Some(due) if now.saturating_sub(due).as_secs_f64() <= frame_time * MAX_LATE_FRAMES => {
+                due
+            }
+            _ => now,
+        };
+        if due > now {
+            self.clock.sleep(due - now);
+        }
+        let next = due.as_secs_f64() + frame_time + self.audio_correction(speed);
+        self.deadline = Some(Duration::from_secs_f64(next.max(0.0)));
+        Pace::Frame
+    }
+
+    // Nudges the next deadline so that the queued audio stays around the target latency: more
+    // queued than that and the frame takes a little longer, less and it comes a little sooner
+    fn audio_correction(&self, speed: Speed) -> f64 {
+        match &self.audio {
+            Some(audio) if speed == Speed::REAL_TIME => {
+                let queued = audio.queued() as f64 / (2 * SAMPLE_RATE) as f64;
+                let target = AUDIO_LATENCY_FRAMES * self.frame_time();
+                let limit = MAX_AUDIO_CORRECTION * self.frame_time();
+                (queued - target).clamp(-limit, limit)
+            }
+            _ => 0.0,
+        }
+    }
+
+    // The samples of the frame that just ran. Only real time sound is played, at any other
+    // speed it would not keep up with the picture.
+    pub fn play(&mut self, samples: &[i16]) {
+        if let Some(audio) = &mut self.audio
+            && self.handle.speed() == Speed::REAL_TIME
+        {
+            audio.play(samples);
+        }
+    }
+}
+
+// Emulates frames as the pacer says until its handle stops it or the console fails
+pub fn run<C: Console + ?Sized>(
+    console: &mut C,
+    pacer: &mut FramePacer,
+) -> Result<(), Box<dyn Error>> {
+    loop {
+        match pacer.wait_for_frame() {
+            Pace::Frame => {
+                console.step_frame()?;
+                let samples = console.audio_samples();
+                pacer.play(&samples);
+            }
+            Pace::Paused => {}
+            Pace::Stop => return Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    use rstest::rstest;
+
+    use super::{AudioSink, Clock, FramePacer, Pace, PacerHandle, Speed};
+
+    const FRAME_RATE: f64 = 50.0;
+
+    // Only moves when slept on, or when a test moves it
+    #[derive(Clone, Default)]
+    struct FakeClock {
+        now: Rc<Cell<Duration>>,
+        slept: Rc<RefCell<Vec<Duration>>>,
+    }
+
+    impl FakeClock {
+        fn advance(&self, duration: Duration) {
+            self.now.set(self.now.get() + duration);
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> Duration {
+            self.now.get()
+        }
+
+        fn sleep(&mut self, duration: Duration) {
+            self.slept.borrow_mut().push(duration);
+            self.advance(duration);
+        }
+    }
+
+    struct FakeAudio {
+        queued: Rc<Cell<usize>>,
+        played: Rc<Cell<usize>>,
+    }
+
+    impl AudioSink for FakeAudio {
+        fn play(&mut self, samples: &[i16]) {
+            self.played.set(self.played.get() + samples.len());
+        }
+
+        fn queued(&self) -> usize {
+            self.queued.get()
+        }
+    }
+
+    fn setup() -> (FramePacer, FakeClock, PacerHandle) {
+        let clock = FakeClock::default();
+        let pacer = FramePacer::with_clock(FRAME_RATE, Box::new(clock.clone()));
+        let handle = pacer.handle();
+        (pacer, clock, handle)
+    }
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn run_frames(pacer: &mut FramePacer, frames: usize) {
+        for _ in 0..frames {
+            assert_eq!(pacer.wait_for_frame(), Pace::Frame);
+        }
+    }
+
+    #[rstest]
+    #[case(Speed::REAL_TIME, ms(1000))]
+    #[case(Speed::Multiplier(2.0), ms(500))]
+    #[case(Speed::Multiplier(0.5), ms(2000))]
+    #[case(Speed::Unthrottled, ms(0))]
+    fn test_speed(#[case] speed: Speed, #[case] expected: Duration) {
+        let (mut pacer, clock, handle) = setup();
+        handle.set_speed(speed);
+        // The first frame is due right away, the 51st one after 50 frames' time
+        run_frames(&mut pacer, 51);
+        let error = clock.now().abs_diff(expected);
+        assert!(error < Duration::from_micros(10), "{:?}", clock.now());
+    }
+
+    #[test]
+    fn test_time_spent_emulating_is_not_slept() {
+        let (mut pacer, clock, _) = setup();
+        run_frames(&mut pacer, 1);
+        clock.advance(ms(15));
+        run_frames(&mut pacer, 1);
+        assert_eq!(*clock.slept.borrow(), vec![ms(5)]);
+    }
+
+    #[rstest]
+    // Late frames are caught up on
+    #[case(ms(60), 2)]
+    // Too late and the pacer starts over from now
+    #[case(ms(200), 0)]
+    fn test_falling_behind(#[case] late: Duration, #[case] expected_without_sleep: usize) {
+        let (mut pacer, clock, _) = setup();
+        run_frames(&mut pacer, 1);
+        clock.advance(late);
+        run_frames(&mut pacer, 1);
+
+        let mut without_sleep = 0;
+        while clock.slept.borrow().is_empty() {
+            run_frames(&mut pacer, 1);
+            without_sleep += 1;
+        }
+        assert_eq!(without_sleep - 1, expected_without_sleep);
+    }
+
+    #[test]
+    fn test_pause_and_frame_advance() {
+        let (mut pacer, clock, handle) = setup();
+        run_frames(&mut pacer, 1);
+
+        handle.pause();
+        assert_eq!(pacer.wait_for_frame(), Pace::Paused);
+        assert_eq!(pacer.wait_for_frame(), Pace::Paused);
+        assert!(handle.is_paused());
+
+        handle.advance_frame();
+        handle.advance_frame();
+        let before = clock.now();
+        run_frames(&mut pacer, 2);
+        assert_eq!(clock.now(), before);
+        assert_eq!(pacer.wait_for_frame(), Pace::Paused);
+
+        // Pacing starts over, the time spent paused is not caught up on
+        handle.resume();
+        let before = clock.now();
+        run_frames(&mut pacer, 2);
+        assert_eq!(clock.now() - before, ms(20));
+    }
+
+    #[test]
+    fn test_advance_frame_only_while_paused() {
+        let (mut pacer, _, handle) = setup();
+        handle.advance_frame();
+        handle.pause();
+        assert_eq!(pacer.wait_for_frame(), Pace::Paused);
+    }
+
+    #[test]
+    fn test_stop() {
+        let (mut pacer, _, handle) = setup();
+        handle.pause();
+        handle.stop();
+        assert_eq!(pacer.wait_for_frame(), Pace::Stop);
+    }
+
+    #[rstest]
+    // Right on the latency of 3 frames of stereo samples
+    #[case(3 * 2 * 960, ms(20))]
+    // A frame more than that only slows down by an eighth of a frame
+    #[case(4 * 2 * 960, Duration::from_micros(22500))]
+    #[case(0, Duration::from_micros(17500))]
+    fn test_audio_sync(#[case] queued: usize, #[case] expected: Duration) {
+        let (mut pacer, clock, _) = setup();
+        let queued = Rc::new(Cell::new(queued));
+        pacer.set_audio(Some(Box::new(FakeAudio {
+            queued: queued.clone(),
+            played: Rc::default(),
+        })));
+        run_frames(&mut pacer, 2);
+        assert!(clock.now().abs_diff(expected) < Duration::from_micros(10));
+    }
+
+    #[rstest]
+    #[case(Speed::REAL_TIME, 4)]
+    #[case(Speed::Multiplier(2.0), 0)]
+    #[case(Speed::Unthrottled, 0)]
+    fn test_audio_only_plays_at_real_time(#[case] speed: Speed, #[case] expected: usize) {
+        let (mut pacer, _, handle) = setup();
+        let played = Rc::new(Cell::new(0));
+        pacer.set_audio(Some(Box::new(FakeAudio {
+            queued: Rc::default(),
+            played: played.clone(),
+        })));
+        handle.set_speed(speed);
+        pacer.play(&[1, 2, 3, 4]);
+        assert_eq!(played.get(), expected);
+    }
+
+    #[rstest]
+    #[case("1", Ok(Speed::REAL_TIME))]
+    #[case("0.25", Ok(Speed::Multiplier(0.25)))]
+    #[case("max", Ok(Speed::Unthrottled))]
+    #[case("0", Err(()))]
+    #[case("-2", Err(()))]
+    #[case("fast", Err(()))]
+    fn test_parse_speed(#[case] input: &str, #[case] expected: Result<Speed, ()>) {
+        assert_eq!(input.parse::<Speed>().map_err(|_| ()), expected);
+    }
+}
@@ -0,0 +1,200 @@
+use std::error::Error;
+use std::fs;
+
+use super::cartridge::{Cartridge, CartridgeNotFoundError};
+use super::console::{Console, NoConsolePresentError};
+
+// A console the emulator can run, along with the cartridges it takes. The backend that loaded a
+// cartridge is the one that knows its concrete type.
+pub trait ConsoleBackend {
+    fn name(&self) -> &'static str;
+    // File name suffixes of the roms for the console, without the dot
+    fn suffixes(&self) -> &[&'static str];
+    fn load_cartridge(
+        &self,
+        name: &str,
+        data: Vec<u8>,
+    ) -> Result<Box<dyn Cartridge>, Box<dyn Error>>;
+    fn accepts(&self, cartridge: &dyn Cartridge) -> bool;
+    // Only called with cartridges the backend accepts
+    fn create_console(
+        &self,
+        cartridge: Box<dyn Cartridge>,
+    ) -> Result<Box<dyn Console>, Box<dyn Error>>;
+}
+
+// The consoles to pick from when a rom comes in, the first one to take it wins
+pub struct ConsoleRegistry {
+    backends: Vec<Box<dyn ConsoleBackend>>,
+}
+
+impl ConsoleRegistry {
+    // A registry without any console, see Default for the ones that are compiled in
+    pub fn new() -> ConsoleRegistry {
+        ConsoleRegistry { backends: vec![] }
+    }
+
+    pub fn register(&mut self, backend: Box<dyn ConsoleBackend>) {
+        self.backends.push(backend);
+    }
+
+    pub fn backends(&self) -> impl Iterator<Item = &dyn ConsoleBackend> {
+        self.backends.iter().map(|backend| backend.as_ref())
+    }
+
+    fn backend_for_rom(&self, name: &str) -> Result<&dyn ConsoleBackend, Box<dyn Error>> {
+        let Some(i) = name.rfind(".") else {
+            return Err(Box::new(CartridgeNotFoundError {
+                what: "Invalid path: ".to_string() + name,
+            }));
+        };
+        let suffix = &name[i + 1..];
+        self.backends()
+            .find(|backend| backend.suffixes().contains(&suffix))
+            .ok_or_else(|| {
+                Box::new(CartridgeNotFoundError {
+                    what: "Suffix unknown: ".to_string() + suffix,
+                })
+                .into()
+            })
+    }
+
+    pub fn load_cartridge(&self, path: &str) -> Result<Box<dyn Cartridge>, Box<dyn Error>> {
+        let backend = self.backend_for_rom(path)?;
+        let data = fs::read(path).map_err(|e| CartridgeNotFoundError {
+            what: format!("Failed to open file: {e}"),
+        })?;
+        backend.load_cartridge(path, data)
+    }
+
+    // The name only picks the console, the rom does not have to exist as a file
+    pub fn cartridge_from_bytes(
+        &self,
+        name: &str,
+        data: Vec<u8>,
+    ) -> Result<Box<dyn Cartridge>, Box<dyn Error>> {
+        self.backend_for_rom(name)?.load_cartridge(name, data)
+    }
+
+    pub fn create_console_for(
+        &self,
+        cartridge: Box<dyn Cartridge>,
+    ) -> Result<Box<dyn Console>, Box<dyn Error>> {
+        match self
+            .backends()
+            .find(|backend| backend.accepts(cartridge.as_ref()))
+        {
+            Some(backend) => backend.create_console(cartridge),
+            None => Err(Box::new(NoConsolePresentError {
+                what: String::from("No Console present for the rom provided"),
+            })),
+        }
+    }
+
+    pub fn create_console(&self, path: &str) -> Result<Box<dyn Console>, Box<dyn Error>> {
+        self.create_console_for(self.load_cartridge(path)?)
+    }
+
+    pub fn create_console_from_bytes(
+        &self,
+        name: &str,
+        data: Vec<u8>,
+    ) -> Result<Box<dyn Console>, Box<dyn Error>> {
+        self.create_console_for(self.cartridge_from_bytes(name, data)?)
+    }
+}
+
+// Every console the crate was built with
+impl Default for ConsoleRegistry {
+    fn default() -> Self {
+        #[allow(unused_mut)]
+        let mut registry = ConsoleRegistry::new();
+        #[cfg(feature = "gameboy")]
+        registry.register(Box::new(super::gameboy::backend::GameBoyBackend));
+        registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use rstest::rstest;
+
+    use super::{ConsoleBackend, ConsoleRegistry};
+    use crate::consoles::cartridge::Cartridge;
+    use crate::consoles::console::Console;
+    use crate::consoles::fake_cartridge::FakeCartridge;
+
+    // Takes .fake roms but cannot run them
+    struct FakeBackend;
+
+    impl ConsoleBackend for FakeBackend {
+        fn name(&self) -> &'static str {
+            "fake"
+        }
+
+        fn suffixes(&self) -> &[&'static str] {
+            &["fake"]
+        }
+
+        fn load_cartridge(
+            &self,
+            _name: &str,
+            _data: Vec<u8>,
+        ) -> Result<Box<dyn Cartridge>, Box<dyn Error>> {
+            Ok(Box::new(FakeCartridge::new()))
+        }
+
+        fn accepts(&self, cartridge: &dyn Cartridge) -> bool {
+            cartridge.as_any().is::<FakeCartridge>()
+        }
+
+        fn create_console(
+            &self,
+            _cartridge: Box<dyn Cartridge>,
+        ) -> Result<Box<dyn Console>, Box<dyn Error>> {
+            Err("the fake console does not run".into())
+        }
+    }
+
+    fn registry() -> ConsoleRegistry {
+        let mut registry = ConsoleRegistry::new();
+        registry.register(Box::new(FakeBackend));
+        registry
+    }
+
+    #[rstest]
+    #[case("test.fake")]
+    #[case("roms/v1.2/test.fake")]
+    fn test_backend_for_rom(#[case] name: &str) {
+        assert_eq!(registry().backend_for_rom(name).unwrap().name(), "fake");
+    }
+
+    #[rstest]
+    #[case("tetris.nes", "Suffix unknown: nes")]
+    #[case("tetris", "Invalid path: tetris")]
+    fn test_unknown_rom(#[case] name: &str, #[case] expected: &str) {
+        let error = registry().backend_for_rom(name).err().unwrap();
+        assert_eq!(error.to_string(), expected);
+    }
+
+    #[test]
+    fn test_cartridge_goes_to_its_backend() {
+        let registry = registry();
+        let cartridge = registry.cartridge_from_bytes("test.fake", vec![]).unwrap();
+        assert!(cartridge.as_any().is::<FakeCartridge>());
+
+        let error = registry.create_console_for(cartridge).err().unwrap();
+        assert_eq!(error.to_string(), "the fake console does not run");
+    }
+
+    #[test]
+    fn test_no_backend_accepts() {
+        let error = ConsoleRegistry::new()
+            .create_console_for(Box::new(FakeCartridge::new()))
+            .err()
+            .unwrap();
+        assert_eq!(error.to_string(), "No Console present for the rom provided");
+    }
+}
@@ -0,0 +1,204 @@
+use std::error::Error;
+use std::fmt::Display;
+
+// Save states hold the state of every device of a console one after the other, in the order
+// the console saves them. There are no names or lengths in between, so a state only loads into
+// the same kind of console it was saved from, which the header at the start checks.
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum StateError {
+    // The state was saved by another console or another version of the format
+    WrongHeader,
+    // The state ends before every device has been loaded
+    UnexpectedEnd,
+    // There is more in the state than the devices loaded
+    TrailingBytes(usize),
+    // A value no device could have saved
+    Invalid(String),
+}
+
+impl Display for StateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StateError::WrongHeader => write!(f, "Not a save state of this console"),
+            StateError::UnexpectedEnd => write!(f, "Save state ends early"),
+            StateError::TrailingBytes(count) => {
+                write!(f, "Save state has {count} bytes left over")
+            }
+            StateError::Invalid(what) => write!(f, "Invalid save state: {what}"),
+        }
+    }
+}
+
+impl Error for StateError {}
+
+#[derive(Debug, Default)]
+pub struct StateWriter {
+    bytes: Vec<u8>,
+}
+
+impl StateWriter {
+    pub fn new(header: &[u8]) -> StateWriter {
+        StateWriter {
+            bytes: header.to_vec(),
+        }
+    }
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    pub fn write_bool(&mut self, value: bool) {
+        self.write_u8(value as u8);
+    }
+
+    pub fn write_u16(&mut self, value: u16) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+#[derive(Debug)]
+pub struct StateReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> StateReader<'a> {
+    pub fn new(bytes: &'a [u8], header: &[u8]) -> Result<StateReader<'a>, StateError> {
+        match bytes.strip_prefix(header) {
+            Some(bytes) => Ok(StateReader { bytes }),
+            None => Err(StateError::WrongHeader),
+        }
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, StateError> {
+        Ok(self.read_array::<1>()?[0])
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool, StateError> {
+        match self.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            value => Err(StateError::Invalid(format!("{value} is not a bool"))),
+        }
+    }
+
+    pub fn read_u16(&mut self) -> Result<u16, StateError> {
+        Ok(u16::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, StateError> {
+        Ok(u32::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, StateError> {
+        Ok(u64::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_bytes(&mut self, into: &mut [u8]) -> Result<(), StateError> {
+        if self.bytes.len() < into.len() {
+            return Err(StateError::UnexpectedEnd);
+        }
+        let (bytes, rest) = self.bytes.split_at(into.len());
+        into.copy_from_slice(bytes);
+        self.bytes = rest;
+        Ok(())
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], StateError> {
+        let mut array = [0; N];
+        self.read_bytes(&mut array)?;
+        Ok(array)
+    }
+
+    // Every device has been loaded, nothing may be left
+    pub fn finish(self) -> Result<(), StateError> {
+        match self.bytes.len() {
+            0 => Ok(()),
+            count => Err(StateError::TrailingBytes(count)),
+        }
+    }
+}
+
+// A device that can be written to and restored from a save state. Loading reads back exactly
+// what saving wrote.
+pub trait SaveState {
+    fn save_state(&self, out: &mut StateWriter);
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), StateError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::{StateError, StateReader, StateWriter};
+
+    const HEADER: &[u8] = b"TEST";
+
+    #[test]
+    fn test_round_trip() {
+        let mut out = StateWriter::new(HEADER);
+        out.write_u8(0x12);
+        out.write_bool(true);
+        out.write_u16(0x3456);
+        out.write_u32(0x789A_BCDE);
+        out.write_u64(u64::MAX - 1);
+        out.write_bytes(&[1, 2, 3]);
+        let bytes = out.into_bytes();
+
+        let mut state = StateReader::new(&bytes, HEADER).unwrap();
+        assert_eq!(state.read_u8(), Ok(0x12));
+        assert_eq!(state.read_bool(), Ok(true));
+        assert_eq!(state.read_u16(), Ok(0x3456));
+        assert_eq!(state.read_u32(), Ok(0x789A_BCDE));
+        assert_eq!(state.read_u64(), Ok(u64::MAX - 1));
+        let mut bytes = [0; 3];
+        assert_eq!(state.read_bytes(&mut bytes), Ok(()));
+        assert_eq!(bytes, [1, 2, 3]);
+        assert_eq!(state.finish(), Ok(()));
+    }
+
+    #[rstest]
+    #[case(b"TES")]
+    #[case(b"GBST\x00")]
+    fn test_wrong_header(#[case] bytes: &[u8]) {
+        assert_eq!(
+            StateReader::new(bytes, HEADER).unwrap_err(),
+            StateError::WrongHeader
+        );
+    }
+
+    #[test]
+    fn test_unexpected_end() {
+        let mut state = StateReader::new(b"TEST\x01", HEADER).unwrap();
+        assert_eq!(state.read_u16(), Err(StateError::UnexpectedEnd));
+    }
+
+    #[test]
+    fn test_invalid_bool() {
+        let mut state = StateReader::new(b"TEST\x02", HEADER).unwrap();
+        assert!(matches!(state.read_bool(), Err(StateError::Invalid(_))));
+    }
+
+    #[test]
+    fn test_trailing_bytes() {
+        let mut state = StateReader::new(b"TEST\x01\x02", HEADER).unwrap();
+        state.read_u8().unwrap();
+        assert_eq!(state.finish(), Err(StateError::TrailingBytes(1)));
+    }
+}
@@ -1,3 +1,6 @@
+// Save file lookup for when cartridge ram can be persisted, nothing calls it yet
+#![allow(dead_code)]
+
 use std::{
     error::Error,
     fmt::Display,
@@ -32,8 +35,8 @@ fn get_newest_save_file(console_name: &str, game_name: &str) -> Result<File, Box
     let dir_path = format!("saves/{console_name}/{game_name}");
     let dir = fs::read_dir(dir_path.clone())?;
     dir.into_iter()
-        .flat_map(|x| x)
-        .filter(|f| f.metadata().map_or(false, |f| f.is_dir()))
+        .flatten()
+        .filter(|f| f.metadata().is_ok_and(|f| f.is_dir()))
         .max_by(|a, b| {
             a.metadata()
                 .unwrap()
@@ -64,7 +67,7 @@ fn get_files_for_game(console_name: &str, game_name: &str) -> Vec<File> {
         Ok(dir) => dir
             .filter(|f| {
                 f.as_ref()
-                    .map_or(false, |f| f.metadata().map_or(false, |f| f.is_file()))
+                    .is_ok_and(|f| f.metadata().is_ok_and(|f| f.is_file()))
             })
             .flat_map(|f| f.and_then(|f| File::open(f.path())))
             .collect(),
@@ -79,7 +82,7 @@ fn get_dirs(console_name: &str) -> Vec<String> {
         Ok(dir) => dir
             .filter(|x| {
                 x.as_ref()
-                    .map_or(false, |f| f.metadata().map_or(false, |f| f.is_dir()))
+                    .is_ok_and(|f| f.metadata().is_ok_and(|f| f.is_dir()))
             })
             .flat_map(|x| x.map(|x| x.path().to_str().map(|x| x.to_string())).unwrap())
             .collect(),
@@ -0,0 +1,18 @@
+#[macro_use]
+extern crate lazy_static;
+
+pub mod consoles;
+pub mod filio;
+pub mod logging;
+pub mod macros;
+pub mod utils;
+
+// What embedding the emulator takes, everything else is reachable through consoles
+pub use consoles::StrictMode;
+pub use consoles::console::{
+    Buttons, Console, Frame, SAMPLE_RATE, create_console_for, create_console_from_bytes,
+};
+#[cfg(feature = "gameboy")]
+pub use consoles::gameboy::game_boy::GameBoy;
+pub use consoles::pacing::{AudioSink, FramePacer, PacerHandle, Speed};
+pub use consoles::registry::{ConsoleBackend, ConsoleRegistry};
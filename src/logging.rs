@@ -0,0 +1,222 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+// Runtime filter for the log! macro. Every category has its own level, anything more verbose is
+// dropped. Building without the logging feature compiles the log! calls out altogether.
+
+pub const ENABLED: bool = cfg!(feature = "logging");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    const ALL: [Level; 6] = [
+        Level::Off,
+        Level::Error,
+        Level::Warn,
+        Level::Info,
+        Level::Debug,
+        Level::Trace,
+    ];
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Level::Off => "off",
+            Level::Error => "error",
+            Level::Warn => "warn",
+            Level::Info => "info",
+            Level::Debug => "debug",
+            Level::Trace => "trace",
+        };
+        f.pad(name)
+    }
+}
+
+impl FromStr for Level {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Level::ALL
+            .into_iter()
+            .find(|level| level.to_string() == s)
+            .ok_or_else(|| {
+                format!("Unknown log level {s}, expected off, error, warn, info, debug or trace")
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    Cpu,
+    Bus,
+    Ppu,
+    Apu,
+    Mbc,
+}
+
+impl Category {
+    pub const ALL: [Category; 5] = [
+        Category::Cpu,
+        Category::Bus,
+        Category::Ppu,
+        Category::Apu,
+        Category::Mbc,
+    ];
+}
+
+impl Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Category::Cpu => "cpu",
+            Category::Bus => "bus",
+            Category::Ppu => "ppu",
+            Category::Apu => "apu",
+            Category::Mbc => "mbc",
+        };
+        f.pad(name)
+    }
+}
+
+impl FromStr for Category {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Category::ALL
+            .into_iter()
+            .find(|category| category.to_string() == s)
+            .ok_or_else(|| format!("Unknown log category {s}, expected cpu, bus, ppu, apu or mbc"))
+    }
+}
+
+// The level of every category, indexed by the category
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogFilter {
+    levels: [Level; Category::ALL.len()],
+}
+
+impl LogFilter {
+    pub fn new(level: Level) -> LogFilter {
+        LogFilter {
+            levels: [level; Category::ALL.len()],
+        }
+    }
+
+    pub fn set(&mut self, category: Category, level: Level) {
+        self.levels[category as usize] = level;
+    }
+
+    pub fn allows(&self, category: Category, level: Level) -> bool {
+        level != Level::Off && level <= self.levels[category as usize]
+    }
+}
+
+// Only errors and warnings get through until told otherwise
+impl Default for LogFilter {
+    fn default() -> Self {
+        LogFilter::new(Level::Warn)
+    }
+}
+
+// A comma separated list of category=level, where all stands for every category and a category
+// on its own logs everything, e.g. all=info,cpu=trace. Later entries win.
+impl FromStr for LogFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut filter = LogFilter::default();
+        for entry in s
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+        {
+            let (category, level) = match entry.split_once('=') {
+                Some((category, level)) => (category, level.parse()?),
+                None => (entry, Level::Trace),
+            };
+            match category {
+                "all" => filter = LogFilter::new(level),
+                category => filter.set(category.parse()?, level),
+            }
+        }
+        Ok(filter)
+    }
+}
+
+static LEVELS: [AtomicU8; Category::ALL.len()] =
+    [const { AtomicU8::new(Level::Warn as u8) }; Category::ALL.len()];
+
+// Makes the filter the one the log! macro goes by
+pub fn install(filter: &LogFilter) {
+    for (level, installed) in filter.levels.iter().zip(&LEVELS) {
+        installed.store(*level as u8, Ordering::Relaxed);
+    }
+}
+
+pub fn enabled(category: Category, level: Level) -> bool {
+    level != Level::Off && level as u8 <= LEVELS[category as usize].load(Ordering::Relaxed)
+}
+
+pub fn write(category: Category, level: Level, file: &str, line: u32, message: fmt::Arguments) {
+    eprintln!("[{level:5} {category}] {file}:{line} {message}");
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::{Category, Level, LogFilter};
+
+    #[rstest]
+    #[case("error", Level::Error)]
+    #[case("trace", Level::Trace)]
+    #[case("off", Level::Off)]
+    fn test_parse_level(#[case] input: &str, #[case] expected: Level) {
+        assert_eq!(input.parse::<Level>(), Ok(expected));
+    }
+
+    #[rstest]
+    #[case("mbc", Category::Mbc)]
+    #[case("ppu", Category::Ppu)]
+    fn test_parse_category(#[case] input: &str, #[case] expected: Category) {
+        assert_eq!(input.parse::<Category>(), Ok(expected));
+    }
+
+    #[rstest]
+    #[case("", Category::Cpu, Level::Warn, true)]
+    #[case("", Category::Cpu, Level::Info, false)]
+    #[case("cpu=debug", Category::Cpu, Level::Debug, true)]
+    #[case("cpu=debug", Category::Cpu, Level::Trace, false)]
+    #[case("cpu=debug", Category::Bus, Level::Debug, false)]
+    #[case("bus", Category::Bus, Level::Trace, true)]
+    #[case("all=trace,bus=off", Category::Bus, Level::Error, false)]
+    #[case("all=trace,bus=off", Category::Apu, Level::Trace, true)]
+    #[case("cpu=trace, all=error", Category::Cpu, Level::Warn, false)]
+    #[case("all=trace", Category::Mbc, Level::Off, false)]
+    fn test_filter(
+        #[case] spec: &str,
+        #[case] category: Category,
+        #[case] level: Level,
+        #[case] expected: bool,
+    ) {
+        let filter: LogFilter = spec.parse().unwrap();
+        assert_eq!(filter.allows(category, level), expected);
+    }
+
+    #[rstest]
+    #[case("gpu=trace")]
+    #[case("cpu=loud")]
+    #[case("cpu=")]
+    fn test_filter_rejects(#[case] spec: &str) {
+        assert!(spec.parse::<LogFilter>().is_err());
+    }
+}
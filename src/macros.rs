@@ -1,26 +1,25 @@
-use crate::function_name;
-
+// log!(Cpu, Debug, "pc: {pc:04X}") prints the message when the level of the category allows it
+#[cfg(feature = "logging")]
 #[macro_export]
-macro_rules! trace {
-    ($e: expr) => {{
-        println!("{} {}: {}", line!(), file!(), stringify!($e));
-        $e
+macro_rules! log {
+    ($category: ident, $level: ident, $($arg: tt)+) => {{
+        let category = $crate::logging::Category::$category;
+        let level = $crate::logging::Level::$level;
+        if $crate::logging::enabled(category, level) {
+            $crate::logging::write(category, level, file!(), line!(), format_args!($($arg)+));
+        }
     }};
 }
 
+// Without the feature the arguments are still checked but nothing is left to run
+#[cfg(not(feature = "logging"))]
 #[macro_export]
 macro_rules! log {
-    () => {{
-        println!("{} {} => : {}", line!(), file!(), function_name!())
-    }};
-    ($e: expr) => {{
-        println!(
-            "{} {} => : {} -> {}",
-            line!(),
-            file!(),
-            function_name!(),
-            format!($e)
-        )
+    ($category: ident, $level: ident, $($arg: tt)+) => {{
+        let _ = ($crate::logging::Category::$category, $crate::logging::Level::$level);
+        if false {
+            let _ = format_args!($($arg)+);
+        }
     }};
 }
 
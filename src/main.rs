@@ -1,31 +1,205 @@
-#[macro_use]
-extern crate lazy_static;
+// The headless frontend, everything it runs comes from the library
+use std::error::Error;
+use std::fs::File;
+#[cfg(feature = "debugger")]
+use std::io::BufReader;
+use std::io::BufWriter;
+#[cfg(feature = "debugger")]
+use std::path::Path;
 
-#[macro_use]
-extern crate function_name;
+use gb::consoles::StrictMode;
+use gb::consoles::console::Console;
+#[cfg(feature = "debugger")]
+use gb::consoles::gameboy::conformance::ConformanceReport;
+use gb::consoles::gameboy::execution::{CYCLES_PER_SECOND, emulated_mhz};
+use gb::consoles::gameboy::game_boy::GameBoy;
+use gb::consoles::gameboy::gbcartridge::GbCartridge;
+#[cfg(feature = "debugger")]
+use gb::consoles::gameboy::single_step::SingleStepReport;
+#[cfg(feature = "debugger")]
+use gb::consoles::gameboy::trace_diff::diff_trace;
+use gb::consoles::pacing::{FramePacer, Speed};
+use gb::consoles::registry::ConsoleRegistry;
+use gb::logging::{self, LogFilter};
 
-pub mod consoles;
-pub mod filio;
-pub mod macros;
-pub mod utils;
+fn init_console_and_cartridge(path: &str) -> Result<Box<dyn Console>, Box<dyn Error>> {
+    let registry = ConsoleRegistry::default();
+    let cartridge = registry.load_cartridge(path)?;
+    // println!("{}", cartridge.dump());
+    registry.create_console_for(cartridge)
+}
 
-use std::error::Error;
+// trace-diff <rom> <reference log> [context]
+#[cfg(feature = "debugger")]
+fn run_trace_diff(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let (rom, reference) = match args {
+        [rom, reference, ..] => (rom, reference),
+        _ => return Err("Usage: trace-diff <rom> <reference log> [context]".into()),
+    };
+    let context = match args.get(2) {
+        Some(context) => context.parse()?,
+        None => 16,
+    };
 
-use consoles::cartridge::{Cartridge, create_catridge};
-use consoles::console::{Console, create_console_for};
+    let mut gameboy = GameBoy::new(GbCartridge::new(rom)?);
+    let reference = BufReader::new(File::open(reference)?);
+    println!("{}", diff_trace(&mut gameboy, reference, context)?);
+    Ok(())
+}
 
-fn init_console_and_cartridge(path: &str) -> Result<impl Console, Box<dyn Error>> {
-    let cartridge = create_catridge(path)?;
-    // println!("{}", cartridge.dump());
-    create_console_for(cartridge)
+// conformance <rom directory> [emulated seconds per rom]
+#[cfg(feature = "debugger")]
+fn run_conformance(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let dir = match args.first() {
+        Some(dir) => dir,
+        None => return Err("Usage: conformance <rom directory> [seconds]".into()),
+    };
+    let seconds: u64 = match args.get(1) {
+        Some(seconds) => seconds.parse()?,
+        None => 30,
+    };
+
+    let report = ConformanceReport::run(Path::new(dir), seconds * CYCLES_PER_SECOND)?;
+    println!("{report}");
+    Ok(())
+}
+
+// single-step <json directory>
+#[cfg(feature = "debugger")]
+fn run_single_step(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let dir = match args.first() {
+        Some(dir) => dir,
+        None => return Err("Usage: single-step <json directory>".into()),
+    };
+
+    let report = SingleStepReport::run(Path::new(dir))?;
+    println!("{report}");
+    Ok(())
+}
+
+// bench <rom> [emulated seconds], the step by step interpreter against the block cache
+fn run_bench(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let rom = match args.first() {
+        Some(rom) => rom,
+        None => return Err("Usage: bench <rom> [seconds]".into()),
+    };
+    let seconds: u64 = match args.get(1) {
+        Some(seconds) => seconds.parse()?,
+        None => 10,
+    };
+
+    let mut results = vec![];
+    for (mode, block_cache) in [("interpreter", false), ("block cache", true)] {
+        let mut gameboy = GameBoy::new(GbCartridge::new(rom)?);
+        gameboy.set_block_cache(block_cache);
+        results.push((
+            mode,
+            emulated_mhz(&mut gameboy, seconds * CYCLES_PER_SECOND),
+        ));
+    }
+
+    let real_time = CYCLES_PER_SECOND as f64 / 1_000_000.0;
+    for (mode, mhz) in results {
+        println!(
+            "{mode:12} {mhz:8.2} MHz  {:6.1}x real time",
+            mhz / real_time
+        );
+    }
+    Ok(())
+}
+
+// --log <category=level,...> goes before everything else, so that it works for every command
+fn take_log_filter(args: &mut Vec<String>) -> Result<(), String> {
+    let Some(index) = args.iter().position(|arg| arg == "--log") else {
+        return Ok(());
+    };
+    let spec = match args.get(index + 1) {
+        Some(spec) => spec.clone(),
+        None => return Err("Usage: --log <category=level,...>".into()),
+    };
+    args.drain(index..=index + 1);
+
+    if !logging::ENABLED {
+        println!("Logging was left out of this build, ignoring --log {spec}");
+        return Ok(());
+    }
+    logging::install(&spec.parse::<LogFilter>()?);
+    Ok(())
 }
 
 fn main() {
-    let path = "roms/Pokemon-Silver.gbc";
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    if let Err(e) = take_log_filter(&mut args) {
+        println!("{e}");
+        return;
+    }
+
+    let result = match args.first().map(String::as_str) {
+        #[cfg(feature = "debugger")]
+        Some("trace-diff") => Some(run_trace_diff(&args[1..])),
+        #[cfg(feature = "debugger")]
+        Some("conformance") => Some(run_conformance(&args[1..])),
+        #[cfg(feature = "debugger")]
+        Some("single-step") => Some(run_single_step(&args[1..])),
+        Some("bench") => Some(run_bench(&args[1..])),
+        _ => None,
+    };
+    if let Some(result) = result {
+        if let Err(e) = result {
+            println!("{e}");
+        }
+        return;
+    }
+
+    let mut path = String::from("roms/Pokemon-Silver.gbc");
+    let mut trace_path = None;
+    let mut strict_mode = StrictMode::Off;
+    let mut speed = Speed::REAL_TIME;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--trace" => trace_path = args.next(),
+            "--strict-bus" => match args.next().map(|mode| mode.parse()) {
+                Some(Ok(mode)) => strict_mode = mode,
+                Some(Err(e)) => {
+                    println!("{e}");
+                    return;
+                }
+                None => {
+                    println!("Usage: --strict-bus <off|log|break>");
+                    return;
+                }
+            },
+            "--speed" => match args.next().map(|speed| speed.parse()) {
+                Some(Ok(parsed)) => speed = parsed,
+                Some(Err(e)) => {
+                    println!("{e}");
+                    return;
+                }
+                None => {
+                    println!("Usage: --speed <multiplier|max>");
+                    return;
+                }
+            },
+            _ => path = arg,
+        }
+    }
 
-    match init_console_and_cartridge(path) {
+    match init_console_and_cartridge(&path) {
         Ok(mut console) => {
-            trace!(console.run());
+            console.set_strict_mode(strict_mode);
+            if let Some(trace_path) = trace_path {
+                match File::create(&trace_path) {
+                    Ok(file) => console.trace_to(Box::new(BufWriter::new(file))),
+                    Err(e) => println!("Failed to create trace file {trace_path}: {e}"),
+                }
+            }
+            let mut pacer = FramePacer::new(console.frame_rate());
+            pacer.handle().set_speed(speed);
+            if let Err(e) = console.run(&mut pacer) {
+                println!("{e}");
+            }
         }
         Err(e) => println!("{e}"),
     }
@@ -28,9 +28,9 @@ pub fn u8_as_bit_string(x: u8) -> String {
 
         let bit = x & mask;
         if bit >= 1 {
-            bits = bits + "1";
+            bits += "1";
         } else {
-            bits = bits + "0";
+            bits += "0";
         }
     }
 
@@ -44,9 +44,9 @@ pub fn u16_as_bit_string(x: u16) -> String {
 
         let bit = x & mask;
         if bit >= 1 {
-            bits = bits + "1";
+            bits += "1";
         } else {
-            bits = bits + "0";
+            bits += "0";
         }
     }
 
@@ -59,42 +59,42 @@ pub fn u8_as_hex_string(x: u8) -> String {
     let lower = x & (0b1111);
 
     match upper {
-        0 => hex = hex + "0",
-        1 => hex = hex + "1",
-        2 => hex = hex + "2",
-        3 => hex = hex + "3",
-        4 => hex = hex + "4",
-        5 => hex = hex + "5",
-        6 => hex = hex + "6",
-        7 => hex = hex + "7",
-        8 => hex = hex + "8",
-        9 => hex = hex + "9",
-        10 => hex = hex + "A",
-        11 => hex = hex + "B",
-        12 => hex = hex + "C",
-        13 => hex = hex + "D",
-        14 => hex = hex + "E",
-        15 => hex = hex + "F",
+        0 => hex += "0",
+        1 => hex += "1",
+        2 => hex += "2",
+        3 => hex += "3",
+        4 => hex += "4",
+        5 => hex += "5",
+        6 => hex += "6",
+        7 => hex += "7",
+        8 => hex += "8",
+        9 => hex += "9",
+        10 => hex += "A",
+        11 => hex += "B",
+        12 => hex += "C",
+        13 => hex += "D",
+        14 => hex += "E",
+        15 => hex += "F",
         _ => {}
     }
 
     match lower {
-        0 => hex = hex + "0",
-        1 => hex = hex + "1",
-        2 => hex = hex + "2",
-        3 => hex = hex + "3",
-        4 => hex = hex + "4",
-        5 => hex = hex + "5",
-        6 => hex = hex + "6",
-        7 => hex = hex + "7",
-        8 => hex = hex + "8",
-        9 => hex = hex + "9",
-        10 => hex = hex + "A",
-        11 => hex = hex + "B",
-        12 => hex = hex + "C",
-        13 => hex = hex + "D",
-        14 => hex = hex + "E",
-        15 => hex = hex + "F",
+        0 => hex += "0",
+        1 => hex += "1",
+        2 => hex += "2",
+        3 => hex += "3",
+        4 => hex += "4",
+        5 => hex += "5",
+        6 => hex += "6",
+        7 => hex += "7",
+        8 => hex += "8",
+        9 => hex += "9",
+        10 => hex += "A",
+        11 => hex += "B",
+        12 => hex += "C",
+        13 => hex += "D",
+        14 => hex += "E",
+        15 => hex += "F",
         _ => {}
     }
 
@@ -109,22 +109,22 @@ pub fn u16_as_hex_string(x: u16) -> String {
         let shifted = (x >> shift) & 0b1111;
 
         match shifted {
-            0 => hex = hex + "0",
-            1 => hex = hex + "1",
-            2 => hex = hex + "2",
-            3 => hex = hex + "3",
-            4 => hex = hex + "4",
-            5 => hex = hex + "5",
-            6 => hex = hex + "6",
-            7 => hex = hex + "7",
-            8 => hex = hex + "8",
-            9 => hex = hex + "9",
-            10 => hex = hex + "A",
-            11 => hex = hex + "B",
-            12 => hex = hex + "C",
-            13 => hex = hex + "D",
-            14 => hex = hex + "E",
-            15 => hex = hex + "F",
+            0 => hex += "0",
+            1 => hex += "1",
+            2 => hex += "2",
+            3 => hex += "3",
+            4 => hex += "4",
+            5 => hex += "5",
+            6 => hex += "6",
+            7 => hex += "7",
+            8 => hex += "8",
+            9 => hex += "9",
+            10 => hex += "A",
+            11 => hex += "B",
+            12 => hex += "C",
+            13 => hex += "D",
+            14 => hex += "E",
+            15 => hex += "F",
             _ => {}
         }
     }